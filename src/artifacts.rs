@@ -0,0 +1,47 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 获取到的微博列表（JSON）
+const WEIBOS_FILE: &str = "weibos.json";
+/// 已处理的微博，即检查点文件
+const PROCESSED_FILE: &str = "processed.csv";
+/// 失败记录（JSON Lines）
+const FAILURES_FILE: &str = "failures.jsonl";
+
+/// 单次运行的产物目录 `<dir>/run_<时间>/`，集中存放列表、检查点与失败记录，便于事后核对
+#[derive(Debug)]
+pub struct RunDir {
+    path: PathBuf,
+}
+
+impl RunDir {
+    /// 在 `parent` 下创建以当前本地时间命名的子目录，`parent` 不存在时一并创建
+    pub fn create(parent: &str) -> Result<Self> {
+        let name = format!("run_{}", chrono::Local::now().format("%Y%m%d_%H%M%S"));
+        let path = Path::new(parent).join(name);
+        fs::create_dir_all(parent).context(format!("无法创建输出目录: {}", parent))?;
+        fs::create_dir(&path).context(format!("无法创建本次运行的目录: {}", path.display()))?;
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn weibos_path(&self) -> String {
+        self.file(WEIBOS_FILE)
+    }
+
+    pub fn processed_path(&self) -> String {
+        self.file(PROCESSED_FILE)
+    }
+
+    pub fn failures_path(&self) -> String {
+        self.file(FAILURES_FILE)
+    }
+
+    fn file(&self, name: &str) -> String {
+        self.path.join(name).to_string_lossy().into_owned()
+    }
+}
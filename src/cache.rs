@@ -0,0 +1,225 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use weibo_hide::weibo_client::{FetchOptions, Visibility, WeiboInfo};
+
+/// 缓存文件的格式版本，格式变化时递增，旧版本的缓存视为无效
+const CACHE_VERSION: u32 = 5;
+
+/// 微博列表缓存（`--cache`），调整筛选条件反复运行时不必每次重新获取整个列表
+///
+/// 缓存记录获取时的用户 ID、时间与获取选项，三者任一不符（或超过有效期）时重新获取并覆盖。
+#[derive(Debug)]
+pub struct ListCache {
+    path: String,
+    /// 忽略已有缓存，总是重新获取
+    refresh: bool,
+    /// 有效期，超过后重新获取
+    ttl: Duration,
+}
+
+/// 缓存文件的内容
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    uid: String,
+    /// 获取完成的时间（RFC 3339）
+    fetched_at: String,
+    options: CacheKey,
+    weibos: Vec<CachedWeibo>,
+}
+
+/// 影响获取结果的选项，与缓存中记录的不同时缓存无效；每页间隔等不影响结果的选项不计入
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct CacheKey {
+    api: String,
+    feature: u32,
+    topic: Option<String>,
+    max_pages: Option<u32>,
+    max_weibos: Option<usize>,
+    expand_long_text: bool,
+    after_id: Option<u64>,
+}
+
+impl From<&FetchOptions> for CacheKey {
+    fn from(options: &FetchOptions) -> Self {
+        Self {
+            api: format!("{:?}", options.api).to_lowercase(),
+            feature: options.feature,
+            topic: options.topic.clone(),
+            max_pages: options.max_pages,
+            max_weibos: options.max_weibos,
+            expand_long_text: options.expand_long_text,
+            after_id: options.after_id,
+        }
+    }
+}
+
+/// 缓存中的一条微博；[`WeiboInfo`] 按接口字段反序列化，不能直接读回自己的序列化结果
+#[derive(Serialize, Deserialize)]
+struct CachedWeibo {
+    id: String,
+    text: Option<String>,
+    created_at: Option<String>,
+    is_long_text: bool,
+    visibility: Option<String>,
+    is_repost: bool,
+    is_top: bool,
+    reposts_count: Option<u64>,
+    comments_count: Option<u64>,
+    attitudes_count: Option<u64>,
+    has_media: bool,
+    repost_has_media: bool,
+    pic_urls: Vec<String>,
+    video_url: Option<String>,
+    source: Option<String>,
+    user_id: Option<String>,
+}
+
+impl From<&WeiboInfo> for CachedWeibo {
+    fn from(weibo: &WeiboInfo) -> Self {
+        Self {
+            id: weibo.id.clone(),
+            text: weibo.text.clone(),
+            created_at: weibo.created_at.clone(),
+            is_long_text: weibo.is_long_text,
+            visibility: weibo.visibility.map(|v| v.key().to_string()),
+            is_repost: weibo.is_repost,
+            is_top: weibo.is_top,
+            reposts_count: weibo.reposts_count,
+            comments_count: weibo.comments_count,
+            attitudes_count: weibo.attitudes_count,
+            has_media: weibo.has_media,
+            repost_has_media: weibo.repost_has_media,
+            pic_urls: weibo.pic_urls.clone(),
+            video_url: weibo.video_url.clone(),
+            source: weibo.source.clone(),
+            user_id: weibo.user_id.clone(),
+        }
+    }
+}
+
+impl From<CachedWeibo> for WeiboInfo {
+    fn from(cached: CachedWeibo) -> Self {
+        let mut weibo = WeiboInfo::from_id(cached.id);
+        weibo.text = cached.text;
+        weibo.created_at = cached.created_at;
+        weibo.is_long_text = cached.is_long_text;
+        weibo.visibility = cached.visibility.as_deref().and_then(Visibility::from_key);
+        weibo.is_repost = cached.is_repost;
+        weibo.is_top = cached.is_top;
+        weibo.reposts_count = cached.reposts_count;
+        weibo.comments_count = cached.comments_count;
+        weibo.attitudes_count = cached.attitudes_count;
+        weibo.has_media = cached.has_media;
+        weibo.repost_has_media = cached.repost_has_media;
+        weibo.pic_urls = cached.pic_urls;
+        weibo.video_url = cached.video_url;
+        weibo.source = cached.source;
+        weibo.user_id = cached.user_id;
+        weibo
+    }
+}
+
+impl ListCache {
+    pub fn new(path: String, refresh: bool, ttl: Duration) -> Self {
+        Self { path, refresh, ttl }
+    }
+
+    /// 读取有效的缓存；不存在、已过期或与本次获取不符时返回 None，由调用方重新获取
+    pub fn load(&self, uid: &str, options: &FetchOptions) -> Option<Vec<WeiboInfo>> {
+        if self.refresh {
+            info!("已指定 --refresh，忽略缓存 {} 重新获取", self.path);
+            return None;
+        }
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return None,
+            Err(e) => {
+                warn!("无法读取缓存 {}: {}，将重新获取", self.path, e);
+                return None;
+            }
+        };
+        let file: CacheFile = match serde_json::from_str(&content) {
+            Ok(file) => file,
+            Err(e) => {
+                warn!("缓存 {} 格式无效: {}，将重新获取", self.path, e);
+                return None;
+            }
+        };
+
+        if file.version != CACHE_VERSION {
+            info!("缓存 {} 由其他版本生成，将重新获取", self.path);
+            return None;
+        }
+        if file.uid != uid {
+            warn!(
+                "缓存 {} 属于用户 {}，与本次的 {} 不同，将重新获取",
+                self.path, file.uid, uid
+            );
+            return None;
+        }
+        if file.options != CacheKey::from(options) {
+            info!("获取选项与缓存 {} 不同，将重新获取", self.path);
+            return None;
+        }
+        let fetched_at = match DateTime::parse_from_rfc3339(&file.fetched_at) {
+            Ok(time) => time,
+            Err(_) => {
+                warn!("缓存 {} 的获取时间无效，将重新获取", self.path);
+                return None;
+            }
+        };
+        let age = (Local::now().fixed_offset() - fetched_at)
+            .to_std()
+            .unwrap_or_default();
+        if age > self.ttl {
+            info!(
+                "缓存 {} 获取于 {}，已超过有效期，将重新获取",
+                self.path,
+                fetched_at.format("%Y-%m-%d %H:%M:%S")
+            );
+            return None;
+        }
+
+        info!(
+            "✓ 从缓存 {} 读取 {} 条微博（获取于 {}，如需最新列表请加 --refresh）",
+            self.path,
+            file.weibos.len(),
+            fetched_at.format("%Y-%m-%d %H:%M:%S")
+        );
+        Some(file.weibos.into_iter().map(WeiboInfo::from).collect())
+    }
+
+    /// 写入刚获取到的完整列表
+    pub fn save(&self, uid: &str, options: &FetchOptions, weibos: &[WeiboInfo]) -> Result<()> {
+        let file = CacheFile {
+            version: CACHE_VERSION,
+            uid: uid.to_string(),
+            fetched_at: Local::now().to_rfc3339(),
+            options: CacheKey::from(options),
+            weibos: weibos.iter().map(CachedWeibo::from).collect(),
+        };
+        let content = serde_json::to_string(&file)?;
+        fs::write(&self.path, content).context(format!("无法写入缓存文件: {}", self.path))?;
+        info!("✓ 微博列表已缓存到: {}", self.path);
+        Ok(())
+    }
+
+    /// 修改了微博后删除缓存，避免下次读到过时的可见性或已删除的微博
+    pub fn invalidate(&self) {
+        match fs::remove_file(&self.path) {
+            Ok(()) => info!("微博已修改，缓存 {} 已删除", self.path),
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => warn!(
+                "无法删除缓存 {}: {}，下次运行请加 --refresh 重新获取",
+                self.path, e
+            ),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::warn;
+
+use weibo_hide::weibo_client::Visibility;
+
+/// 检查点文件头前缀，后接目标可见性标识
+const HEADER_PREFIX: &str = "# weibo_hide checkpoint visibility=";
+
+/// 断点续传检查点
+///
+/// 文件首行记录目标可见性，之后每行为 `id,old_visibility` 格式的 CSV，
+/// 记录已处理成功的微博及其设置前的可见性（未知时留空），可直接用于 restore。
+/// 每条记录写入后立即刷新，进程被强制结束时最多丢失正在写入的一条。
+pub struct Checkpoint {
+    file: Mutex<File>,
+}
+
+impl Checkpoint {
+    /// 读取检查点中已完成的微博 ID，文件不存在时返回空集合
+    ///
+    /// 若文件头记录的可见性与本次目标不同，打印警告。
+    pub fn load_completed(path: &str, visibility: Visibility) -> Result<HashSet<String>> {
+        if !Path::new(path).exists() {
+            return Ok(HashSet::new());
+        }
+
+        let content = fs::read_to_string(path).context(format!("无法读取检查点文件: {}", path))?;
+
+        if let Some(recorded) = content
+            .lines()
+            .find_map(|l| l.trim().strip_prefix(HEADER_PREFIX))
+        {
+            if recorded != visibility.key() {
+                warn!(
+                    "检查点 {} 记录的目标可见性为 {}，与本次的 {} 不同",
+                    path,
+                    recorded,
+                    visibility.key()
+                );
+            }
+        }
+
+        Ok(parse_entries(&content)?
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect())
+    }
+
+    /// 读取检查点中的全部记录：微博 ID 及其设置前的可见性
+    pub fn load_entries(path: &str) -> Result<Vec<(String, Option<Visibility>)>> {
+        let content = fs::read_to_string(path).context(format!("无法读取检查点文件: {}", path))?;
+        parse_entries(&content)
+    }
+
+    /// 以追加方式打开检查点文件，新文件会先写入文件头
+    pub fn open(path: &str, visibility: Visibility) -> Result<Self> {
+        let is_new = fs::metadata(path).map(|m| m.len() == 0).unwrap_or(true);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .context(format!("无法打开检查点文件: {}", path))?;
+
+        if is_new {
+            writeln!(file, "{}{}", HEADER_PREFIX, visibility.key())?;
+            file.flush()?;
+        }
+
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// 记录一条处理成功的微博及其设置前的可见性
+    pub fn record(&self, weibo_id: &str, previous: Option<Visibility>) -> Result<()> {
+        let mut file = self.file.lock().unwrap();
+        writeln!(
+            file,
+            "{},{}",
+            weibo_id,
+            previous.map(|v| v.key()).unwrap_or("")
+        )?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// 解析检查点内容，跳过空行与 `#` 开头的注释行
+fn parse_entries(content: &str) -> Result<Vec<(String, Option<Visibility>)>> {
+    let mut entries = Vec::new();
+
+    for (line_no, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (id, old) = line.split_once(',').unwrap_or((line, ""));
+        let old = old.trim();
+        let previous = if old.is_empty() {
+            None
+        } else {
+            Some(
+                Visibility::from_key(old)
+                    .ok_or_else(|| anyhow!("检查点第 {} 行的可见性无效: {}", line_no + 1, old))?,
+            )
+        };
+        entries.push((id.trim().to_string(), previous));
+    }
+
+    Ok(entries)
+}
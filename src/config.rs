@@ -0,0 +1,195 @@
+use anyhow::{anyhow, Context, Result};
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use serde::{Deserialize, Deserializer};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use weibo_hide::i18n::Lang;
+use weibo_hide::weibo_client::{parse_header_line, validate_base_url, validate_proxy};
+
+/// 默认配置文件名，依次在当前目录与 `~/.config/` 下查找
+pub const CONFIG_FILE_NAME: &str = "weibo_hide.toml";
+
+/// 配置文件内容，键名与命令行参数一致（如 `user-id`、`cookie-file`）
+///
+/// 配置值会替换对应参数的默认值，命令行中显式指定的参数优先；
+/// `visibility` 只作用于 hide（restore 中同名参数为缺省时的恢复级别）。
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Settings {
+    pub user_id: Option<String>,
+    pub cookie_file: Option<String>,
+    pub visibility: Option<String>,
+    pub max_pages: Option<u32>,
+    pub page_delay: Option<f64>,
+    pub max_weibos: Option<u64>,
+    pub parallel_fetch: Option<u32>,
+    pub page_size: Option<u32>,
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub delay: Option<Duration>,
+    pub batch_size: Option<u64>,
+    pub concurrency: Option<u64>,
+    pub rate_limit_cooldown: Option<u64>,
+    pub failure_log: Option<String>,
+    pub min_interval: Option<f64>,
+    pub max_interval: Option<f64>,
+    pub auto_retry_passes: Option<u32>,
+    pub retry_pass_delay: Option<u64>,
+    pub checkpoint: Option<String>,
+    pub max_retries: Option<u32>,
+    pub backoff_base: Option<u64>,
+    pub timeout: Option<u64>,
+    pub connect_timeout: Option<u64>,
+    pub proxy: Option<String>,
+    pub base_url: Option<String>,
+    pub max_requests: Option<u64>,
+    pub rate: Option<u32>,
+    pub user_agent: Option<String>,
+    pub header: Option<Vec<String>>,
+    pub strict_validation: Option<bool>,
+    pub session: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub session_ttl: Option<Duration>,
+    pub require_typed_confirmation: Option<bool>,
+    pub typed_confirmation_threshold: Option<u64>,
+    pub lang: Option<Lang>,
+}
+
+impl Settings {
+    /// 读取配置文件
+    ///
+    /// 指定了 `explicit` 时该文件必须存在；否则在默认位置查找，都不存在时返回空配置。
+    /// 返回配置及实际读取的文件路径。
+    pub fn load(explicit: Option<&str>) -> Result<(Self, Option<PathBuf>)> {
+        let path = match explicit {
+            Some(path) => {
+                if !Path::new(path).exists() {
+                    return Err(anyhow!("配置文件不存在: {}", path));
+                }
+                PathBuf::from(path)
+            }
+            None => match default_locations().into_iter().find(|p| p.exists()) {
+                Some(path) => path,
+                None => return Ok((Self::default(), None)),
+            },
+        };
+
+        let content =
+            fs::read_to_string(&path).context(format!("无法读取配置文件: {}", path.display()))?;
+        let settings: Settings =
+            toml::from_str(&content).context(format!("配置文件格式错误: {}", path.display()))?;
+        settings.validate()?;
+        Ok((settings, Some(path)))
+    }
+
+    /// 检查配置值的取值范围（命令行参数由 clap 检查）
+    fn validate(&self) -> Result<()> {
+        if self.batch_size == Some(0) {
+            return Err(anyhow!("配置项 batch-size 不能为 0"));
+        }
+        if self.max_weibos == Some(0) {
+            return Err(anyhow!("配置项 max-weibos 不能为 0"));
+        }
+        if self.concurrency == Some(0) {
+            return Err(anyhow!("配置项 concurrency 不能为 0"));
+        }
+        if self.page_size == Some(0) {
+            return Err(anyhow!("配置项 page-size 不能为 0"));
+        }
+        if self.parallel_fetch.is_some_and(|n| n < 2) {
+            return Err(anyhow!("配置项 parallel-fetch 应不小于 2"));
+        }
+        let invalid_secs = |d: f64| !d.is_finite() || d < 0.0;
+        if self.page_delay.is_some_and(invalid_secs) {
+            return Err(anyhow!("配置项 page-delay 应为非负数"));
+        }
+        if self.min_interval.is_some_and(invalid_secs)
+            || self.max_interval.is_some_and(invalid_secs)
+        {
+            return Err(anyhow!("配置项 min-interval 与 max-interval 应为非负数"));
+        }
+        if self.timeout == Some(0) || self.connect_timeout == Some(0) {
+            return Err(anyhow!("配置项 timeout 与 connect-timeout 不能为 0"));
+        }
+        if let Some(ref proxy) = self.proxy {
+            validate_proxy(proxy).context("配置项 proxy 无效")?;
+        }
+        if let Some(ref base_url) = self.base_url {
+            validate_base_url(base_url).context("配置项 base-url 无效")?;
+        }
+        if self.max_retries == Some(0) {
+            return Err(anyhow!("配置项 max-retries 不能为 0"));
+        }
+        for line in self.header.iter().flatten() {
+            parse_header_line(line).context("配置项 header 无效")?;
+        }
+        if self.rate == Some(0) {
+            return Err(anyhow!("配置项 rate 不能为 0"));
+        }
+        if self.max_requests == Some(0) {
+            return Err(anyhow!("配置项 max-requests 不能为 0"));
+        }
+        if self.typed_confirmation_threshold == Some(0) {
+            return Err(anyhow!("配置项 typed-confirmation-threshold 不能为 0"));
+        }
+        Ok(())
+    }
+}
+
+/// 时长配置项：数字按秒计（可为小数），字符串的写法同命令行，如 "500ms"
+fn deserialize_duration<'de, D>(deserializer: D) -> std::result::Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Secs(f64),
+        Text(String),
+    }
+
+    let text = match Raw::deserialize(deserializer)? {
+        Raw::Secs(secs) => secs.to_string(),
+        Raw::Text(text) => text,
+    };
+    crate::parse_duration(&text)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+/// 参数是否在命令行中显式指定
+fn from_command_line(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::CommandLine)
+}
+
+/// 命令行未显式指定该参数时，使用配置值
+pub fn fill<T: Clone>(matches: &ArgMatches, id: &str, target: &mut T, value: &Option<T>) {
+    if let Some(value) = value {
+        if !from_command_line(matches, id) {
+            *target = value.clone();
+        }
+    }
+}
+
+/// 同 [`fill`]，用于可选参数
+pub fn fill_option<T: Clone>(
+    matches: &ArgMatches,
+    id: &str,
+    target: &mut Option<T>,
+    value: &Option<T>,
+) {
+    if value.is_some() && !from_command_line(matches, id) {
+        *target = value.clone();
+    }
+}
+
+/// 默认配置文件位置：当前目录，然后是 `~/.config/`
+fn default_locations() -> Vec<PathBuf> {
+    let mut locations = vec![PathBuf::from(CONFIG_FILE_NAME)];
+    if let Some(home) = std::env::var_os("HOME") {
+        locations.push(Path::new(&home).join(".config").join(CONFIG_FILE_NAME));
+    }
+    locations
+}
@@ -0,0 +1,11 @@
+//! 进程退出码，供脚本判断运行结果，不必解析输出
+//!
+//! 命令行参数有误时由 clap 以 2 退出，与 [`FAILURE`] 一致；
+//! 连续两次 Ctrl+C 强制退出时为 130。
+
+/// 全部成功，或筛选后没有需要处理的微博
+pub const SUCCESS: u8 = 0;
+/// 部分成功：有微博失败、未处理（中断或达到请求上限），或评论权限设置失败
+pub const PARTIAL: u8 = 1;
+/// 全部失败，或开始处理前出错（Cookie 无效、没有找到微博、文件无法读取等）
+pub const FAILURE: u8 = 2;
@@ -0,0 +1,80 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::sync::Mutex;
+use tracing::warn;
+
+/// 失败记录中的一行
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FailureRecord {
+    pub id: String,
+    pub error: String,
+    /// RFC 3339 格式的本地时间
+    pub timestamp: String,
+}
+
+/// 失败记录文件（JSON Lines）
+///
+/// 每次失败立即追加一行并刷新，运行中途被中断也不会丢失已记录的失败。
+pub struct FailureLog {
+    file: Mutex<File>,
+}
+
+impl FailureLog {
+    /// 以追加方式打开失败记录文件
+    pub fn open(path: &str) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .context(format!("无法打开失败记录文件: {}", path))?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// 读取失败记录中的微博 ID，按首次出现的顺序去重
+    ///
+    /// 无法解析的行会被跳过并提示。
+    pub fn load_ids(path: &str) -> Result<Vec<String>> {
+        let content =
+            fs::read_to_string(path).context(format!("无法读取失败记录文件: {}", path))?;
+        let mut seen = HashSet::new();
+        let mut ids = Vec::new();
+
+        for (line_no, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<FailureRecord>(line) {
+                Ok(record) => {
+                    if seen.insert(record.id.clone()) {
+                        ids.push(record.id);
+                    }
+                }
+                Err(e) => warn!("跳过失败记录第 {} 行: {}", line_no + 1, e),
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// 追加一条失败记录
+    pub fn record(&self, weibo_id: &str, error: &str) -> Result<()> {
+        let record = FailureRecord {
+            id: weibo_id.to_string(),
+            error: error.to_string(),
+            timestamp: chrono::Local::now().to_rfc3339(),
+        };
+        let line = serde_json::to_string(&record)?;
+
+        let mut file = self.file.lock().unwrap();
+        writeln!(file, "{}", line)?;
+        file.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,836 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use clap::{Args, ValueEnum};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use tracing::{info, warn};
+
+use weibo_hide::weibo_client::{parse_weibo_time, Visibility, WeiboInfo};
+
+/// 微博筛选参数（Hide 与 List 共用）
+///
+/// 日期边界按微博自身时区（通常为 +0800）的发布日期比较，
+/// `--since` 与 `--until` 均包含当天。
+/// 多个 `--contains` 之间为"或"关系，不同种类的条件之间为"且"关系。
+#[derive(Args, Debug, Clone, Default)]
+pub struct FilterArgs {
+    /// 只处理该日期及之后发布的微博（YYYY-MM-DD，包含当天）
+    #[arg(long)]
+    pub since: Option<NaiveDate>,
+
+    /// 只处理该日期及之前发布的微博（YYYY-MM-DD，包含当天）
+    #[arg(long)]
+    pub until: Option<NaiveDate>,
+
+    /// 只处理 ID 不小于该值的微博（包含该 ID，按数值比较）
+    #[arg(long, value_name = "ID")]
+    pub since_id: Option<u64>,
+
+    /// 只处理 ID 不大于该值的微博（包含该 ID，按数值比较）
+    #[arg(long, value_name = "ID")]
+    pub until_id: Option<u64>,
+
+    /// 只处理内容包含该文字的微博（可重复，满足任意一个即可）
+    #[arg(long)]
+    pub contains: Vec<String>,
+
+    /// 只处理内容匹配该正则表达式的微博
+    #[arg(long, value_parser = Regex::new)]
+    pub matches: Option<Regex>,
+
+    /// 内容为空的微博也视为匹配内容筛选
+    #[arg(long, default_value = "false")]
+    pub include_empty: bool,
+
+    /// 只处理原创微博，跳过转发
+    #[arg(long, default_value = "false", conflicts_with = "reposts_only")]
+    pub originals_only: bool,
+
+    /// 只处理转发的微博
+    #[arg(long, default_value = "false")]
+    pub reposts_only: bool,
+
+    /// 只处理带图片或视频的微博
+    #[arg(long, default_value = "false", conflicts_with = "text_only")]
+    pub media_only: bool,
+
+    /// 只处理不带图片和视频的纯文字微博
+    #[arg(long, default_value = "false")]
+    pub text_only: bool,
+
+    /// 配合 --media-only/--text-only：转发的微博按被转发的原微博是否带图片或视频判断（默认只看转发者自己的内容）
+    #[arg(long, default_value = "false")]
+    pub media_from_original: bool,
+
+    /// 只处理转发数不超过 N 的微博
+    #[arg(long, value_name = "N")]
+    pub max_reposts: Option<u64>,
+
+    /// 只处理评论数不超过 N 的微博
+    #[arg(long, value_name = "N")]
+    pub max_comments: Option<u64>,
+
+    /// 只处理点赞数不超过 N 的微博
+    #[arg(long, value_name = "N")]
+    pub max_likes: Option<u64>,
+}
+
+impl FilterArgs {
+    /// 是否设置了日期范围
+    fn has_date_range(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
+
+    /// 是否设置了 ID 范围
+    fn has_id_range(&self) -> bool {
+        self.since_id.is_some() || self.until_id.is_some()
+    }
+
+    /// 是否设置了互动数上限
+    fn has_engagement_limit(&self) -> bool {
+        self.max_reposts.is_some() || self.max_comments.is_some() || self.max_likes.is_some()
+    }
+
+    /// 是否设置了内容筛选
+    fn has_text_filter(&self) -> bool {
+        !self.contains.is_empty() || self.matches.is_some()
+    }
+
+    /// 按筛选条件过滤微博列表
+    pub fn apply(&self, weibos: Vec<WeiboInfo>) -> Result<Vec<WeiboInfo>> {
+        let mut weibos = weibos;
+        if self.has_date_range() {
+            weibos = self.filter_by_date(weibos);
+        }
+        if self.has_id_range() {
+            weibos = self.filter_by_id(weibos);
+        }
+        if self.has_text_filter() {
+            weibos = self.filter_by_text(weibos);
+        }
+        if self.originals_only || self.reposts_only {
+            weibos = self.filter_by_repost(weibos);
+        }
+        if self.media_only || self.text_only {
+            weibos = self.filter_by_media(weibos);
+        } else if self.media_from_original {
+            warn!("--media-from-original 需配合 --media-only 或 --text-only 使用，已忽略");
+        }
+        if self.has_engagement_limit() {
+            weibos = self.filter_by_engagement(weibos);
+        }
+        Ok(weibos)
+    }
+
+    fn filter_by_repost(&self, weibos: Vec<WeiboInfo>) -> Vec<WeiboInfo> {
+        let total = weibos.len();
+        let want_repost = self.reposts_only;
+        let kept: Vec<WeiboInfo> = weibos
+            .into_iter()
+            .filter(|weibo| weibo.is_repost == want_repost)
+            .collect();
+
+        info!(
+            "✓ {}筛选: {} 条中保留 {} 条",
+            if want_repost { "转发" } else { "原创" },
+            total,
+            kept.len()
+        );
+        kept
+    }
+
+    /// 按是否带图片或视频筛选
+    fn filter_by_media(&self, weibos: Vec<WeiboInfo>) -> Vec<WeiboInfo> {
+        let total = weibos.len();
+        let want_media = self.media_only;
+        let kept: Vec<WeiboInfo> = weibos
+            .into_iter()
+            .filter(|weibo| {
+                let has_media = if weibo.is_repost && self.media_from_original {
+                    weibo.repost_has_media
+                } else {
+                    weibo.has_media
+                };
+                has_media == want_media
+            })
+            .collect();
+
+        info!(
+            "✓ {}筛选: {} 条中保留 {} 条",
+            if want_media {
+                "图片/视频"
+            } else {
+                "纯文字"
+            },
+            total,
+            kept.len()
+        );
+        kept
+    }
+
+    /// 按互动数筛选：任一设置了上限的计数超过上限即跳过，保留转发、评论或点赞多的微博
+    fn filter_by_engagement(&self, weibos: Vec<WeiboInfo>) -> Vec<WeiboInfo> {
+        let total = weibos.len();
+        let limits = [
+            (self.max_reposts, "转发数"),
+            (self.max_comments, "评论数"),
+            (self.max_likes, "点赞数"),
+        ];
+        let mut kept = Vec::new();
+        let mut unknown = Vec::new();
+
+        for weibo in weibos {
+            let counts = [
+                weibo.reposts_count,
+                weibo.comments_count,
+                weibo.attitudes_count,
+            ];
+            let mut within = true;
+            for ((limit, _), count) in limits.iter().zip(counts) {
+                let Some(limit) = limit else { continue };
+                match count {
+                    Some(count) if count <= *limit => {}
+                    Some(_) => within = false,
+                    None => {
+                        within = false;
+                        unknown.push(weibo.id.clone());
+                        break;
+                    }
+                }
+            }
+            if within {
+                kept.push(weibo);
+            }
+        }
+
+        if !unknown.is_empty() {
+            warn!(
+                "{} 条微博缺少互动计数，无法确认是否超过上限，已排除: {}",
+                unknown.len(),
+                unknown.join(", ")
+            );
+        }
+
+        let described: Vec<String> = limits
+            .iter()
+            .filter_map(|(limit, name)| limit.map(|n| format!("{} ≤ {}", name, n)))
+            .collect();
+        info!(
+            "✓ 互动数筛选（{}）: {} 条中保留 {} 条",
+            described.join("，"),
+            total,
+            kept.len()
+        );
+        kept
+    }
+
+    fn filter_by_date(&self, weibos: Vec<WeiboInfo>) -> Vec<WeiboInfo> {
+        let total = weibos.len();
+        let mut kept = Vec::new();
+        let mut unparsed = Vec::new();
+
+        for weibo in weibos {
+            let date = match weibo.created_at.as_deref().map(parse_weibo_time) {
+                Some(Ok(time)) => time.date_naive(),
+                _ => {
+                    unparsed.push(weibo);
+                    continue;
+                }
+            };
+
+            if self.since.is_some_and(|since| date < since) {
+                continue;
+            }
+            if self.until.is_some_and(|until| date > until) {
+                continue;
+            }
+            kept.push(weibo);
+        }
+
+        if !unparsed.is_empty() {
+            warn!(
+                "{} 条微博的发布时间无法解析，已排除在日期筛选之外:",
+                unparsed.len()
+            );
+            for weibo in &unparsed {
+                warn!(
+                    "  - ID {}: {}",
+                    weibo.id,
+                    weibo.created_at.as_deref().unwrap_or("无时间")
+                );
+            }
+        }
+
+        info!("✓ 日期筛选: {} 条中保留 {} 条", total, kept.len());
+        kept
+    }
+
+    /// 按 ID 范围筛选：微博 ID 大致随发布时间递增，可以不解析日期而按 ID 划定范围
+    fn filter_by_id(&self, weibos: Vec<WeiboInfo>) -> Vec<WeiboInfo> {
+        let total = weibos.len();
+        let mut kept = Vec::new();
+        let mut unparsed = Vec::new();
+
+        for weibo in weibos {
+            let id = match weibo.id.parse::<u64>() {
+                Ok(id) => id,
+                Err(_) => {
+                    unparsed.push(weibo.id);
+                    continue;
+                }
+            };
+            if self.since_id.is_some_and(|since| id < since) {
+                continue;
+            }
+            if self.until_id.is_some_and(|until| id > until) {
+                continue;
+            }
+            kept.push(weibo);
+        }
+
+        if !unparsed.is_empty() {
+            warn!(
+                "{} 条微博的 ID 不是数字，已排除在 ID 范围筛选之外: {}",
+                unparsed.len(),
+                unparsed.join(", ")
+            );
+        }
+
+        info!("✓ ID 范围筛选: {} 条中保留 {} 条", total, kept.len());
+        kept
+    }
+
+    fn filter_by_text(&self, weibos: Vec<WeiboInfo>) -> Vec<WeiboInfo> {
+        let total = weibos.len();
+        let kept: Vec<WeiboInfo> = weibos
+            .into_iter()
+            .filter(|weibo| self.text_matches(weibo.text.as_deref()))
+            .collect();
+
+        info!("✓ 内容筛选: {} 条中保留 {} 条", total, kept.len());
+        kept
+    }
+
+    fn text_matches(&self, text: Option<&str>) -> bool {
+        let text = match text {
+            Some(text) if !text.is_empty() => text,
+            _ => return self.include_empty,
+        };
+
+        if !self.contains.is_empty() && !self.contains.iter().any(|s| text.contains(s.as_str())) {
+            return false;
+        }
+        if let Some(ref re) = self.matches {
+            if !re.is_match(text) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// 按当前可见性筛选待处理的微博
+///
+/// 只保留当前可见性为 `only_if` 的微博；当前可见性已与目标相同或更严格的微博也会跳过。
+/// 无法识别当前可见性的微博无法确认，一并跳过并提示数量。
+pub fn filter_by_current_visibility(
+    weibos: Vec<WeiboInfo>,
+    only_if: Visibility,
+    target: Visibility,
+) -> Vec<WeiboInfo> {
+    let total = weibos.len();
+    let mut unknown = 0;
+
+    let kept: Vec<WeiboInfo> = weibos
+        .into_iter()
+        .filter(|weibo| match weibo.visibility {
+            Some(current) => {
+                current == only_if && current.restrictiveness() < target.restrictiveness()
+            }
+            None => {
+                unknown += 1;
+                false
+            }
+        })
+        .collect();
+
+    if unknown > 0 {
+        warn!("{} 条微博无法识别当前可见性，已跳过", unknown);
+    }
+    info!(
+        "✓ 可见性筛选: {} 条中保留 {} 条当前为{}的微博",
+        total,
+        kept.len(),
+        only_if.as_str()
+    );
+    kept
+}
+
+/// 微博列表的排序方式，不指定时保持接口返回的顺序
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    /// 按发布时间从旧到新
+    Oldest,
+    /// 按发布时间从新到旧
+    Newest,
+    /// 按微博 ID 从小到大（按数值比较）
+    Id,
+}
+
+/// 按指定方式排序，顺序相同的微博保持原有先后；发布时间无法解析的微博排在最后
+pub fn sort_weibos(weibos: &mut [WeiboInfo], order: SortOrder) {
+    let time = |w: &WeiboInfo| {
+        w.created_at
+            .as_deref()
+            .and_then(|t| parse_weibo_time(t).ok())
+    };
+    match order {
+        SortOrder::Oldest => weibos.sort_by_cached_key(|w| {
+            let t = time(w);
+            (t.is_none(), t)
+        }),
+        SortOrder::Newest => weibos.sort_by_cached_key(|w| {
+            let t = time(w);
+            (t.is_none(), std::cmp::Reverse(t))
+        }),
+        // 纯数字的 ID 先按长度再按字典序比较，等同于按数值比较且不会溢出
+        SortOrder::Id => weibos.sort_by(|a, b| (a.id.len(), &a.id).cmp(&(b.id.len(), &b.id))),
+    }
+}
+
+/// 指定不处理的微博（Hide 与 Delete 共用）
+#[derive(Args, Debug, Clone, Default)]
+pub struct ExcludeArgs {
+    /// 不处理的微博 ID，以逗号分隔（如 123,456）
+    #[arg(long, value_delimiter = ',')]
+    pub exclude_ids: Vec<String>,
+
+    /// 不处理的微博 ID 文件，每行一个（空行与 # 开头的行忽略）
+    #[arg(long)]
+    pub exclude_file: Option<String>,
+}
+
+impl ExcludeArgs {
+    /// 合并两种参数指定的 ID
+    pub fn load(&self) -> Result<HashSet<String>> {
+        let mut ids: HashSet<String> = self
+            .exclude_ids
+            .iter()
+            .map(|id| id.trim())
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        if let Some(ref path) = self.exclude_file {
+            ids.extend(read_id_file(path)?);
+        }
+
+        Ok(ids)
+    }
+}
+
+/// 读取微博 ID 文件：每行一个数字 ID，空行与 # 开头的行忽略
+///
+/// 不是数字的行给出警告并跳过；保留原有顺序，重复的 ID 只保留第一个。
+pub fn read_id_file(path: &str) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path).context(format!("无法读取微博 ID 文件: {}", path))?;
+    let mut seen = HashSet::new();
+    let mut ids = Vec::new();
+
+    for (line_no, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if !line.bytes().all(|b| b.is_ascii_digit()) {
+            warn!(
+                "跳过 {} 第 {} 行: \"{}\" 不是有效的微博 ID",
+                path,
+                line_no + 1,
+                line
+            );
+            continue;
+        }
+        if seen.insert(line.to_string()) {
+            ids.push(line.to_string());
+        }
+    }
+
+    Ok(ids)
+}
+
+/// 读取逐条指定目标可见性的 CSV 文件：每行 `id,visibility`，# 开头的行忽略
+///
+/// 首行含 `id` 列时视为表头，按列名读取 `id` 与 `visibility`，其余列忽略，
+/// 因此 `list --format csv` 导出的文件改好 visibility 列后可以直接使用。
+/// 可见性的写法与 `--visibility` 相同；留空的行跳过，无法识别的可见性直接报错，
+/// 避免把微博设成意料之外的可见性。同一 ID 重复出现时目标必须一致，只保留第一个。
+pub fn read_targets_file(path: &str) -> Result<Vec<(String, Visibility)>> {
+    let content = fs::read_to_string(path).context(format!("无法读取目标可见性文件: {}", path))?;
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .comment(Some(b'#'))
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    let mut records = reader.records().peekable();
+    let (id_col, visibility_col) = match records.peek() {
+        Some(Ok(first)) if first.iter().any(|f| f.eq_ignore_ascii_case("id")) => {
+            let column = |name: &str| first.iter().position(|f| f.eq_ignore_ascii_case(name));
+            let id_col = column("id").unwrap_or_default();
+            let visibility_col = column("visibility")
+                .ok_or_else(|| anyhow::anyhow!("{} 的表头中没有 visibility 列", path))?;
+            records.next();
+            (id_col, visibility_col)
+        }
+        _ => (0, 1),
+    };
+
+    let mut targets: Vec<(String, Visibility)> = Vec::new();
+    let mut seen: HashMap<String, Visibility> = HashMap::new();
+    let mut blank = 0;
+    for record in records {
+        let record = record.context(format!("目标可见性文件格式错误: {}", path))?;
+        let line_no = record.position().map(|p| p.line()).unwrap_or_default();
+        let id = record.get(id_col).unwrap_or_default();
+        if id.is_empty() {
+            continue;
+        }
+        if !id.bytes().all(|b| b.is_ascii_digit()) {
+            warn!(
+                "跳过 {} 第 {} 行: \"{}\" 不是有效的微博 ID",
+                path, line_no, id
+            );
+            continue;
+        }
+        let value = record.get(visibility_col).unwrap_or_default();
+        if value.is_empty() {
+            blank += 1;
+            continue;
+        }
+        let visibility = crate::parse_visibility(value)
+            .with_context(|| format!("{} 第 {} 行", path, line_no))?;
+        match seen.get(id) {
+            Some(&existing) if existing != visibility => {
+                return Err(anyhow::anyhow!(
+                    "{} 第 {} 行: 微博 {} 重复出现且目标可见性不同（{} 与 {}）",
+                    path,
+                    line_no,
+                    id,
+                    existing.key(),
+                    visibility.key()
+                ));
+            }
+            Some(_) => {}
+            None => {
+                seen.insert(id.to_string(), visibility);
+                targets.push((id.to_string(), visibility));
+            }
+        }
+    }
+    if blank > 0 {
+        info!("{} 中 {} 行未填写可见性，已跳过", path, blank);
+    }
+
+    Ok(targets)
+}
+
+/// 移除排除列表中的微博，返回移除的数量
+pub fn remove_excluded(weibos: &mut Vec<WeiboInfo>, excluded: &HashSet<String>) -> usize {
+    let before = weibos.len();
+    weibos.retain(|w| !excluded.contains(&w.id));
+    before - weibos.len()
+}
+
+/// hide 选择待处理微博的完整流程
+///
+/// 依次应用筛选条件、当前可见性（`--only-if`）、跳过前 N 条、检查点、
+/// 已是目标可见性与数量限制。可以一次处理全部微博，也可以逐页调用，
+/// 跳过与限制的计数会跨页保留。
+pub struct Selection<'a> {
+    filter: Option<&'a FilterArgs>,
+    only_if: Option<Visibility>,
+    target: Visibility,
+    skip_remaining: usize,
+    completed: HashSet<String>,
+    excluded: HashSet<String>,
+    include_pinned: bool,
+    keep_unchanged: bool,
+    limit_remaining: Option<usize>,
+    /// 因检查点中已完成而跳过的数量
+    pub skipped_completed: usize,
+    /// 因在排除列表中而跳过的数量
+    pub skipped_excluded: usize,
+    /// 跳过的置顶微博
+    pub skipped_pinned: Vec<WeiboInfo>,
+    /// 因已是目标可见性而跳过的数量
+    pub already_at_target: usize,
+    /// 已是目标可见性而跳过的微博，只在 [`Selection::with_unchanged_kept`] 后记录
+    pub unchanged: Vec<WeiboInfo>,
+    /// 因检查点中已完成而跳过的微博，记录条件同上
+    pub completed_weibos: Vec<WeiboInfo>,
+    /// 因在排除列表中而跳过的微博，记录条件同上
+    pub excluded_weibos: Vec<WeiboInfo>,
+}
+
+impl<'a> Selection<'a> {
+    pub fn new(target: Visibility, skip: usize, limit: Option<usize>) -> Self {
+        Self {
+            filter: None,
+            only_if: None,
+            target,
+            skip_remaining: skip,
+            completed: HashSet::new(),
+            excluded: HashSet::new(),
+            include_pinned: false,
+            keep_unchanged: false,
+            limit_remaining: limit,
+            skipped_completed: 0,
+            skipped_excluded: 0,
+            skipped_pinned: Vec::new(),
+            already_at_target: 0,
+            unchanged: Vec::new(),
+            completed_weibos: Vec::new(),
+            excluded_weibos: Vec::new(),
+        }
+    }
+
+    /// 应用筛选条件与当前可见性筛选
+    pub fn with_filter(mut self, filter: &'a FilterArgs, only_if: Option<Visibility>) -> Self {
+        self.filter = Some(filter);
+        self.only_if = only_if;
+        self
+    }
+
+    /// 跳过检查点中已完成的微博
+    pub fn with_completed(mut self, completed: HashSet<String>) -> Self {
+        self.completed = completed;
+        self
+    }
+
+    /// 跳过排除列表中的微博
+    pub fn with_excluded(mut self, excluded: HashSet<String>) -> Self {
+        self.excluded = excluded;
+        self
+    }
+
+    /// 置顶微博也照常处理（默认跳过）
+    pub fn with_pinned(mut self, include_pinned: bool) -> Self {
+        self.include_pinned = include_pinned;
+        self
+    }
+
+    /// 记录已是目标可见性、检查点中已完成与被排除的微博（预览时用于显示"无变化"的部分与生成计划清单）
+    pub fn with_unchanged_kept(mut self, keep: bool) -> Self {
+        self.keep_unchanged = keep;
+        self
+    }
+
+    /// 检查点中已完成的微博总数
+    pub fn completed_count(&self) -> usize {
+        self.completed.len()
+    }
+
+    /// 是否已达到数量限制，之后的微博都不会再被选中
+    pub fn is_full(&self) -> bool {
+        self.limit_remaining == Some(0)
+    }
+
+    /// 从一批微博中选出待处理的部分
+    pub fn take(&mut self, weibos: Vec<WeiboInfo>) -> Result<Vec<WeiboInfo>> {
+        let mut weibos = match self.filter {
+            Some(filter) => filter.apply(weibos)?,
+            None => weibos,
+        };
+        if let Some(current) = self.only_if {
+            weibos = filter_by_current_visibility(weibos, current, self.target);
+        }
+
+        // 跳过指定数量
+        let skipped = self.skip_remaining.min(weibos.len());
+        weibos.drain(..skipped);
+        self.skip_remaining -= skipped;
+
+        // 跳过排除列表中的微博
+        if self.keep_unchanged {
+            let (excluded, rest): (Vec<_>, Vec<_>) = weibos
+                .into_iter()
+                .partition(|w| self.excluded.contains(&w.id));
+            self.skipped_excluded += excluded.len();
+            self.excluded_weibos.extend(excluded);
+            weibos = rest;
+        } else {
+            self.skipped_excluded += remove_excluded(&mut weibos, &self.excluded);
+        }
+
+        // 跳过置顶微博
+        if !self.include_pinned {
+            let (pinned, rest): (Vec<_>, Vec<_>) = weibos.into_iter().partition(|w| w.is_top);
+            self.skipped_pinned.extend(pinned);
+            weibos = rest;
+        }
+
+        // 跳过检查点中已完成的微博
+        if !self.completed.is_empty() {
+            let (completed, rest): (Vec<_>, Vec<_>) = weibos
+                .into_iter()
+                .partition(|w| self.completed.contains(&w.id));
+            self.skipped_completed += completed.len();
+            if self.keep_unchanged {
+                self.completed_weibos.extend(completed);
+            }
+            weibos = rest;
+        }
+
+        // 跳过已是目标可见性的微博
+        let target = self.target;
+        let (unchanged, rest): (Vec<_>, Vec<_>) = weibos
+            .into_iter()
+            .partition(|w| w.visibility == Some(target));
+        self.already_at_target += unchanged.len();
+        if self.keep_unchanged {
+            self.unchanged.extend(unchanged);
+        }
+        weibos = rest;
+
+        // 限制处理数量
+        if let Some(ref mut remaining) = self.limit_remaining {
+            weibos.truncate(*remaining);
+            *remaining -= weibos.len();
+        }
+
+        Ok(weibos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 指定 ID 与发布时间的微博
+    fn weibo(id: &str, created_at: Option<&str>) -> WeiboInfo {
+        serde_json::from_value(serde_json::json!({ "id": id, "created_at": created_at })).unwrap()
+    }
+
+    /// 指定 ID 与内容的微博
+    fn with_text(id: &str, text: Option<&str>) -> WeiboInfo {
+        serde_json::from_value(serde_json::json!({ "id": id, "text": text })).unwrap()
+    }
+
+    fn ids(weibos: &[WeiboInfo]) -> Vec<&str> {
+        weibos.iter().map(|w| w.id.as_str()).collect()
+    }
+
+    #[test]
+    fn sort_puts_unparseable_dates_last() {
+        let mut weibos = vec![
+            weibo("10", Some("Wed Oct 09 15:30:00 +0800 2019")),
+            weibo("9", Some("刚刚")),
+            weibo("100", Some("Tue Oct 01 08:00:00 +0800 2019")),
+            weibo("11", Some("Thu Oct 10 00:00:00 +0800 2019")),
+        ];
+        sort_weibos(&mut weibos, SortOrder::Oldest);
+        assert_eq!(ids(&weibos), ["100", "10", "11", "9"]);
+        sort_weibos(&mut weibos, SortOrder::Newest);
+        assert_eq!(ids(&weibos), ["11", "10", "100", "9"]);
+        sort_weibos(&mut weibos, SortOrder::Id);
+        assert_eq!(ids(&weibos), ["9", "10", "11", "100"]);
+    }
+
+    /// 只含筛选参数的命令行，用于测试参数解析
+    #[derive(clap::Parser)]
+    struct FilterCli {
+        #[command(flatten)]
+        filter: FilterArgs,
+    }
+
+    fn parse(args: &[&str]) -> Result<FilterArgs, clap::Error> {
+        use clap::Parser;
+        FilterCli::try_parse_from(std::iter::once("weibo_hide").chain(args.iter().copied()))
+            .map(|cli| cli.filter)
+    }
+
+    #[test]
+    fn date_range_includes_both_bounds() {
+        let filter = FilterArgs {
+            since: NaiveDate::from_ymd_opt(2019, 10, 2),
+            until: NaiveDate::from_ymd_opt(2019, 10, 9),
+            ..Default::default()
+        };
+        let weibos = vec![
+            // --since 当天的零点；按 UTC 计算是前一天，应按微博自身时区算作当天
+            weibo("1", Some("Wed Oct 02 00:00:00 +0800 2019")),
+            // --until 当天的最后一秒
+            weibo("2", Some("Wed Oct 09 23:59:59 +0800 2019")),
+            weibo("3", Some("Tue Oct 01 23:59:59 +0800 2019")),
+            weibo("4", Some("Thu Oct 10 00:00:00 +0800 2019")),
+        ];
+        assert_eq!(ids(&filter.apply(weibos).unwrap()), ["1", "2"]);
+    }
+
+    #[test]
+    fn unparseable_dates_are_excluded() {
+        let filter = FilterArgs {
+            since: NaiveDate::from_ymd_opt(2019, 1, 1),
+            ..Default::default()
+        };
+        let weibos = vec![
+            weibo("1", Some("Wed Oct 09 15:30:00 +0800 2019")),
+            weibo("2", Some("2019-10-05")),
+            weibo("3", Some("刚刚")),
+            weibo("4", None),
+        ];
+        assert_eq!(ids(&filter.apply(weibos).unwrap()), ["1"]);
+    }
+
+    #[test]
+    fn id_range_compares_numerically() {
+        // 按字符串比较时 "9" > "10"，按数值比较时 9 < 10
+        let filter = FilterArgs {
+            since_id: Some(9),
+            until_id: Some(10),
+            ..Default::default()
+        };
+        let weibos = ["8", "9", "10", "11", "100", "not-a-number"]
+            .iter()
+            .map(|id| weibo(id, None))
+            .collect();
+        let kept = filter.apply(weibos).unwrap();
+        assert_eq!(ids(&kept), ["9", "10"]);
+    }
+
+    #[test]
+    fn repeated_contains_are_or_combined() {
+        let filter = parse(&["--contains", "猫", "--contains", "狗"]).unwrap();
+        let weibos = vec![
+            with_text("1", Some("今天撸猫")),
+            with_text("2", Some("遛狗去了")),
+            with_text("3", Some("猫和狗")),
+            with_text("4", Some("吃饭")),
+            with_text("5", None),
+        ];
+        let kept = filter.apply(weibos).unwrap();
+        assert_eq!(ids(&kept), ["1", "2", "3"]);
+
+        // 与 --matches 之间仍为"且"
+        let filter = parse(&["--contains", "猫", "--contains", "狗", "--matches", "^遛"]).unwrap();
+        let kept = filter
+            .apply(vec![
+                with_text("1", Some("今天撸猫")),
+                with_text("2", Some("遛狗去了")),
+            ])
+            .unwrap();
+        assert_eq!(ids(&kept), ["2"]);
+    }
+
+    #[test]
+    fn invalid_regex_is_an_error() {
+        let error = parse(&["--matches", "(未闭合"]).err().unwrap();
+        assert_eq!(error.kind(), clap::error::ErrorKind::ValueValidation);
+        let message = error.to_string();
+        assert!(message.contains("--matches"), "{}", message);
+        assert!(message.contains("(未闭合"), "{}", message);
+    }
+}
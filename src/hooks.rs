@@ -0,0 +1,99 @@
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// 同时运行的钩子命令个数上限，避免大批微博同时完成时一次启动过多进程
+const MAX_RUNNING_HOOKS: usize = 4;
+
+/// 每条处理结果产生后运行的命令（`--on-success`、`--on-failure`）
+///
+/// 命令交给系统 shell 执行（Unix 为 `sh -c`，Windows 为 `cmd /C`），微博 ID 与结果作为参数 `$1`、`$2` 传入，
+/// 同时设置环境变量 `WEIBO_ID`、`WEIBO_ACTION`、`WEIBO_RESULT`，失败时另有 `WEIBO_ERROR`。
+/// 命令在后台运行，不阻塞处理流程；启动失败或以非零状态退出时只记录警告，不影响本次运行。
+#[derive(Debug)]
+pub struct Hooks {
+    on_success: Option<String>,
+    on_failure: Option<String>,
+    permits: Arc<Semaphore>,
+    running: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl Hooks {
+    /// 两个命令都未指定时返回 None
+    pub fn new(on_success: Option<String>, on_failure: Option<String>) -> Option<Arc<Self>> {
+        if on_success.is_none() && on_failure.is_none() {
+            return None;
+        }
+        Some(Arc::new(Self {
+            on_success,
+            on_failure,
+            permits: Arc::new(Semaphore::new(MAX_RUNNING_HOOKS)),
+            running: Mutex::new(Vec::new()),
+        }))
+    }
+
+    /// 按结果选择命令并在后台启动；`success` 触发 `--on-success`，
+    /// `failed`、`unsupported`、`unverified` 触发 `--on-failure`，其余（如 `missing`）不触发
+    pub fn fire(&self, id: &str, action: &str, result: &str, error: Option<&str>) {
+        let command = match result {
+            "success" => self.on_success.as_ref(),
+            "failed" | "unsupported" | "unverified" => self.on_failure.as_ref(),
+            _ => None,
+        };
+        let Some(command) = command else {
+            return;
+        };
+
+        let mut cmd = shell_command(command);
+        cmd.arg(id)
+            .arg(result)
+            .env("WEIBO_ID", id)
+            .env("WEIBO_ACTION", action)
+            .env("WEIBO_RESULT", result)
+            .stdin(Stdio::null());
+        if let Some(error) = error {
+            cmd.env("WEIBO_ERROR", error);
+        }
+
+        let permits = Arc::clone(&self.permits);
+        let id = id.to_string();
+        let handle = tokio::spawn(async move {
+            let Ok(_permit) = permits.acquire_owned().await else {
+                return;
+            };
+            match cmd.status().await {
+                Ok(status) if status.success() => debug!("微博 {} 的钩子命令已完成", id),
+                Ok(status) => warn!("微博 {} 的钩子命令退出状态为 {}", id, status),
+                Err(e) => warn!("无法运行微博 {} 的钩子命令: {}", id, e),
+            }
+        });
+        self.running.lock().unwrap().push(handle);
+    }
+
+    /// 等待已启动的命令全部结束，在本轮处理完成、打印汇总之前调用
+    pub async fn wait(&self) {
+        let handles = std::mem::take(&mut *self.running.lock().unwrap());
+        for handle in handles {
+            if let Err(e) = handle.await {
+                warn!("钩子命令的任务异常结束: {}", e);
+            }
+        }
+    }
+}
+
+/// 用系统 shell 执行命令行，之后追加的参数在 Unix 上依次成为 `$1`、`$2`
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        // `sh -c` 之后的第一个参数是 `$0`
+        cmd.arg("-c").arg(command).arg("weibo_hide");
+        cmd
+    }
+}
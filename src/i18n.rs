@@ -0,0 +1,330 @@
+use serde::Deserialize;
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 界面语言（`--lang`），默认中文
+///
+/// 影响可见性名称、处理汇总、确认提示、预览、进度、交互选择与文本导出等面向用户的输出；
+/// 日志（含 `--log-format json`）、错误信息与帮助文字仍为中文。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    #[default]
+    Zh,
+    En,
+}
+
+impl Lang {
+    /// 命令行与配置文件中的标识：`zh` 或 `en`
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key.to_lowercase().as_str() {
+            "zh" | "zh-cn" | "中文" => Some(Lang::Zh),
+            "en" | "english" => Some(Lang::En),
+            _ => None,
+        }
+    }
+}
+
+/// 当前语言，0 为中文、1 为英文
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+/// 设置当前语言，应在输出任何文字之前调用
+pub fn set_lang(lang: Lang) {
+    CURRENT.store(lang as u8, Ordering::Relaxed);
+}
+
+/// 当前语言
+pub fn lang() -> Lang {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Lang::En,
+        _ => Lang::Zh,
+    }
+}
+
+/// 文字表：键、中文、英文；带参数的文字用 `{}` 占位，由 [`tf`] 依次替换
+const MESSAGES: &[(&str, &str, &str)] = &[
+    ("visibility.public", "公开", "public"),
+    ("visibility.friends", "仅好友可见", "friends only"),
+    ("visibility.private", "仅自己可见", "only me"),
+    ("visibility.fans", "仅粉丝可见", "fans only"),
+    ("visibility.unknown", "未知", "unknown"),
+    ("comment.everyone", "所有人可评论", "everyone can comment"),
+    ("comment.following", "我关注的人可评论", "people I follow can comment"),
+    ("comment.fans", "粉丝可评论", "fans can comment"),
+    ("comment.closed", "关闭评论", "comments closed"),
+    ("summary.done", "=== 处理完成 ===", "=== Done ==="),
+    ("summary.interrupted", "=== 已中断 ===", "=== Interrupted ==="),
+    ("summary.success", "成功", "Succeeded"),
+    ("summary.failed", "失败", "Failed"),
+    ("summary.retry_passes", "经过 {} 轮自动重试", "after {} retry passes"),
+    ("summary.unsupported", "不支持目标可见性", "Target not supported"),
+    ("summary.missing", "已删除/不存在", "Deleted/missing"),
+    ("summary.unverified", "已设置但未生效", "Set but not in effect"),
+    ("summary.skipped", "已是目标可见性", "Already at target"),
+    ("summary.unprocessed", "未处理", "Not processed"),
+    ("summary.comment_success", "评论权限已设置", "Comment permission set"),
+    (
+        "summary.comment_failed",
+        "评论权限设置失败",
+        "Comment permission failed",
+    ),
+    ("summary.count", "{} 条", "{}"),
+    ("summary.failed_details", "失败详情:", "Failures:"),
+    ("summary.category_count", "{}（{} 条）:", "{} ({}):"),
+    ("summary.note_separator", "，", ", "),
+    ("summary.note", "（{}）", " ({})"),
+    ("failure.network", "网络错误", "Network error"),
+    ("failure.auth", "认证错误", "Authentication error"),
+    ("failure.api", "API 拒绝", "Rejected by API"),
+    ("failure.unknown", "未知", "Unknown"),
+    (
+        "summary.unsupported_details",
+        "不支持目标可见性（微博限制，重试也不会成功）:",
+        "Target visibility not supported (a Weibo restriction; retrying will not help):",
+    ),
+    (
+        "summary.missing_details",
+        "已删除或不存在（获取列表后被删除，无需处理）:",
+        "Deleted or missing (removed after the list was fetched; nothing to do):",
+    ),
+    (
+        "summary.unverified_details",
+        "已设置但未生效（接口返回成功，重新读取时可见性不是目标）:",
+        "Set but not in effect (the API reported success, but the read-back visibility differs):",
+    ),
+    (
+        "summary.comment_failed_details",
+        "评论权限失败详情（可见性已设置）:",
+        "Comment permission failures (visibility was set):",
+    ),
+    ("summary.by_visibility", "按可见性:", "By visibility:"),
+    ("summary.skipped_mark", "（跳过）", " (skipped)"),
+    ("stats.wall_time", "总用时: {}", "Total time: {}"),
+    (
+        "stats.latency",
+        "请求耗时（共 {} 次）: 最短 {}，中位 {}，P95 {}，最长 {}",
+        "Request latency ({} requests): min {}, median {}, p95 {}, max {}",
+    ),
+    ("confirm.press_enter", "按 Ctrl+C 取消，或按回车继续...", "Press Ctrl+C to cancel, or Enter to continue..."),
+    (
+        "confirm.type_count",
+        "⚠ 即将处理 {} 条微博，请输入 {} 以确认（其他任意内容取消）:",
+        "⚠ About to process {} weibos. Type {} to confirm (anything else cancels):",
+    ),
+    (
+        "confirm.type_delete",
+        "⚠ 即将永久删除 {} 条微博，删除后无法恢复！输入 {} 确认，其他任意内容取消:",
+        "⚠ About to permanently delete {} weibos. This cannot be undone! Type {} to confirm, anything else cancels:",
+    ),
+    ("hide.to_process", "将要处理 {} 条微博", "{} weibos to process"),
+    ("hide.about_to_set", "准备将这些微博设置为: {}", "About to set these weibos to: {}"),
+    ("hide.none_found", "没有找到微博", "No weibos found"),
+    ("hide.none_selected", "筛选后没有需要处理的微博", "No weibos left to process after filtering"),
+    (
+        "hide.dry_run_hint",
+        "使用相同命令但不加 --dry-run 参数即可开始修改",
+        "Run the same command without --dry-run to apply the changes",
+    ),
+    (
+        "delete.dry_run_hint",
+        "使用相同命令但不加 --dry-run 参数即可开始删除",
+        "Run the same command without --dry-run to delete them",
+    ),
+    (
+        "restore.dry_run_hint",
+        "使用相同命令但不加 --dry-run 参数即可开始恢复",
+        "Run the same command without --dry-run to restore them",
+    ),
+    (
+        "apply.dry_run_hint",
+        "使用相同命令但不加 --dry-run 参数即可开始设置",
+        "Run the same command without --dry-run to apply the file",
+    ),
+    ("account.logged_in", "✓ 已登录: {}", "✓ Logged in as: {}"),
+    ("account.uid", "  用户 ID: {}", "  User ID: {}"),
+    ("account.statuses", "  微博总数: {}", "  Total weibos: {}"),
+    ("account.xsrf_found", "✓ XSRF-TOKEN: 已提取", "✓ XSRF-TOKEN: found"),
+    ("account.xsrf_missing", "✗ XSRF-TOKEN: 未找到", "✗ XSRF-TOKEN: not found"),
+    ("weibo.no_text", "无内容", "(no text)"),
+    ("preview.header", "预览前10条:", "First 10 weibos:"),
+    ("preview.more", "  ... 还有 {} 条", "  ... and {} more"),
+    ("diff.header", "可见性变化:", "Visibility changes:"),
+    ("diff.unchanged", "{}（无变化）", "{} (unchanged)"),
+    (
+        "diff.total",
+        "共 {} 条：将改变 {} 条，无变化 {} 条",
+        "{} in total: {} to change, {} unchanged",
+    ),
+    ("counts.by_current_visibility", "按当前可见性:", "By current visibility:"),
+    ("export.text", "   内容: {}", "   Text: {}"),
+    ("export.time", "   时间: {}", "   Time: {}"),
+    ("export.visibility", "   可见性: {}", "   Visibility: {}"),
+    ("export.link", "   链接: {}", "   Link: {}"),
+    ("progress.done", "完成", "done"),
+    ("progress.interrupted", "已中断", "interrupted"),
+    ("progress.processed", "已处理 {}/{} ({}%)", "Processed {}/{} ({}%)"),
+    ("progress.processed_count", "已处理 {} 条", "Processed {}"),
+    ("progress.fetched", "获取列表: 已获取 {}/约 {} 条", "Fetching list: {}/~{}"),
+    ("progress.fetched_count", "获取列表: 已获取 {} 条", "Fetching list: {}"),
+    (
+        "progress.fetch_bar",
+        "{spinner:.green} 获取列表 [{bar:40.cyan/blue}] {pos}/约 {len} 条 {msg}",
+        "{spinner:.green} Fetching list [{bar:40.cyan/blue}] {pos}/~{len} {msg}",
+    ),
+    (
+        "progress.fetch_spinner",
+        "{spinner:.green} 获取列表: 已获取 {pos} 条 {msg}",
+        "{spinner:.green} Fetching list: {pos} {msg}",
+    ),
+    ("progress.page", "（第 {} 页）", "(page {})"),
+    ("progress.batch_ok", "✓ 本批 {} 条成功", "✓ Batch: {} succeeded"),
+    (
+        "progress.batch_failed",
+        "✗ 本批成功 {} 条，失败 {} 条",
+        "✗ Batch: {} succeeded, {} failed",
+    ),
+    ("progress.deleted", "✓ 已删除 {}", "✓ Deleted {}"),
+    ("progress.delete_failed", "✗ 删除 {} 失败", "✗ Failed to delete {}"),
+    (
+        "picker.help",
+        "选择要处理的微博：↑↓ 移动，空格 勾选，a 全选，回车 确认，q 取消",
+        "Select weibos: ↑↓ move, Space toggle, a all, Enter confirm, q cancel",
+    ),
+    ("picker.unknown_date", "未知日期", "unknown"),
+    ("picker.selected", "已选 {} / {} 条", "{} / {} selected"),
+    ("picker.window", "（显示第 {}-{} 条）", " (showing {}-{})"),
+    ("pinned.skipped", "📌 已跳过置顶微博 {} - {}...", "📌 Skipped pinned weibo {} - {}..."),
+    (
+        "pinned.hint",
+        "   如需一并处理置顶微博，请加上 --include-pinned",
+        "   Add --include-pinned to process pinned weibos as well",
+    ),
+    ("run_dir.saved", "本次运行的记录已保存到: {}", "Run records saved to: {}"),
+    ("plan.saved", "✓ 预览计划清单已保存到: {}", "✓ Plan saved to: {}"),
+    ("preview.saved", "✓ 完整预览列表已保存到: {}", "✓ Full preview list saved to: {}"),
+    ("export.saved", "✓ 已保存到: {}", "✓ Saved to: {}"),
+    (
+        "export.after_id_hint",
+        "  最新的微博 ID 为 {}，下次加上 --after-id {} 即可只导出之后的微博",
+        "  The newest weibo ID is {}; add --after-id {} next time to export only newer weibos",
+    ),
+    (
+        "export.more",
+        "... 还有 {} 条（使用 --output 参数保存完整列表）",
+        "... and {} more (use --output to save the full list)",
+    ),
+    ("export.fetched", "共获取 {} 条微博，符合条件 {} 条", "Fetched {} weibos, {} matched"),
+    ("hide.none_picked", "没有选择任何微博", "No weibos selected"),
+    (
+        "hide.stream_about_to_set",
+        "准备边获取边将 {} 的微博设置为: {}",
+        "About to set the weibos of {} to {} while fetching",
+    ),
+    ("sample.start", "先处理抽样的 {} 条微博", "Processing a sample of {} weibos first"),
+    (
+        "sample.passed",
+        "✓ 抽样验证通过: {} 条微博均已变为{}，继续处理其余 {} 条",
+        "✓ Sample verified: all {} weibos are now {}; processing the remaining {}",
+    ),
+    (
+        "sample.none_applied",
+        "抽样的 {} 条微博均未设置成功，无法验证",
+        "None of the {} sampled weibos were set, so the sample cannot be verified",
+    ),
+    (
+        "sample.failed",
+        "抽样验证失败: {} 条中 {} 条未变为{}",
+        "Sample verification failed: of {} weibos, {} are not {}",
+    ),
+    ("sample.current", "  {} 当前为{}", "  {} is currently {}"),
+    ("sample.not_found", "  {} 重新获取的列表中找不到", "  {} is missing from the refetched list"),
+    ("delete.none", "没有需要删除的微博", "No weibos to delete"),
+    ("delete.to_process", "将要永久删除 {} 条微博", "{} weibos will be permanently deleted"),
+    ("restore.none", "没有需要恢复的微博", "No weibos to restore"),
+    (
+        "restore.about_to_restore",
+        "准备将这 {} 条微博恢复为原可见性",
+        "About to restore these {} weibos to their previous visibility",
+    ),
+    ("apply.none", "没有需要设置的微博", "No weibos to set"),
+    ("apply.group", "设置为 {}: {} 条", "Set to {}: {}"),
+    (
+        "apply.about_to_set",
+        "准备按文件设置这 {} 条微博的可见性",
+        "About to set the visibility of these {} weibos from the file",
+    ),
+    ("accounts.header", "=== 账号 {}/{}: {} ===", "=== Account {}/{}: {} ==="),
+    ("accounts.error", "✗ 账号 {} 处理出错: {}", "✗ Account {} failed: {}"),
+    ("accounts.summary", "=== 多账号汇总（{} 个账号）===", "=== Summary of {} accounts ==="),
+    ("accounts.success", "成功 {} 条", "{} succeeded"),
+    ("accounts.failed", "失败 {} 条", "{} failed"),
+    ("accounts.unsupported", "不支持 {} 条", "{} not supported"),
+    ("accounts.missing", "已删除 {} 条", "{} deleted"),
+    ("accounts.unverified", "未生效 {} 条", "{} not in effect"),
+    ("accounts.unprocessed", "未处理 {} 条", "{} not processed"),
+    ("accounts.row", "  {} {}（{}，{}）: {}", "  {} {} ({}, {}): {}"),
+    ("accounts.nothing", "  ✓ {}: 没有需要处理的微博", "  ✓ {}: nothing to process"),
+    ("accounts.failed_row", "  ✗ {}: 出错: {}", "  ✗ {}: error: {}"),
+    ("accounts.skipped_row", "  ⚠ {}: 已中断，未处理", "  ⚠ {}: interrupted, not processed"),
+    ("accounts.failed_details", "失败详情（按账号）:", "Failures by account:"),
+    ("accounts.account", "  {}（{}，{}）:", "  {} ({}, {}):"),
+];
+
+/// 指定语言下键对应的文字；表中没有该键时原样返回键，便于发现遗漏
+pub fn text(lang: Lang, key: &'static str) -> &'static str {
+    match MESSAGES.iter().find(|(k, _, _)| *k == key) {
+        Some((_, zh, en)) => match lang {
+            Lang::Zh => zh,
+            Lang::En => en,
+        },
+        None => key,
+    }
+}
+
+/// 当前语言下键对应的文字
+pub fn t(key: &'static str) -> &'static str {
+    text(lang(), key)
+}
+
+/// 当前语言下带参数的文字，依次替换模板中的 `{}`；参数多于占位符时多余的忽略
+pub fn tf(key: &'static str, args: &[&dyn fmt::Display]) -> String {
+    fill(t(key), args)
+}
+
+/// 依次用 `args` 替换 `template` 中的 `{}`
+fn fill(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(pos) = rest.find("{}") {
+        out.push_str(&rest[..pos]);
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        rest = &rest[pos + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_have_unique_keys_and_matching_placeholders() {
+        for (idx, (key, zh, en)) in MESSAGES.iter().enumerate() {
+            assert!(
+                MESSAGES[..idx].iter().all(|(k, _, _)| k != key),
+                "重复的键: {}",
+                key
+            );
+            assert_eq!(
+                zh.matches("{}").count(),
+                en.matches("{}").count(),
+                "中英文占位符个数不同: {}",
+                key
+            );
+        }
+    }
+}
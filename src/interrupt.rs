@@ -0,0 +1,43 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+use tracing::warn;
+
+use crate::logging;
+
+/// 是否已收到第一次 Ctrl+C
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// 强制退出时的退出码（128 + SIGINT）
+const FORCE_EXIT_CODE: i32 = 130;
+
+/// 安装 Ctrl+C 处理，只在会修改微博的命令中调用；多次调用只安装一次
+///
+/// 第一次按下时只做标记：不再发起新的请求，进行中的请求完成后按已处理的部分汇总；
+/// 第二次按下立即退出。检查点与失败记录每条都即时写入，中断时不会丢失。
+pub fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        tokio::spawn(async {
+            loop {
+                if tokio::signal::ctrl_c().await.is_err() {
+                    return;
+                }
+                if INTERRUPTED.swap(true, Ordering::SeqCst) {
+                    logging::progress().suspend(|| eprintln!("\n再次收到中断信号，立即退出"));
+                    std::process::exit(FORCE_EXIT_CODE);
+                }
+                warn!("收到中断信号，等待进行中的请求完成后退出（再按一次 Ctrl+C 立即退出）");
+            }
+        });
+    });
+}
+
+/// 因其他原因（如达到 --max-requests）要求停止，效果同第一次 Ctrl+C
+pub fn request_stop() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// 是否已收到中断信号
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
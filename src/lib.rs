@@ -0,0 +1,34 @@
+//! 微博批量隐私设置工具的客户端库
+//!
+//! 命令行工具 `weibo_hide` 基于本库实现，其他 Rust 程序也可以直接使用 [`WeiboPrivacyClient`]
+//! 获取微博列表、设置可见性或删除微博。所有方法的错误类型均为 [`WeiboError`]，可按种类分别处理。
+//!
+//! # 示例
+//!
+//! ```no_run
+//! use weibo_hide::{HttpOptions, Visibility, WeiboPrivacyClient};
+//!
+//! # async fn example() -> Result<(), weibo_hide::WeiboError> {
+//! // Cookie 从浏览器复制，需包含 XSRF-TOKEN
+//! let cookie = "SUB=...; XSRF-TOKEN=...".to_string();
+//! let client = WeiboPrivacyClient::new(cookie, &HttpOptions::default())?;
+//!
+//! let account = client.validate_cookie().await?;
+//! println!("已登录: {}", account.screen_name);
+//!
+//! client
+//!     .set_weibo_privacy("4890000000000001", Visibility::FriendsOnly)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod i18n;
+pub mod rate_limit;
+pub mod stats;
+pub mod weibo_client;
+
+pub use weibo_client::{
+    BatchOutcome, CommentPermission, FailureCategory, FetchOptions, HttpOptions, Visibility,
+    WeiboAccount, WeiboError, WeiboInfo, WeiboPrivacyClient, WeiboPrivacyClientBuilder,
+};
@@ -0,0 +1,193 @@
+use clap::ValueEnum;
+use indicatif::MultiProgress;
+use serde::Serialize;
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::sync::OnceLock;
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter};
+use tracing_subscriber::registry::LookupSpan;
+
+/// 日志格式
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// 便于阅读的文本
+    #[default]
+    Text,
+    /// 每行一个 JSON 对象，并为每条微博的处理结果输出一行，便于其他程序收集
+    Json,
+}
+
+/// 本次运行的日志格式，由 [`init`] 设置
+static FORMAT: OnceLock<LogFormat> = OnceLock::new();
+
+/// 是否以逐行文字代替进度条，由 [`init`] 设置
+static LINE_PROGRESS: OnceLock<bool> = OnceLock::new();
+
+/// 是否以逐行文字代替进度条报告进度
+///
+/// 指定了 `--quiet-progress`，或标准错误不是终端（重定向到日志文件、在 nohup/systemd 下运行）时为 true；
+/// 此时进度条不会显示，改为每隔一段时间输出一行进度。`-q` 或 JSON 日志格式下不输出进度。
+pub fn line_progress() -> bool {
+    LINE_PROGRESS.get().copied().unwrap_or(false)
+}
+
+/// 全局进度条容器，日志输出时先暂停其中的进度条，避免日志与进度条互相覆盖
+pub fn progress() -> &'static MultiProgress {
+    static PROGRESS: OnceLock<MultiProgress> = OnceLock::new();
+    PROGRESS.get_or_init(MultiProgress::new)
+}
+
+/// 初始化日志：默认只显示警告，`-v` 逐级增加到 info/debug/trace，`-q` 只显示错误
+///
+/// `quiet_progress` 为 true 时以逐行文字代替进度条，见 [`line_progress`]。
+pub fn init(verbose: u8, quiet: bool, format: LogFormat, quiet_progress: bool) {
+    let level = if quiet {
+        LevelFilter::ERROR
+    } else {
+        match verbose {
+            0 => LevelFilter::WARN,
+            1 => LevelFilter::INFO,
+            2 => LevelFilter::DEBUG,
+            _ => LevelFilter::TRACE,
+        }
+    };
+
+    let _ = FORMAT.set(format);
+    let _ = LINE_PROGRESS.set(
+        !quiet && format == LogFormat::Text && (quiet_progress || !io::stderr().is_terminal()),
+    );
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(ProgressWriter);
+    match format {
+        LogFormat::Text => builder
+            .with_target(false)
+            .without_time()
+            .with_ansi(io::stderr().is_terminal())
+            .init(),
+        LogFormat::Json => builder.with_ansi(false).event_format(JsonEvents).init(),
+    }
+}
+
+/// 一条微博的处理结果，JSON 日志模式下每条输出一行
+#[derive(Serialize)]
+struct ResultRecord<'a> {
+    ts: String,
+    id: &'a str,
+    action: &'a str,
+    result: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+}
+
+/// 在 JSON 日志模式下输出一条微博的处理结果，文本模式下不输出（结果见最后的汇总）
+///
+/// `action` 如 `visibility:friends`、`comment:closed`、`delete`；
+/// `result` 为 `success`、`failed` 或 `unsupported`。
+pub fn record_result(id: &str, action: &str, result: &str, error: Option<&str>) {
+    if FORMAT.get() == Some(&LogFormat::Json) {
+        print_result(id, action, result, error);
+    }
+}
+
+/// 输出一条微博的处理结果：JSON 日志模式下为一行 JSON，文本模式下为一行文字
+///
+/// 用于 `--ordered-output`，文本模式下同样逐条输出，便于比较两次运行的结果。
+pub fn print_result(id: &str, action: &str, result: &str, error: Option<&str>) {
+    let line = if FORMAT.get() == Some(&LogFormat::Json) {
+        let record = ResultRecord {
+            ts: chrono::Local::now().to_rfc3339(),
+            id,
+            action,
+            result,
+            error,
+        };
+        match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(_) => return,
+        }
+    } else {
+        match (result, error) {
+            ("success", _) => format!("✓ {} {}", id, action),
+            ("unsupported", _) => format!("- {} {}: 不支持目标可见性", id, action),
+            ("missing", _) => format!("- {} {}: 微博已删除或不存在", id, action),
+            (_, Some(error)) => format!("✗ {} {}: {}", id, action, error),
+            (_, None) => format!("✗ {} {}", id, action),
+        }
+    };
+    progress().suspend(|| eprintln!("{}", line));
+}
+
+/// JSON 格式的日志：`{"ts": ..., "level": ..., "message": ..., 其他字段}`
+struct JsonEvents;
+
+impl<S, N> FormatEvent<S, N> for JsonEvents
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let mut object = serde_json::Map::new();
+        object.insert("ts".into(), chrono::Local::now().to_rfc3339().into());
+        object.insert("level".into(), event.metadata().level().to_string().into());
+        event.record(&mut JsonFields(&mut object));
+        writeln!(writer, "{}", serde_json::Value::Object(object))
+    }
+}
+
+/// 将日志事件的字段收集到 JSON 对象中
+struct JsonFields<'a>(&'a mut serde_json::Map<String, serde_json::Value>);
+
+impl Visit for JsonFields<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().into(), format!("{:?}", value).into());
+    }
+}
+
+/// 通过 `MultiProgress::suspend` 写入 stderr 的日志输出
+#[derive(Clone, Copy)]
+struct ProgressWriter;
+
+impl Write for ProgressWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        progress().suspend(|| io::stderr().write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for ProgressWriter {
+    type Writer = ProgressWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        *self
+    }
+}
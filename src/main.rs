@@ -1,45 +1,335 @@
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
-use indicatif::{ProgressBar, ProgressStyle};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::IsTerminal;
+use std::ops::ControlFlow;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
 
-mod weibo_client;
-use weibo_client::{Visibility, WeiboPrivacyClient};
+mod artifacts;
+mod cache;
+mod checkpoint;
+mod config;
+mod exit_code;
+mod failure_log;
+mod filter;
+mod hooks;
+mod interrupt;
+mod logging;
+mod output;
+mod pacing;
+mod picker;
+mod runner;
+mod session;
+use artifacts::RunDir;
+use cache::ListCache;
+use checkpoint::Checkpoint;
+use config::Settings;
+use failure_log::FailureLog;
+use filter::{ExcludeArgs, FilterArgs, Selection, SortOrder};
+use logging::LogFormat;
+use output::{OutputFormat, VisibilityCounts};
+use runner::{PrivacyTask, RunArgs};
+use session::SessionFile;
+use weibo_hide::i18n::{self, t, tf, Lang};
+use weibo_hide::weibo_client::{
+    missing_cookies, parse_header_line, user_agent_preset, Api, CommentPermission, FailureCategory,
+    FetchOptions, Visibility, WeiboAccount, WeiboError, WeiboInfo, WeiboPrivacyClient,
+    DEFAULT_BACKOFF_BASE, DEFAULT_BASE_URL, DEFAULT_MAX_RETRIES, DEFAULT_PAGE_DELAY_SECS,
+    DEFAULT_TIMEOUT_SECS, FEATURE_ALL, MAX_PAGE_SIZE, USER_AGENT_PRESETS,
+};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about = "微博批量隐私设置工具", long_about = None)]
 struct Args {
+    /// 显示更详细的日志（可重复：-v 信息，-vv 调试，-vvv 跟踪）
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+
+    /// 只显示错误和最终结果
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// 日志格式；json 时日志为每行一个 JSON 对象，并为每条微博的处理结果输出一行（均输出到 stderr）
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    /// 不显示进度条，改为每隔一段时间输出一行进度（标准错误不是终端时自动启用），适合重定向到日志文件
+    #[arg(long, global = true)]
+    quiet_progress: bool,
+
+    /// 界面语言: zh(中文), en(英文)；影响汇总、预览、进度等输出，日志与错误信息仍为中文
+    #[arg(long, global = true, default_value = "zh", value_parser = parse_lang)]
+    lang: Lang,
+
+    /// 配置文件路径（默认依次查找 ./weibo_hide.toml 与 ~/.config/weibo_hide.toml）
+    #[arg(long, global = true)]
+    config: Option<String>,
+
+    #[command(flatten)]
+    client: ClientArgs,
+
     #[command(subcommand)]
     command: Commands,
 }
 
-#[derive(Subcommand, Debug)]
+/// HTTP 客户端参数（所有子命令共用，可写在任意位置）
+#[derive(clap::Args, Debug)]
+struct ClientArgs {
+    /// 每个请求的最大尝试次数（含首次请求）
+    #[arg(long, global = true, default_value_t = DEFAULT_MAX_RETRIES, value_parser = clap::value_parser!(u32).range(1..))]
+    max_retries: u32,
+
+    /// 重试退避底数（秒），第 n 次重试前等待约 底数^n 秒（随机浮动 ±50%），单次最多 64 秒
+    #[arg(long, global = true, default_value_t = DEFAULT_BACKOFF_BASE)]
+    backoff_base: u64,
+
+    /// 单个请求的超时时间（秒，含读取响应）
+    #[arg(long, global = true, default_value_t = DEFAULT_TIMEOUT_SECS, value_parser = clap::value_parser!(u64).range(1..))]
+    timeout: u64,
+
+    /// 建立连接的超时时间（秒），默认只受 --timeout 限制
+    #[arg(long, global = true, value_parser = clap::value_parser!(u64).range(1..))]
+    connect_timeout: Option<u64>,
+
+    /// 代理地址，支持 http:// 与 socks5://；未指定时读取 HTTPS_PROXY 等环境变量
+    #[arg(long, global = true, value_parser = parse_proxy)]
+    proxy: Option<String>,
+
+    /// 网页版接口的地址，用于镜像或代理网关；移动版接口（--api mobile）不受影响
+    #[arg(long, global = true, default_value = DEFAULT_BASE_URL, value_parser = parse_base_url)]
+    base_url: String,
+
+    /// 本次运行最多发起的 HTTP 请求数（列表与设置，含重试），达到后中止运行；默认不限，
+    /// 建议按微博数估算后设置，如 2000 条微博、每批 20 条时设为 500 左右，防止误操作时大量请求
+    #[arg(long, global = true, value_parser = clap::value_parser!(u64).range(1..))]
+    max_requests: Option<u64>,
+
+    /// 每分钟最多发起的 HTTP 请求数（列表与设置共用，含重试），并发时也不会超过；默认不限
+    #[arg(long, global = true, value_name = "PER_MIN", value_parser = clap::value_parser!(u32).range(1..))]
+    rate: Option<u32>,
+
+    /// 请求使用的 User-Agent：完整字符串，或内置名称 chrome（默认）、edge、firefox、safari；
+    /// random 表示每次运行随机选用一个内置值（同一次运行中保持不变）
+    #[arg(long, global = true)]
+    user_agent: Option<String>,
+
+    /// 附加到每个请求的请求头，格式为 "名称: 值"，可重复；与内置请求头同名时替换内置值
+    #[arg(long = "header", global = true, value_name = "K: V", value_parser = parse_header)]
+    headers: Vec<String>,
+
+    /// 启动时验证 Cookie 失败即中止运行；默认只记录警告并继续，
+    /// 因为部分账号能正常设置可见性，却无法访问验证所用的账号资料接口
+    #[arg(long, global = true)]
+    strict_validation: bool,
+
+    /// 会话文件：保存验证通过的账号与 Cookie（含重新获取的 XSRF-TOKEN），有效期内再次运行时沿用其中的 Cookie 与 token
+    #[arg(long, global = true, value_name = "PATH")]
+    session: Option<String>,
+
+    /// 会话的有效期，秒数或带单位的时长，如 30m、12h；超过后重新验证 Cookie
+    #[arg(long, global = true, value_name = "DURATION", default_value = "1h", value_parser = parse_duration)]
+    session_ttl: Duration,
+}
+
+/// 启动时检查代理地址，避免到第一次请求才失败
+fn parse_proxy(value: &str) -> std::result::Result<String, String> {
+    weibo_hide::weibo_client::validate_proxy(value).map_err(|e| e.to_string())
+}
+
+fn parse_lang(value: &str) -> std::result::Result<Lang, String> {
+    Lang::from_key(value).ok_or_else(|| format!("不支持的语言: {}（可选 zh、en）", value))
+}
+
+/// 启动时检查自定义请求头
+fn parse_header(value: &str) -> std::result::Result<String, String> {
+    weibo_hide::weibo_client::parse_header_line(value)
+        .map(|_| value.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// 确认提示的参数（Hide、Restore 与 Apply 共用）
+#[derive(clap::Args, Debug, Clone)]
+struct ConfirmArgs {
+    /// 确认时必须输入将要处理的微博条数，而不只是按回车
+    #[arg(long, default_value = "false")]
+    require_typed_confirmation: bool,
+
+    /// 将要处理的微博超过该条数时，自动要求输入条数确认
+    #[arg(long, value_name = "N", default_value = "1000", value_parser = clap::value_parser!(u64).range(1..))]
+    typed_confirmation_threshold: u64,
+}
+
+/// 启动时检查接口地址
+fn parse_base_url(value: &str) -> std::result::Result<String, String> {
+    weibo_hide::weibo_client::validate_base_url(value).map_err(|e| e.to_string())
+}
+
+/// 获取微博列表的参数（Hide 与 List 共用）
+#[derive(clap::Args, Debug, Clone)]
+struct FetchArgs {
+    /// 为被截断的长微博额外请求全文（每条长微博多一次请求）
+    #[arg(long, default_value = "false")]
+    expand_long_text: bool,
+
+    /// 获取列表使用的接口；网页版返回空列表时可改用移动版
+    #[arg(long, value_enum, default_value_t = Api::Desktop)]
+    api: Api,
+
+    /// 获取列表时每页之间的等待时间（秒，可为小数），0 表示不等待
+    #[arg(long, default_value_t = DEFAULT_PAGE_DELAY_SECS, value_parser = parse_seconds)]
+    page_delay: f64,
+
+    /// 按页码翻页时同时请求 N 页（不写 N 时为 4），按顺序拼接结果；会增加瞬时请求数，按游标翻页时无效
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "4", value_parser = clap::value_parser!(u32).range(2..))]
+    parallel_fetch: Option<u32>,
+
+    /// 网页版列表接口每页获取的微博条数（count 参数，最大 100），页数越少请求越少；不指定时使用接口默认值（约 20 条）
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    page_size: Option<u32>,
+
+    /// 最多获取的微博条数，达到后立即停止翻页；与 --max-pages 不同，按条数而不是页数计算（每页条数不固定）
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    max_weibos: Option<u64>,
+
+    /// 网页版列表接口的 feature 参数，按内容类型筛选: 0 全部，1 原创，2 图片，3 视频，4 音乐（以微博实际行为为准）
+    #[arg(long, default_value_t = FEATURE_ALL)]
+    feature: u32,
+
+    /// 只获取该超话中本人发布的微博（超话 ID，即超话页面地址中 100808 开头的部分）；通过移动版接口获取
+    #[arg(long)]
+    topic: Option<String>,
+
+    /// 将获取到的微博列表缓存到该文件；再次运行时若账号、获取选项一致且未过期，直接读取缓存而不再获取
+    #[arg(long, value_name = "PATH")]
+    cache: Option<String>,
+
+    /// 忽略已有的缓存，重新获取并覆盖缓存文件
+    #[arg(long, default_value = "false", requires = "cache")]
+    refresh: bool,
+
+    /// 缓存的有效期，秒数或带单位的时长，如 30m、12h
+    #[arg(long, value_name = "DURATION", default_value = "24h", value_parser = parse_duration)]
+    cache_ttl: Duration,
+}
+
+/// 解析非负的秒数，允许小数，也接受 [`parse_duration`] 支持的带单位写法
+fn parse_seconds(value: &str) -> std::result::Result<f64, String> {
+    parse_duration(value).map(|d| d.as_secs_f64())
+}
+
+/// 解析时长：纯数字按秒计（可为小数）；也可以带单位 ms、s、m、h 并组合使用，如 500ms、1.5s、2m、1m30s
+fn parse_duration(value: &str) -> std::result::Result<Duration, String> {
+    let invalid = || {
+        format!(
+            "无效的时长: {}，应为非负的秒数或带单位的时长，如 500ms、1.5s、2m",
+            value
+        )
+    };
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<f64>() {
+        return Duration::try_from_secs_f64(secs).map_err(|_| invalid());
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = value;
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .ok_or_else(invalid)?;
+        let (number, tail) = rest.split_at(number_end);
+        let unit_end = tail
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(tail.len());
+        let (unit, next) = tail.split_at(unit_end);
+        let number: f64 = number.parse().map_err(|_| invalid())?;
+        let unit_secs = match unit.trim() {
+            "ms" => 0.001,
+            "s" => 1.0,
+            "m" | "min" => 60.0,
+            "h" => 3600.0,
+            _ => return Err(invalid()),
+        };
+        total += Duration::try_from_secs_f64(number * unit_secs).map_err(|_| invalid())?;
+        rest = next.trim_start();
+    }
+    Ok(total)
+}
+
+impl FetchArgs {
+    fn options(&self, max_pages: Option<u32>) -> FetchOptions {
+        if self.topic.is_some() && self.feature != FEATURE_ALL {
+            warn!("指定 --topic 时不支持 --feature，将获取超话中的全部微博");
+        } else if self.api == Api::Mobile && self.feature != FEATURE_ALL {
+            warn!("移动版接口不支持 --feature，将获取全部微博");
+        }
+        if self.page_size.is_some() && (self.topic.is_some() || self.api == Api::Mobile) {
+            warn!("--page-size 只对网页版列表接口有效，将使用接口默认的每页条数");
+        } else if let Some(size) = self.page_size.filter(|n| *n > MAX_PAGE_SIZE) {
+            warn!(
+                "--page-size {} 超过接口允许的最大值，按每页 {} 条获取",
+                size, MAX_PAGE_SIZE
+            );
+        }
+        FetchOptions {
+            max_pages,
+            expand_long_text: self.expand_long_text,
+            api: self.api,
+            page_delay: Duration::from_secs_f64(self.page_delay),
+            max_weibos: self.max_weibos.map(|n| n as usize),
+            feature: self.feature,
+            topic: self.topic.clone(),
+            parallel_pages: self.parallel_fetch.unwrap_or(1),
+            after_id: None,
+            page_size: self.page_size,
+        }
+    }
+
+    /// 指定了 --cache 时的列表缓存
+    fn cache(&self) -> Option<ListCache> {
+        self.cache
+            .clone()
+            .map(|path| ListCache::new(path, self.refresh, self.cache_ttl))
+    }
+}
+
+// 只在启动时解析一次，各子命令的参数个数相差较大也无妨
+#[allow(clippy::large_enum_variant)]
+#[derive(Subcommand, Debug, Clone)]
 enum Commands {
     /// 批量设置微博隐私
     Hide {
-        /// 微博用户ID
+        /// 微博用户ID（默认为当前登录的账号）；多个 Cookie 文件时可重复，按顺序与 Cookie 文件对应
         #[arg(short, long)]
-        user_id: String,
+        user_id: Vec<String>,
 
-        /// Cookie字符串（从浏览器复制）
+        /// Cookie字符串（从浏览器复制）；未提供 Cookie 参数时读取环境变量 WEIBO_COOKIE
         #[arg(short, long)]
         cookie: Option<String>,
 
-        /// Cookie文件路径
+        /// Cookie文件路径（支持浏览器插件导出的 .json 文件）；可重复，多个时依次处理每个账号
         #[arg(short = 'f', long)]
-        cookie_file: Option<String>,
+        cookie_file: Vec<String>,
 
         /// 最大处理页数（默认处理所有）
         #[arg(short = 'p', long)]
         max_pages: Option<u32>,
 
         /// 隐私级别: public(公开), friends(仅好友), private(仅自己), fans(仅粉丝)
-        #[arg(short = 'v', long, default_value = "friends")]
+        #[arg(long, default_value = "friends")]
         visibility: String,
 
-        /// 延迟时间（秒），每条微博设置后的等待时间
-        #[arg(short = 'd', long, default_value = "1")]
-        delay: u64,
+        #[command(flatten)]
+        run: RunArgs,
 
         /// 跳过前N条微博
         #[arg(short = 's', long, default_value = "0")]
@@ -52,45 +342,1065 @@ enum Commands {
         /// 只显示将要处理的微博，不实际修改
         #[arg(long, default_value = "false")]
         dry_run: bool,
+
+        /// 预览模式下将完整的待处理列表输出到文件
+        #[arg(short, long, requires = "dry_run")]
+        output: Option<String>,
+
+        /// 预览列表的输出格式；json 输出的是计划清单，逐条列出将要进行的操作与跳过的原因
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// 处理顺序（默认保持接口返回的顺序），与 --skip、--limit 配合可从最旧的微博开始分批处理
+        #[arg(long, value_enum, conflicts_with = "stream")]
+        sort: Option<SortOrder>,
+
+        /// 跳过确认提示，直接开始修改（用于脚本或定时任务）
+        #[arg(short = 'y', long, default_value = "false")]
+        yes: bool,
+
+        #[command(flatten)]
+        confirm: ConfirmArgs,
+
+        /// 只处理当前为该可见性的微博，已与目标相同或更严格的会跳过
+        #[arg(long)]
+        only_if: Option<String>,
+
+        /// 可见性设置成功后同时设置评论权限: everyone(所有人), following(我关注的人), fans(粉丝), closed(关闭评论)
+        #[arg(long)]
+        comment_permission: Option<String>,
+
+        /// 置顶微博也照常处理（默认跳过置顶微博）
+        #[arg(long, default_value = "false")]
+        include_pinned: bool,
+
+        /// 边获取列表边设置，不等全部获取完成（适合微博很多的账号，不显示总数）
+        #[arg(long, default_value = "false", conflicts_with_all = ["dry_run", "from_failures", "ids_file"])]
+        stream: bool,
+
+        /// 获取并筛选后在终端中逐条勾选要处理的微博，只处理选中的部分（适合精确挑选几十条）
+        #[arg(long, default_value = "false", conflicts_with_all = ["stream", "from_failures", "ids_file"])]
+        interactive: bool,
+
+        /// 每条微博设置成功后重新读取详情，确认可见性确实变为目标（请求数加倍）
+        #[arg(long, default_value = "false")]
+        verify: bool,
+
+        /// 先只处理前 N 条，重新获取列表确认其可见性已变为目标后再处理其余微博；确认失败时中止
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..), conflicts_with_all = ["stream", "dry_run", "from_failures", "ids_file"])]
+        sample: Option<u64>,
+
+        /// 从 --failure-log 生成的失败记录中读取微博 ID 重新处理，不再获取微博列表
+        #[arg(long)]
+        from_failures: Option<String>,
+
+        /// 只处理该文件中的微博 ID（每行一个），不再获取微博列表，也不应用筛选条件
+        #[arg(long, conflicts_with = "from_failures")]
+        ids_file: Option<String>,
+
+        /// 检查点文件路径，记录已完成的微博，再次运行时自动跳过
+        #[arg(long)]
+        checkpoint: Option<String>,
+
+        /// 在该目录下为本次运行创建 run_<时间>/ 子目录，写入 weibos.json、processed.csv 与 failures.jsonl
+        #[arg(long, conflicts_with_all = ["checkpoint", "failure_log"])]
+        output_dir: Option<String>,
+
+        #[command(flatten)]
+        fetch: FetchArgs,
+
+        #[command(flatten)]
+        filter: FilterArgs,
+
+        #[command(flatten)]
+        exclude: ExcludeArgs,
+    },
+
+    /// 永久删除微博（不可恢复）
+    Delete {
+        /// 微博用户ID（默认为当前登录的账号）
+        #[arg(short, long)]
+        user_id: Option<String>,
+
+        /// Cookie字符串（从浏览器复制）；未提供 Cookie 参数时读取环境变量 WEIBO_COOKIE
+        #[arg(short, long)]
+        cookie: Option<String>,
+
+        /// Cookie文件路径（支持浏览器插件导出的 .json 文件）
+        #[arg(short = 'f', long)]
+        cookie_file: Option<String>,
+
+        /// 最大处理页数（默认处理所有）
+        #[arg(short = 'p', long)]
+        max_pages: Option<u32>,
+
+        #[command(flatten)]
+        run: RunArgs,
+
+        /// 跳过前N条微博
+        #[arg(short = 's', long, default_value = "0")]
+        skip: usize,
+
+        /// 限制删除的微博数量
+        #[arg(short = 'l', long)]
+        limit: Option<usize>,
+
+        /// 只显示将要删除的微博，不实际删除
+        #[arg(long, default_value = "false")]
+        dry_run: bool,
+
+        /// 跳过确认提示（需同时指定 --i-understand-this-is-permanent）
+        #[arg(short = 'y', long, default_value = "false")]
+        yes: bool,
+
+        /// 确认已了解删除不可恢复（需同时指定 --yes 才会跳过确认提示）
+        #[arg(long, default_value = "false")]
+        i_understand_this_is_permanent: bool,
+
+        #[command(flatten)]
+        fetch: FetchArgs,
+
+        #[command(flatten)]
+        filter: FilterArgs,
+
+        #[command(flatten)]
+        exclude: ExcludeArgs,
+    },
+
+    /// 根据 hide 生成的检查点文件恢复微博原来的可见性
+    Restore {
+        /// Cookie字符串（从浏览器复制）；未提供 Cookie 参数时读取环境变量 WEIBO_COOKIE
+        #[arg(short, long)]
+        cookie: Option<String>,
+
+        /// Cookie文件路径（支持浏览器插件导出的 .json 文件）
+        #[arg(short = 'f', long)]
+        cookie_file: Option<String>,
+
+        /// hide 使用 --checkpoint 生成的记录文件（每行 id,old_visibility）
+        #[arg(short, long)]
+        input: String,
+
+        /// 记录中缺少原可见性时恢复为该级别
+        #[arg(long, default_value = "public")]
+        visibility: String,
+
+        #[command(flatten)]
+        run: RunArgs,
+
+        /// 只显示将要恢复的微博，不实际修改
+        #[arg(long, default_value = "false")]
+        dry_run: bool,
+
+        /// 跳过确认提示，直接开始恢复
+        #[arg(short = 'y', long, default_value = "false")]
+        yes: bool,
+
+        #[command(flatten)]
+        confirm: ConfirmArgs,
+    },
+
+    /// 按 CSV 文件逐条设置可见性（每行 id,visibility），不获取微博列表
+    Apply {
+        /// Cookie字符串（从浏览器复制）；未提供 Cookie 参数时读取环境变量 WEIBO_COOKIE
+        #[arg(short, long)]
+        cookie: Option<String>,
+
+        /// Cookie文件路径（支持浏览器插件导出的 .json 文件）
+        #[arg(short = 'f', long)]
+        cookie_file: Option<String>,
+
+        /// 目标可见性文件：每行 id,visibility，或含 id 与 visibility 列的 CSV（如 list --format csv 的导出）
+        #[arg(short, long)]
+        input: String,
+
+        #[command(flatten)]
+        run: RunArgs,
+
+        /// 只显示将要设置的微博，不实际修改
+        #[arg(long, default_value = "false")]
+        dry_run: bool,
+
+        /// 跳过确认提示，直接开始设置
+        #[arg(short = 'y', long, default_value = "false")]
+        yes: bool,
+
+        #[command(flatten)]
+        confirm: ConfirmArgs,
+    },
+
+    /// 统计微博总数及各可见性的数量（不输出列表）
+    Count {
+        /// 微博用户ID（默认为当前登录的账号）
+        #[arg(short, long)]
+        user_id: Option<String>,
+
+        /// Cookie字符串（从浏览器复制）；未提供 Cookie 参数时读取环境变量 WEIBO_COOKIE
+        #[arg(short, long)]
+        cookie: Option<String>,
+
+        /// Cookie文件路径（支持浏览器插件导出的 .json 文件）
+        #[arg(short = 'f', long)]
+        cookie_file: Option<String>,
+
+        /// 最大统计页数（默认统计所有）
+        #[arg(short = 'p', long)]
+        max_pages: Option<u32>,
+
+        #[command(flatten)]
+        fetch: FetchArgs,
+
+        #[command(flatten)]
+        filter: FilterArgs,
     },
 
     /// 获取微博列表（不修改）
     List {
-        /// 微博用户ID
+        /// 微博用户ID（默认为当前登录的账号）
         #[arg(short, long)]
-        user_id: String,
+        user_id: Option<String>,
 
-        /// Cookie字符串（从浏览器复制）
+        /// Cookie字符串（从浏览器复制）；未提供 Cookie 参数时读取环境变量 WEIBO_COOKIE
         #[arg(short, long)]
         cookie: Option<String>,
 
-        /// Cookie文件路径
-        #[arg(short = 'f', long)]
-        cookie_file: Option<String>,
+        /// Cookie文件路径（支持浏览器插件导出的 .json 文件）
+        #[arg(short = 'f', long)]
+        cookie_file: Option<String>,
+
+        /// 最大获取页数
+        #[arg(short = 'p', long, default_value = "1")]
+        max_pages: u32,
+
+        /// 输出到文件；为 - 时将完整列表输出到 stdout，便于管道处理
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// 输出格式（json/csv 未指定 --output 时输出完整列表到终端）
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// 排序方式（默认保持接口返回的顺序）
+        #[arg(long, value_enum)]
+        sort: Option<SortOrder>,
+
+        /// 只获取 ID 大于该值的微博（如上次导出中最大的 ID），到达该 ID 后不再翻页，用于增量导出
+        #[arg(long, value_name = "ID")]
+        after_id: Option<u64>,
+
+        #[command(flatten)]
+        fetch: FetchArgs,
+
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+
+    /// 显示 Cookie 对应的登录账号（不修改），用于运行其他命令前检查 Cookie 是否有效
+    Whoami {
+        /// Cookie字符串（从浏览器复制）；未提供 Cookie 参数时读取环境变量 WEIBO_COOKIE
+        #[arg(short, long)]
+        cookie: Option<String>,
+
+        /// Cookie文件路径（支持浏览器插件导出的 .json 文件）
+        #[arg(short = 'f', long)]
+        cookie_file: Option<String>,
+    },
+}
+
+/// 等待用户确认，`yes` 为 true 时直接跳过
+///
+/// 通常按回车即可继续；`count` 条数超过阈值或指定了 `--require-typed-confirmation` 时，
+/// 必须输入条数本身才会继续，输入不符即取消。边获取边处理时条数未知（`count` 为 None），
+/// 这时不能要求输入条数。
+/// 标准输入不是终端且未指定 `--yes` 时报错，避免在脚本中无限等待。
+fn wait_for_confirmation(yes: bool, count: Option<usize>, confirm: &ConfirmArgs) -> Result<()> {
+    if yes {
+        return Ok(());
+    }
+    if count.is_none() && confirm.require_typed_confirmation {
+        return Err(anyhow::anyhow!(
+            "边获取边处理时无法预先知道微博数量，--require-typed-confirmation 不能与 --stream 同时使用"
+        ));
+    }
+    if !std::io::stdin().is_terminal() {
+        return Err(anyhow::anyhow!(
+            "标准输入不是终端，无法交互确认；在脚本或定时任务中运行请加上 --yes 参数"
+        ));
+    }
+    let typed = count.filter(|&n| {
+        confirm.require_typed_confirmation || n as u64 > confirm.typed_confirmation_threshold
+    });
+    let Some(count) = typed else {
+        println!("{}", t("confirm.press_enter"));
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+        return Ok(());
+    };
+    println!("{}", tf("confirm.type_count", &[&count, &count]));
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    if input.trim() != count.to_string() {
+        return Err(anyhow::anyhow!("输入与微博数量不符，已取消"));
+    }
+    Ok(())
+}
+
+/// 删除前需要输入的确认文字
+const DELETE_CONFIRMATION: &str = "DELETE";
+
+/// 删除前要求输入确认文字，`skip` 为 true 时直接跳过
+///
+/// 只有同时指定 `--yes` 与 `--i-understand-this-is-permanent` 才能跳过；
+/// 否则必须在终端中输入确认文字，标准输入不是终端时报错。
+fn confirm_permanent_deletion(count: usize, skip: bool) -> Result<()> {
+    if skip {
+        return Ok(());
+    }
+    if !std::io::stdin().is_terminal() {
+        return Err(anyhow::anyhow!(
+            "标准输入不是终端，无法交互确认；非交互运行需同时指定 --yes 与 --i-understand-this-is-permanent"
+        ));
+    }
+    println!(
+        "{}",
+        tf("confirm.type_delete", &[&count, &DELETE_CONFIRMATION])
+    );
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    if input.trim() != DELETE_CONFIRMATION {
+        return Err(anyhow::anyhow!("已取消删除"));
+    }
+    Ok(())
+}
+
+/// 打印前 10 条微博的预览，`offset` 为序号的起始偏移
+fn print_preview(weibos: &[WeiboInfo], offset: usize) {
+    println!("{}", t("preview.header"));
+    for (idx, weibo) in weibos.iter().take(10).enumerate() {
+        let text = weibo
+            .text
+            .as_ref()
+            .map(|s| {
+                let preview: String = s.chars().take(30).collect();
+                preview
+            })
+            .unwrap_or_else(|| t("weibo.no_text").to_string());
+        println!("  {}. ID: {} - {}...", idx + 1 + offset, weibo.id, text);
+        if let Some(url) = weibo.permalink() {
+            println!("     {}", url);
+        }
+    }
+    if weibos.len() > 10 {
+        println!("{}", tf("preview.more", &[&(weibos.len() - 10)]));
+    }
+}
+
+/// 逐条显示预览中可见性的变化：先列出将要修改的，再列出已是目标可见性的
+fn print_diff(changing: &[WeiboInfo], unchanged: &[WeiboInfo], target: Visibility) {
+    let snippet = |weibo: &WeiboInfo| -> String {
+        weibo
+            .text
+            .as_deref()
+            .unwrap_or(t("weibo.no_text"))
+            .chars()
+            .take(20)
+            .collect()
+    };
+    let link = |weibo: &WeiboInfo| -> String {
+        weibo
+            .permalink()
+            .map(|url| format!(" ({})", url))
+            .unwrap_or_default()
+    };
+    println!("{}", t("diff.header"));
+    for weibo in changing {
+        let current = weibo
+            .visibility
+            .map(|v| v.as_str())
+            .unwrap_or(t("visibility.unknown"));
+        println!(
+            "  ID {}: {} → {} - {}{}",
+            weibo.id,
+            current,
+            target.as_str(),
+            snippet(weibo),
+            link(weibo)
+        );
+    }
+    for weibo in unchanged {
+        println!(
+            "  ID {}: {} - {}{}",
+            weibo.id,
+            tf("diff.unchanged", &[&target.as_str()]),
+            snippet(weibo),
+            link(weibo)
+        );
+    }
+    println!(
+        "\n{}",
+        tf(
+            "diff.total",
+            &[
+                &(changing.len() + unchanged.len()),
+                &changing.len(),
+                &unchanged.len()
+            ]
+        )
+    );
+}
+
+/// 预览计划清单：将要设置的微博在前（保持处理顺序），之后是各类跳过的微博及原因
+fn plan_entries<'a>(
+    changing: &'a [WeiboInfo],
+    selection: &'a Selection,
+    target: Visibility,
+) -> Vec<output::PlanEntry<'a>> {
+    let skipped = [
+        (&selection.unchanged, "already_at_target"),
+        (&selection.skipped_pinned, "pinned"),
+        (&selection.completed_weibos, "completed"),
+        (&selection.excluded_weibos, "excluded"),
+    ];
+    changing
+        .iter()
+        .map(|w| output::PlanEntry::new(w, target, None))
+        .chain(skipped.into_iter().flat_map(|(weibos, reason)| {
+            weibos
+                .iter()
+                .map(move |w| output::PlanEntry::new(w, target, Some(reason)))
+        }))
+        .collect()
+}
+
+/// 提示跳过了哪些置顶微博，避免用户不知道它们的存在
+fn print_skipped_pinned(pinned: &[WeiboInfo]) {
+    for weibo in pinned {
+        let text: String = weibo
+            .text
+            .as_deref()
+            .unwrap_or(t("weibo.no_text"))
+            .chars()
+            .take(30)
+            .collect();
+        println!("{}", tf("pinned.skipped", &[&weibo.id, &text]));
+    }
+    if !pinned.is_empty() {
+        println!("{}\n", t("pinned.hint"));
+    }
+}
+
+/// 预计获取的微博总数
+///
+/// 获取自己的微博时使用账号资料中的微博数，并受 `--max-weibos` 限制；
+/// 限制了页数、指定了超话或 `--after-id` 时未知。
+fn expected_count(account: &WeiboAccount, user_id: &str, options: &FetchOptions) -> Option<u64> {
+    if options.max_pages.is_some() || options.topic.is_some() || options.after_id.is_some() {
+        return None;
+    }
+    let total = account
+        .statuses_count
+        .filter(|_| account.uid == user_id)
+        .and_then(|n| u64::try_from(n).ok());
+    let max_weibos = options.max_weibos.map(|n| n as u64);
+    match (total, max_weibos) {
+        (Some(total), Some(max)) => Some(total.min(max)),
+        (total, max) => total.or(max),
+    }
+}
+
+/// 获取用户的全部微博，获取过程中显示已获取的页数与条数
+async fn fetch_all(
+    client: &WeiboPrivacyClient,
+    user_id: &str,
+    options: &FetchOptions,
+    expected: Option<u64>,
+    cache: Option<&ListCache>,
+) -> Result<Vec<WeiboInfo>> {
+    if let Some(weibos) = cache.and_then(|cache| cache.load(user_id, options)) {
+        return Ok(weibos);
+    }
+
+    info!("正在获取微博列表...");
+    let pb = runner::new_fetch_progress(expected);
+    let mut weibos = Vec::new();
+    let mut pages = 0;
+    let result = client
+        .for_each_weibo_page(user_id, options, |page| {
+            pages += 1;
+            pb.inc(page.len() as u64);
+            pb.set_message(tf("progress.page", &[&pages]));
+            weibos.extend(page);
+            async { Ok::<_, anyhow::Error>(ControlFlow::Continue(())) }
+        })
+        .await;
+    pb.finish_and_clear();
+    result?;
+    info!("✓ 共获取 {} 条微博", weibos.len());
+    if let Some(cache) = cache {
+        cache.save(user_id, options, &weibos)?;
+    }
+    Ok(weibos)
+}
+
+/// 获取用户的全部微博并应用筛选条件
+async fn fetch_filtered(
+    client: &WeiboPrivacyClient,
+    account: &WeiboAccount,
+    user_id: &str,
+    options: &FetchOptions,
+    filter: &FilterArgs,
+    cache: Option<&ListCache>,
+) -> Result<Vec<WeiboInfo>> {
+    let expected = expected_count(account, user_id, options);
+    let weibos = fetch_all(client, user_id, options, expected, cache).await?;
+    filter.apply(weibos)
+}
+
+/// 浏览器插件导出的单条 Cookie（EditThisCookie、Cookie-Editor 等）
+#[derive(Deserialize)]
+struct ExportedCookie {
+    name: String,
+    value: String,
+    #[serde(default)]
+    domain: String,
+}
+
+/// 从浏览器插件导出的 JSON 中拼出 Cookie 字符串，只保留 weibo.com 域名下的条目
+fn cookie_from_json(content: &str) -> Result<String> {
+    let cookies: Vec<ExportedCookie> = serde_json::from_str(content)
+        .context("无法解析 Cookie JSON，应为 {name, value, domain} 对象数组")?;
+
+    let pairs: Vec<String> = cookies
+        .iter()
+        .filter(|c| c.domain.trim_start_matches('.').ends_with("weibo.com"))
+        .map(|c| format!("{}={}", c.name, c.value))
+        .collect();
+
+    if pairs.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Cookie JSON 中没有 weibo.com 域名下的 Cookie"
+        ));
+    }
+    Ok(pairs.join("; "))
+}
+
+/// 未通过参数提供 Cookie 时读取的环境变量
+const COOKIE_ENV_VAR: &str = "WEIBO_COOKIE";
+
+/// 从命令行参数、文件或环境变量读取 Cookie，优先级依次降低
+///
+/// 文件扩展名为 `.json` 时按浏览器插件导出的格式解析，否则视为原始 Cookie 字符串。
+fn load_cookie(cookie: &Option<String>, cookie_file: &Option<String>) -> Result<String> {
+    if let Some(cookie_str) = cookie {
+        Ok(cookie_str.clone())
+    } else if let Some(cookie_path) = cookie_file {
+        info!("从文件读取 Cookie: {}", cookie_path);
+        let cookie_content = fs::read_to_string(cookie_path)
+            .context(format!("无法读取 Cookie 文件: {}", cookie_path))?
+            .trim()
+            .to_string();
+        if cookie_path.to_lowercase().ends_with(".json") {
+            cookie_from_json(&cookie_content)
+        } else {
+            Ok(cookie_content)
+        }
+    } else if let Some(cookie_env) = std::env::var(COOKIE_ENV_VAR)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+    {
+        info!("从环境变量 {} 读取 Cookie", COOKIE_ENV_VAR);
+        Ok(cookie_env)
+    } else {
+        Err(anyhow::anyhow!(
+            "必须提供 Cookie，使用 --cookie、--cookie-file 参数或设置环境变量 {}",
+            COOKIE_ENV_VAR
+        ))
+    }
+}
+
+/// 边获取边设置时队列中最多暂存的微博数量
+const STREAM_BUFFER: usize = 100;
+
+/// 重新获取列表，核对抽样处理的微博可见性是否已变为目标（`--sample`）
+///
+/// 返回未生效的微博及其当前可见性，在列表中找不到的为 None；全部找到后即停止翻页。
+async fn verify_sample(
+    client: &WeiboPrivacyClient,
+    user_id: &str,
+    options: &FetchOptions,
+    ids: &[String],
+    target: Visibility,
+) -> Result<Vec<(String, Option<Visibility>)>> {
+    info!("重新获取微博列表，核对抽样的 {} 条微博", ids.len());
+    let mut pending: HashSet<&str> = ids.iter().map(String::as_str).collect();
+    let mut current = HashMap::new();
+    client
+        .for_each_weibo_page(user_id, options, |weibos| {
+            for weibo in weibos {
+                if pending.remove(weibo.id.as_str()) {
+                    current.insert(weibo.id, weibo.visibility);
+                }
+            }
+            let flow = if pending.is_empty() {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            };
+            async move { Ok::<_, anyhow::Error>(flow) }
+        })
+        .await?;
+
+    Ok(ids
+        .iter()
+        .filter_map(|id| match current.get(id) {
+            Some(&visibility) if visibility == Some(target) => None,
+            Some(&visibility) => Some((id.clone(), visibility)),
+            None => Some((id.clone(), None)),
+        })
+        .collect())
+}
+
+/// 确定要处理的用户 ID：优先使用参数或配置文件中的值，否则使用当前登录的账号
+fn resolve_user_id(user_id: Option<String>, account: &WeiboAccount) -> Result<String> {
+    if let Some(user_id) = user_id {
+        return Ok(user_id);
+    }
+    if account.uid.is_empty() {
+        return Err(anyhow::anyhow!(
+            "无法从登录信息中获取用户 ID，请使用 --user-id 指定"
+        ));
+    }
+    info!("未指定 --user-id，使用当前登录的账号: {}", account.uid);
+    Ok(account.uid.clone())
+}
+
+/// 指定的 --user-id 不是当前登录的账号时提前警告：只能修改自己发布的微博，返回是否不一致
+fn warn_if_other_account(user_id: Option<&str>, account: &WeiboAccount) -> bool {
+    let other = match user_id {
+        Some(user_id) => !account.uid.is_empty() && user_id != account.uid,
+        None => false,
+    };
+    if other {
+        warn!(
+            "--user-id {} 不是当前登录的账号 {}（{}）：只能修改自己发布的微博，对他人微博的设置会失败；请确认 --user-id 与 Cookie 是否对应",
+            user_id.unwrap_or_default(),
+            account.uid,
+            account.screen_name
+        );
+    }
+    other
+}
+
+/// 账号不一致且有失败时，提示失败很可能由此导致
+fn hint_other_account(other_account: bool, summary: &runner::RunSummary) {
+    if other_account && !summary.failed_ids.is_empty() {
+        warn!("失败的微博可能不属于当前登录的账号，请检查 --user-id 是否填错");
+    }
+}
+
+/// 用配置文件中的值补全命令行未显式指定的参数
+fn apply_settings(args: &mut Args, matches: &ArgMatches, settings: &Settings) {
+    config::fill(matches, "lang", &mut args.lang, &settings.lang);
+    config::fill(
+        matches,
+        "max_retries",
+        &mut args.client.max_retries,
+        &settings.max_retries,
+    );
+    config::fill(
+        matches,
+        "backoff_base",
+        &mut args.client.backoff_base,
+        &settings.backoff_base,
+    );
+    config::fill(
+        matches,
+        "timeout",
+        &mut args.client.timeout,
+        &settings.timeout,
+    );
+    config::fill_option(
+        matches,
+        "connect_timeout",
+        &mut args.client.connect_timeout,
+        &settings.connect_timeout,
+    );
+    config::fill_option(matches, "proxy", &mut args.client.proxy, &settings.proxy);
+    config::fill(
+        matches,
+        "base_url",
+        &mut args.client.base_url,
+        &settings.base_url,
+    );
+    config::fill_option(
+        matches,
+        "max_requests",
+        &mut args.client.max_requests,
+        &settings.max_requests,
+    );
+    config::fill_option(matches, "rate", &mut args.client.rate, &settings.rate);
+    config::fill_option(
+        matches,
+        "user_agent",
+        &mut args.client.user_agent,
+        &settings.user_agent,
+    );
+    config::fill(
+        matches,
+        "headers",
+        &mut args.client.headers,
+        &settings.header,
+    );
+    config::fill(
+        matches,
+        "strict_validation",
+        &mut args.client.strict_validation,
+        &settings.strict_validation,
+    );
+    config::fill_option(
+        matches,
+        "session",
+        &mut args.client.session,
+        &settings.session,
+    );
+    config::fill(
+        matches,
+        "session_ttl",
+        &mut args.client.session_ttl,
+        &settings.session_ttl,
+    );
+
+    let Some((_, sub)) = matches.subcommand() else {
+        return;
+    };
+    let fill_fetch = |fetch: &mut FetchArgs| {
+        config::fill(
+            sub,
+            "page_delay",
+            &mut fetch.page_delay,
+            &settings.page_delay,
+        );
+        config::fill_option(
+            sub,
+            "max_weibos",
+            &mut fetch.max_weibos,
+            &settings.max_weibos,
+        );
+        config::fill_option(
+            sub,
+            "parallel_fetch",
+            &mut fetch.parallel_fetch,
+            &settings.parallel_fetch,
+        );
+        config::fill_option(sub, "page_size", &mut fetch.page_size, &settings.page_size);
+    };
+    let fill_run = |run: &mut RunArgs| {
+        config::fill(sub, "delay", &mut run.delay, &settings.delay);
+        config::fill(sub, "batch_size", &mut run.batch_size, &settings.batch_size);
+        config::fill(
+            sub,
+            "concurrency",
+            &mut run.concurrency,
+            &settings.concurrency,
+        );
+        config::fill(
+            sub,
+            "rate_limit_cooldown",
+            &mut run.rate_limit_cooldown,
+            &settings.rate_limit_cooldown,
+        );
+        config::fill_option(
+            sub,
+            "failure_log",
+            &mut run.failure_log,
+            &settings.failure_log,
+        );
+        config::fill_option(
+            sub,
+            "min_interval",
+            &mut run.min_interval,
+            &settings.min_interval,
+        );
+        config::fill(
+            sub,
+            "max_interval",
+            &mut run.max_interval,
+            &settings.max_interval,
+        );
+        config::fill(
+            sub,
+            "auto_retry_passes",
+            &mut run.auto_retry_passes,
+            &settings.auto_retry_passes,
+        );
+        config::fill(
+            sub,
+            "retry_pass_delay",
+            &mut run.retry_pass_delay,
+            &settings.retry_pass_delay,
+        );
+    };
+
+    let fill_confirm = |confirm: &mut ConfirmArgs| {
+        config::fill(
+            sub,
+            "require_typed_confirmation",
+            &mut confirm.require_typed_confirmation,
+            &settings.require_typed_confirmation,
+        );
+        config::fill(
+            sub,
+            "typed_confirmation_threshold",
+            &mut confirm.typed_confirmation_threshold,
+            &settings.typed_confirmation_threshold,
+        );
+    };
+
+    match &mut args.command {
+        Commands::Hide {
+            user_id,
+            cookie_file,
+            max_pages,
+            visibility,
+            run,
+            checkpoint,
+            fetch,
+            confirm,
+            ..
+        } => {
+            // 可重复的参数在命令行中未指定时为空，此时使用配置文件中的单个值
+            if user_id.is_empty() {
+                user_id.extend(settings.user_id.clone());
+            }
+            if cookie_file.is_empty() {
+                cookie_file.extend(settings.cookie_file.clone());
+            }
+            config::fill_option(sub, "max_pages", max_pages, &settings.max_pages);
+            config::fill(sub, "visibility", visibility, &settings.visibility);
+            config::fill_option(sub, "checkpoint", checkpoint, &settings.checkpoint);
+            fill_fetch(fetch);
+            fill_run(run);
+            fill_confirm(confirm);
+        }
+        Commands::Delete {
+            user_id,
+            cookie_file,
+            max_pages,
+            run,
+            fetch,
+            ..
+        } => {
+            config::fill_option(sub, "user_id", user_id, &settings.user_id);
+            config::fill_option(sub, "cookie_file", cookie_file, &settings.cookie_file);
+            config::fill_option(sub, "max_pages", max_pages, &settings.max_pages);
+            fill_fetch(fetch);
+            fill_run(run);
+        }
+        Commands::Restore {
+            cookie_file,
+            run,
+            confirm,
+            ..
+        } => {
+            config::fill_option(sub, "cookie_file", cookie_file, &settings.cookie_file);
+            fill_run(run);
+            fill_confirm(confirm);
+        }
+        Commands::Apply {
+            cookie_file,
+            run,
+            confirm,
+            ..
+        } => {
+            config::fill_option(sub, "cookie_file", cookie_file, &settings.cookie_file);
+            fill_run(run);
+            fill_confirm(confirm);
+        }
+        Commands::Count {
+            user_id,
+            cookie_file,
+            max_pages,
+            fetch,
+            ..
+        } => {
+            config::fill_option(sub, "user_id", user_id, &settings.user_id);
+            config::fill_option(sub, "cookie_file", cookie_file, &settings.cookie_file);
+            config::fill_option(sub, "max_pages", max_pages, &settings.max_pages);
+            fill_fetch(fetch);
+        }
+        Commands::List {
+            user_id,
+            cookie_file,
+            max_pages,
+            fetch,
+            ..
+        } => {
+            config::fill_option(sub, "user_id", user_id, &settings.user_id);
+            config::fill_option(sub, "cookie_file", cookie_file, &settings.cookie_file);
+            config::fill(sub, "max_pages", max_pages, &settings.max_pages);
+            fill_fetch(fetch);
+        }
+        Commands::Whoami { cookie_file, .. } => {
+            config::fill_option(sub, "cookie_file", cookie_file, &settings.cookie_file);
+        }
+    }
+}
+
+/// 按命令行参数创建客户端
+fn build_client(cookie: String, args: &ClientArgs) -> Result<WeiboPrivacyClient> {
+    let missing = missing_cookies(&cookie);
+    if !missing.is_empty() {
+        warn!(
+            "Cookie 中缺少 {}，部分接口可能间歇返回 ok=0；请从浏览器复制完整的 Cookie",
+            missing.join("、")
+        );
+    }
+
+    let mut builder = WeiboPrivacyClient::builder(cookie)
+        .timeout(Duration::from_secs(args.timeout))
+        .max_retries(args.max_retries)
+        .backoff_base(args.backoff_base)
+        .base_url(&args.base_url);
+    if let Some(secs) = args.connect_timeout {
+        builder = builder.connect_timeout(Duration::from_secs(secs));
+    }
+    if let Some(ref proxy) = args.proxy {
+        builder = builder.proxy(proxy);
+    }
+    if let Some(max_requests) = args.max_requests {
+        builder = builder.max_requests(max_requests);
+    }
+    if let Some(rate) = args.rate {
+        info!("请求速率上限: 每分钟 {} 次", rate);
+        builder = builder.rate_limit(rate);
+    }
+    if let Some(ref user_agent) = args.user_agent {
+        builder = builder.user_agent(resolve_user_agent(user_agent));
+    }
+    for line in &args.headers {
+        let (name, value) = parse_header_line(line)?;
+        builder = builder.header(name, value);
+    }
+    Ok(builder.build()?)
+}
+
+/// 解析 --user-agent：内置名称换成对应的值，random 随机选用一个内置值，其余原样使用
+fn resolve_user_agent(value: &str) -> String {
+    if value.eq_ignore_ascii_case("random") {
+        let idx = rand::rng().random_range(0..USER_AGENT_PRESETS.len());
+        let (name, user_agent) = USER_AGENT_PRESETS[idx];
+        info!("本次运行使用内置 User-Agent: {}", name);
+        return user_agent.to_string();
+    }
+    user_agent_preset(value).unwrap_or(value).to_string()
+}
+
+/// 验证 Cookie 并获取当前登录的账号
+///
+/// 验证失败时默认只记录警告并继续，返回 uid 为空的账号：此时需用 --user-id 指定用户，
+/// 账号是否一致的检查与预计总数随之跳过；指定了 --strict-validation 时返回错误。
+async fn login(client: &WeiboPrivacyClient, strict: bool) -> Result<WeiboAccount> {
+    match client.validate_cookie().await {
+        Ok(account) => {
+            info!("✓ 已登录: {} ({})", account.screen_name, account.uid);
+            Ok(account)
+        }
+        Err(e) if strict => Err(e.into()),
+        Err(e) => {
+            warn!(
+                "无法验证 Cookie: {}；将继续运行，Cookie 确实无效时后续请求会失败（加 --strict-validation 可在验证失败时中止）",
+                e
+            );
+            Ok(WeiboAccount {
+                uid: String::new(),
+                screen_name: String::new(),
+                statuses_count: None,
+            })
+        }
+    }
+}
+
+/// 创建客户端并登录，返回客户端、账号与有效的会话（`--session`）
+///
+/// 会话有效时使用其中保存的 Cookie（含之前重新获取的 XSRF-TOKEN），并用一次账号资料请求确认它仍然可用；
+/// 服务端已判定未登录或失效时，改用用户提供的 Cookie 照常验证，验证通过后覆盖会话。
+async fn connect(
+    cookie: String,
+    args: &ClientArgs,
+) -> Result<(WeiboPrivacyClient, WeiboAccount, Option<SessionFile>)> {
+    let session = args
+        .session
+        .as_ref()
+        .map(|path| SessionFile::new(path.clone(), args.session_ttl));
+    if let Some(saved) = session.as_ref().and_then(|s| s.load(&cookie)) {
+        let client = build_client(saved.cookie, args)?;
+        match client.validate_cookie().await {
+            Ok(account) => {
+                info!("✓ 已登录: {} ({})", account.screen_name, account.uid);
+                return Ok((client, account, session));
+            }
+            Err(e) if e.category() == FailureCategory::Auth => {
+                info!("会话中的 Cookie 已失效（{}），将重新验证", e);
+            }
+            Err(e) if args.strict_validation => return Err(e.into()),
+            // 网络等问题与会话本身无关，沿用会话中的账号
+            Err(e) => {
+                warn!("无法确认会话是否有效: {}；将继续使用会话中的账号", e);
+                info!(
+                    "✓ 已登录: {} ({})",
+                    saved.account.screen_name, saved.account.uid
+                );
+                return Ok((client, saved.account, session));
+            }
+        }
+    }
+
+    let client = build_client(cookie.clone(), args)?;
+    let account = login(&client, args.strict_validation).await?;
+    // 未能验证的 Cookie 不写入会话，下次仍然验证
+    let session = session.filter(|_| !account.uid.is_empty());
+    if let Some(ref session) = session {
+        session.save(&cookie, &client, &account);
+    }
+    Ok((client, account, session))
+}
+
+/// 运行结束时把重新获取的 XSRF-TOKEN 写回会话（如有）
+fn update_session(session: Option<&SessionFile>, client: &WeiboPrivacyClient) {
+    if let Some(session) = session {
+        session.update(client);
+    }
+}
 
-        /// 最大获取页数
-        #[arg(short = 'p', long, default_value = "1")]
-        max_pages: u32,
+/// 运行因达到 --max-requests 而中止时给出提示，已处理的部分已由检查点与失败记录保存
+///
+/// 未发出的请求已计入汇总中的未处理数量，退出码为部分成功。
+fn warn_request_limit(client: &WeiboPrivacyClient) {
+    if client.request_limit_hit() {
+        warn!(
+            "已达到 --max-requests 上限（{} 次请求），运行已中止；确认无误后可调大上限重新运行",
+            client.request_count()
+        );
+    }
+}
 
-        /// 输出到文件
-        #[arg(short, long)]
-        output: Option<String>,
-    },
+/// 打印本次运行的产物目录
+fn print_run_dir(dir: &RunDir) {
+    println!("\n{}", tf("run_dir.saved", &[&dir.path().display()]));
 }
 
-/// 从命令行参数或文件读取 Cookie
-fn load_cookie(cookie: &Option<String>, cookie_file: &Option<String>) -> Result<String> {
-    if let Some(cookie_str) = cookie {
-        Ok(cookie_str.clone())
-    } else if let Some(cookie_path) = cookie_file {
-        println!("从文件读取 Cookie: {}", cookie_path);
-        let cookie_content = fs::read_to_string(cookie_path)
-            .context(format!("无法读取 Cookie 文件: {}", cookie_path))?
-            .trim()
-            .to_string();
-        Ok(cookie_content)
-    } else {
-        Err(anyhow::anyhow!("必须提供 Cookie，使用 --cookie 或 --cookie-file 参数"))
+/// 解析评论权限
+fn parse_comment_permission(permission_str: &str) -> Result<CommentPermission> {
+    match permission_str.to_lowercase().as_str() {
+        "everyone" | "所有人" => Ok(CommentPermission::Everyone),
+        "following" | "关注" | "我关注的人" => Ok(CommentPermission::Following),
+        "fans" | "粉丝" => Ok(CommentPermission::Fans),
+        "closed" | "关闭" => Ok(CommentPermission::Closed),
+        _ => Err(anyhow::anyhow!(
+            "无效的评论权限: {}，可选值: everyone, following, fans, closed",
+            permission_str
+        )),
     }
 }
 
@@ -109,150 +1419,990 @@ fn parse_visibility(visibility_str: &str) -> Result<Visibility> {
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    let args = Args::parse();
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(code) => ExitCode::from(code),
+        Err(err) => {
+            eprintln!("Error: {:?}", err);
+            ExitCode::from(exit_code::FAILURE)
+        }
+    }
+}
+
+/// 一个账号的 hide 处理结果
+struct AccountReport {
+    account: WeiboAccount,
+    summary: runner::RunSummary,
+}
+
+/// 多账号运行中一个 Cookie 文件的结果：处理完成（附汇总，筛选后无需处理时没有）、出错或因中断未处理
+enum AccountOutcome {
+    Finished(u8, Option<Box<AccountReport>>),
+    Failed(anyhow::Error),
+    Skipped,
+}
+
+/// 指定了多个 `--cookie-file` 时依次处理每个账号
+///
+/// 每个账号各自创建客户端、登录并照常运行 hide，`--user-id` 按顺序与 Cookie 文件对应（未指定时使用各自登录的账号）。
+/// 某个账号出错时继续处理下一个；收到 Ctrl+C 后其余账号不再处理。最后按账号汇总结果与失败。
+async fn run_accounts(command: Commands, client_args: &ClientArgs) -> Result<u8> {
+    let Commands::Hide {
+        ref cookie_file,
+        ref user_id,
+        ref cookie,
+        ref from_failures,
+        ref ids_file,
+        ref output_dir,
+        ..
+    } = command
+    else {
+        unreachable!("只有 hide 支持多个 Cookie 文件");
+    };
+    if cookie.is_some() {
+        return Err(anyhow::anyhow!(
+            "--cookie 不能与多个 --cookie-file 同时使用"
+        ));
+    }
+    if !user_id.is_empty() && user_id.len() != cookie_file.len() {
+        return Err(anyhow::anyhow!(
+            "指定了 {} 个 --user-id 与 {} 个 --cookie-file，两者的个数应相同（或不指定 --user-id）",
+            user_id.len(),
+            cookie_file.len()
+        ));
+    }
+    // 失败记录与 ID 文件中的微博属于某一个账号，同一时间创建的产物目录也会重名
+    // 会话只对应一个 Cookie，多个账号共用时每次都会互相覆盖
+    if client_args.session.is_some() {
+        return Err(anyhow::anyhow!("多个 --cookie-file 时不能使用 --session"));
+    }
+    if from_failures.is_some() || ids_file.is_some() || output_dir.is_some() {
+        return Err(anyhow::anyhow!(
+            "多个 --cookie-file 时不能使用 --from-failures、--ids-file 或 --output-dir"
+        ));
+    }
+
+    let files = cookie_file.clone();
+    let user_ids = user_id.clone();
+    let mut outcomes = Vec::new();
+    for (i, file) in files.iter().enumerate() {
+        if interrupt::is_interrupted() {
+            outcomes.push((file.clone(), AccountOutcome::Skipped));
+            continue;
+        }
+        println!(
+            "\n{}",
+            tf("accounts.header", &[&(i + 1), &files.len(), file])
+        );
+        let mut account_command = command.clone();
+        if let Commands::Hide {
+            ref mut cookie_file,
+            ref mut user_id,
+            ..
+        } = account_command
+        {
+            *cookie_file = vec![file.clone()];
+            *user_id = user_ids.get(i).cloned().into_iter().collect();
+        }
+
+        let mut report = None;
+        let outcome = match execute(account_command, client_args, Instant::now(), &mut report).await
+        {
+            Ok(code) => AccountOutcome::Finished(code, report.map(Box::new)),
+            Err(e) => {
+                println!("{}", tf("accounts.error", &[file, &format!("{:#}", e)]));
+                AccountOutcome::Failed(e)
+            }
+        };
+        outcomes.push((file.clone(), outcome));
+    }
+
+    print_account_reports(&outcomes);
+    let codes: Vec<u8> = outcomes
+        .iter()
+        .map(|(_, outcome)| match outcome {
+            AccountOutcome::Finished(code, _) => *code,
+            AccountOutcome::Failed(_) => exit_code::FAILURE,
+            AccountOutcome::Skipped => exit_code::PARTIAL,
+        })
+        .collect();
+    // 全部账号都失败才算全部失败，否则有任何问题即为部分成功
+    Ok(if codes.iter().all(|&c| c == exit_code::FAILURE) {
+        exit_code::FAILURE
+    } else if codes.iter().any(|&c| c != exit_code::SUCCESS) {
+        exit_code::PARTIAL
+    } else {
+        exit_code::SUCCESS
+    })
+}
+
+/// 打印多账号运行的汇总：每个账号一行，失败按账号分别列出
+fn print_account_reports(outcomes: &[(String, AccountOutcome)]) {
+    println!("\n{}", tf("accounts.summary", &[&outcomes.len()]));
+    for (file, outcome) in outcomes {
+        match outcome {
+            AccountOutcome::Finished(code, Some(report)) => {
+                let summary = &report.summary;
+                let mut parts = vec![tf("accounts.success", &[&summary.success_count])];
+                let counts = [
+                    ("accounts.failed", summary.failed_ids.len()),
+                    ("accounts.unsupported", summary.unsupported_ids.len()),
+                    ("accounts.missing", summary.missing_ids.len()),
+                    ("accounts.unverified", summary.unverified_ids.len()),
+                    ("accounts.unprocessed", summary.unprocessed),
+                ];
+                for (key, n) in counts {
+                    if n > 0 {
+                        parts.push(tf(key, &[&n]));
+                    }
+                }
+                let mark = if *code == exit_code::SUCCESS {
+                    "✓"
+                } else {
+                    "✗"
+                };
+                println!(
+                    "{}",
+                    tf(
+                        "accounts.row",
+                        &[
+                            &mark,
+                            file,
+                            &report.account.screen_name,
+                            &report.account.uid,
+                            &parts.join(t("summary.note_separator")),
+                        ]
+                    )
+                );
+            }
+            AccountOutcome::Finished(_, None) => println!("{}", tf("accounts.nothing", &[file])),
+            AccountOutcome::Failed(e) => {
+                println!(
+                    "{}",
+                    tf("accounts.failed_row", &[file, &format!("{:#}", e)])
+                )
+            }
+            AccountOutcome::Skipped => println!("{}", tf("accounts.skipped_row", &[file])),
+        }
+    }
+
+    let with_failures: Vec<(&String, &AccountReport)> = outcomes
+        .iter()
+        .filter_map(|(file, outcome)| match outcome {
+            AccountOutcome::Finished(_, Some(report)) => Some((file, report.as_ref())),
+            _ => None,
+        })
+        .filter(|(_, r)| !r.summary.failed_ids.is_empty() || !r.summary.unverified_ids.is_empty())
+        .collect();
+    if with_failures.is_empty() {
+        return;
+    }
+    println!("\n{}", t("accounts.failed_details"));
+    for (file, report) in with_failures {
+        println!(
+            "{}",
+            tf(
+                "accounts.account",
+                &[&report.account.screen_name, &report.account.uid, file]
+            )
+        );
+        let failures = report
+            .summary
+            .failed_ids
+            .iter()
+            .map(|(id, _, err)| (id, err))
+            .chain(
+                report
+                    .summary
+                    .unverified_ids
+                    .iter()
+                    .map(|(id, err)| (id, err)),
+            );
+        for (id, err) in failures.take(10) {
+            println!("    - ID {}: {}", id, err);
+        }
+    }
+}
+
+/// 执行命令，返回进程退出码；开始处理前出错时返回错误
+async fn run() -> Result<u8> {
+    let started = Instant::now();
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    logging::init(
+        args.verbose,
+        args.quiet,
+        args.log_format,
+        args.quiet_progress,
+    );
+
+    let (settings, config_path) = Settings::load(args.config.as_deref())?;
+    if let Some(path) = config_path {
+        info!("✓ 已读取配置文件: {}", path.display());
+        apply_settings(&mut args, &matches, &settings);
+    }
+    i18n::set_lang(args.lang);
 
     match args.command {
+        Commands::Hide {
+            ref cookie_file, ..
+        } if cookie_file.len() > 1 => run_accounts(args.command, &args.client).await,
+        command => execute(command, &args.client, started, &mut None).await,
+    }
+}
+
+/// 执行一个子命令，返回进程退出码
+///
+/// hide 处理完成时将所用账号与处理结果写入 `report`，供多账号运行时汇总。
+async fn execute(
+    command: Commands,
+    client_args: &ClientArgs,
+    started: Instant,
+    report: &mut Option<AccountReport>,
+) -> Result<u8> {
+    let mut code = exit_code::SUCCESS;
+    match command {
         Commands::Hide {
             user_id,
             cookie,
             cookie_file,
             max_pages,
             visibility,
-            delay,
+            mut run,
             skip,
             limit,
             dry_run,
+            output,
+            format,
+            sort,
+            yes,
+            confirm,
+            only_if,
+            comment_permission,
+            mut checkpoint,
+            output_dir,
+            from_failures,
+            ids_file,
+            include_pinned,
+            stream,
+            interactive,
+            verify,
+            sample,
+            fetch,
+            filter,
+            exclude,
         } => {
-            println!("=== 微博批量隐私设置工具 ===\n");
+            info!("=== 微博批量隐私设置工具 ===");
+            if user_id.len() > 1 {
+                return Err(anyhow::anyhow!(
+                    "指定了多个 --user-id，但只有一个 Cookie，两者的个数应相同"
+                ));
+            }
+            let user_id = user_id.into_iter().next();
 
-            // 读取 Cookie
-            let cookie_data = load_cookie(&cookie, &cookie_file)?;
+            // 读取 Cookie（多个 Cookie 文件时由 run_accounts 逐个传入）
+            let cookie_data = load_cookie(&cookie, &cookie_file.into_iter().next())?;
 
             // 解析隐私级别
             let visibility_level = parse_visibility(&visibility)?;
+            let only_if = only_if.as_deref().map(parse_visibility).transpose()?;
+            let comment_permission = comment_permission
+                .as_deref()
+                .map(parse_comment_permission)
+                .transpose()?;
 
-            println!("目标用户 ID: {}", user_id);
-            println!("隐私级别: {}", visibility_level.as_str());
+            if let Some(ref uid) = user_id {
+                info!("目标用户 ID: {}", uid);
+            }
+            info!("隐私级别: {}", visibility_level.as_str());
             if let Some(pages) = max_pages {
-                println!("最大处理页数: {}", pages);
+                info!("最大处理页数: {}", pages);
+            }
+            if let Some(current) = only_if {
+                info!("只处理当前为: {}", current.as_str());
+            }
+            if let Some(permission) = comment_permission {
+                info!("评论权限: {}", permission.as_str());
+            }
+            info!("跳过前 {} 条", skip);
+            info!("每批 {} 条", run.batch_size);
+            if run.concurrency > 1 {
+                info!("并发数: {}", run.concurrency);
+            }
+            if let Some(min) = run.min_interval {
+                info!("自适应请求间隔: {}s ~ {}s", min, run.max_interval);
             }
-            println!("跳过前 {} 条", skip);
             if let Some(n) = limit {
-                println!("限制处理 {} 条", n);
+                info!("限制处理 {} 条", n);
+            }
+            if let Some(ref path) = checkpoint {
+                info!("检查点文件: {}", path);
             }
             if dry_run {
-                println!("⚠️  预览模式：只显示将要处理的微博，不实际修改");
+                warn!("预览模式：只显示将要处理的微博，不实际修改");
             }
-            println!();
 
             // 创建客户端
-            println!("正在初始化客户端...");
-            let client = WeiboPrivacyClient::new(cookie_data)?;
-            println!("✓ 客户端初始化成功\n");
+            info!("正在初始化客户端...");
+            let (client, account, session) = connect(cookie_data, client_args).await?;
+            info!("✓ 客户端初始化成功");
+            let other_account = warn_if_other_account(user_id.as_deref(), &account);
 
-            // 获取所有微博
-            println!("正在获取微博列表...");
-            let weibos = client.get_all_weibo_ids(&user_id, max_pages).await?;
-            println!("✓ 共获取 {} 条微博\n", weibos.len());
+            let run_dir = output_dir.as_deref().map(RunDir::create).transpose()?;
+            if let Some(ref dir) = run_dir {
+                info!("本次运行的产物目录: {}", dir.path().display());
+                checkpoint = Some(dir.processed_path());
+                run.failure_log = Some(dir.failures_path());
+            }
 
-            if weibos.is_empty() {
-                println!("没有找到微博");
-                return Ok(());
+            let mut selection = Selection::new(visibility_level, skip, limit);
+            // 直接按 ID 处理时没有微博内容与可见性，筛选条件无从判断
+            if from_failures.is_none() && ids_file.is_none() {
+                selection = selection.with_filter(&filter, only_if);
+            }
+            if let Some(ref path) = checkpoint {
+                selection =
+                    selection.with_completed(Checkpoint::load_completed(path, visibility_level)?);
             }
+            selection = selection
+                .with_excluded(exclude.load()?)
+                .with_pinned(include_pinned)
+                .with_unchanged_kept(dry_run);
+            let cache = fetch.cache();
 
-            // 跳过指定数量
-            let mut weibos_to_process: Vec<_> = weibos.into_iter().skip(skip).collect();
+            if stream {
+                let user_id = resolve_user_id(user_id, &account)?;
+                if cache.is_some() {
+                    warn!("边获取边设置（--stream）时不读写 --cache 指定的缓存");
+                }
 
-            // 限制处理数量
-            if let Some(n) = limit {
-                weibos_to_process.truncate(n);
+                println!(
+                    "{}",
+                    tf(
+                        "hide.stream_about_to_set",
+                        &[&user_id, &visibility_level.as_str()]
+                    )
+                );
+                wait_for_confirmation(yes, None, &confirm)?;
+
+                let checkpoint = match checkpoint {
+                    Some(ref path) => Some(Arc::new(Checkpoint::open(path, visibility_level)?)),
+                    None => None,
+                };
+                let client = Arc::new(client);
+                let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+                let options = fetch.options(max_pages);
+                let mut fetched_weibos = Vec::new();
+
+                // 获取与设置同时进行：获取到的每页微博筛选后立即放入队列，队列满时暂停获取
+                let fetching = async {
+                    let result = client
+                        .for_each_weibo_page(&user_id, &options, |weibos| {
+                            if run_dir.is_some() {
+                                fetched_weibos.extend(weibos.iter().cloned());
+                            }
+                            let tasks = selection.take(weibos).map(|weibos| {
+                                weibos
+                                    .into_iter()
+                                    .map(|w| PrivacyTask {
+                                        id: w.id,
+                                        target: visibility_level,
+                                        previous: w.visibility,
+                                        comment_permission,
+                                        verify,
+                                    })
+                                    .collect::<Vec<_>>()
+                            });
+                            let is_full = selection.is_full();
+                            let tx = tx.clone();
+                            async move {
+                                // 收到 Ctrl+C 后不再获取，已放入队列的任务由设置流程统计为未处理
+                                if interrupt::is_interrupted() {
+                                    return Ok(ControlFlow::Break(()));
+                                }
+                                for task in tasks? {
+                                    if tx.send(task).await.is_err() {
+                                        if interrupt::is_interrupted() {
+                                            return Ok(ControlFlow::Break(()));
+                                        }
+                                        return Err(anyhow::anyhow!("设置流程已中止"));
+                                    }
+                                }
+                                Ok(if is_full {
+                                    ControlFlow::Break(())
+                                } else {
+                                    ControlFlow::Continue(())
+                                })
+                            }
+                        })
+                        .await;
+                    // 关闭队列，设置流程处理完剩余任务后结束
+                    drop(tx);
+                    result
+                };
+                let updating = runner::run_privacy_updates_from(
+                    Arc::clone(&client),
+                    rx,
+                    None,
+                    &run,
+                    checkpoint,
+                );
+                interrupt::install();
+                let (fetched, summary) = tokio::join!(fetching, updating);
+
+                let mut summary = summary?;
+                if selection.skipped_completed > 0 {
+                    info!(
+                        "✓ 检查点中已完成的 {} 条已跳过",
+                        selection.skipped_completed
+                    );
+                }
+                if selection.skipped_excluded > 0 {
+                    info!("✓ 已排除指定的 {} 条微博", selection.skipped_excluded);
+                }
+                print_skipped_pinned(&selection.skipped_pinned);
+                summary.record_skipped(visibility_level, selection.already_at_target);
+                summary.print();
+                hint_other_account(other_account, &summary);
+                client.latency().print(started.elapsed());
+                if let Some(ref dir) = run_dir {
+                    output::write_file(&dir.weibos_path(), &fetched_weibos, OutputFormat::Json)?;
+                    print_run_dir(dir);
+                }
+                warn_request_limit(&client);
+                update_session(session.as_ref(), &client);
+                let mut code = summary.exit_code();
+                if let Err(err) = fetched {
+                    warn!("获取微博列表中途出错，已获取的部分已处理: {:#}", err);
+                    code = code.max(exit_code::PARTIAL);
+                }
+                *report = Some(AccountReport { account, summary });
+                return Ok(code);
+            }
+
+            let mut weibos: Vec<WeiboInfo> = if let Some(ref path) = from_failures {
+                // 直接处理失败记录中的微博，不获取列表也不应用筛选条件
+                let ids = FailureLog::load_ids(path)?;
+                info!("✓ 从失败记录 {} 读取 {} 条微博", path, ids.len());
+                ids.into_iter().map(WeiboInfo::from_id).collect()
+            } else if let Some(ref path) = ids_file {
+                let ids = filter::read_id_file(path)?;
+                info!("✓ 从 {} 读取 {} 条微博 ID", path, ids.len());
+                ids.into_iter().map(WeiboInfo::from_id).collect()
+            } else {
+                let user_id = resolve_user_id(user_id.clone(), &account)?;
+
+                let options = fetch.options(max_pages);
+                let expected = expected_count(&account, &user_id, &options);
+                fetch_all(&client, &user_id, &options, expected, cache.as_ref()).await?
+            };
+
+            if let Some(ref dir) = run_dir {
+                output::write_file(&dir.weibos_path(), &weibos, OutputFormat::Json)?;
+            }
+            if weibos.is_empty() {
+                println!("{}", t("hide.none_found"));
+                return Ok(exit_code::FAILURE);
+            }
+            if let Some(order) = sort {
+                filter::sort_weibos(&mut weibos, order);
+            }
+
+            let weibos_to_process = selection.take(weibos)?;
+            if selection.skipped_excluded > 0 {
+                info!("✓ 已排除指定的 {} 条微博", selection.skipped_excluded);
+            }
+            print_skipped_pinned(&selection.skipped_pinned);
+            if selection.skipped_completed > 0 {
+                info!(
+                    "✓ 检查点中已完成 {} 条，本次跳过 {} 条",
+                    selection.completed_count(),
+                    selection.skipped_completed
+                );
+            }
+            if selection.already_at_target > 0 {
+                info!(
+                    "✓ {} 条微博已是{}，跳过",
+                    selection.already_at_target,
+                    visibility_level.as_str()
+                );
             }
 
             if weibos_to_process.is_empty() {
-                println!("跳过后没有需要处理的微博");
-                return Ok(());
+                if dry_run && !selection.unchanged.is_empty() {
+                    print_diff(&[], &selection.unchanged, visibility_level);
+                }
+                if let (true, Some(output_path)) =
+                    (dry_run && format == OutputFormat::Json, &output)
+                {
+                    let entries = plan_entries(&[], &selection, visibility_level);
+                    output::write_plan(output_path, &entries)?;
+                    println!("{}", tf("plan.saved", &[output_path]));
+                }
+                println!("{}", t("hide.none_selected"));
+                return Ok(exit_code::SUCCESS);
             }
 
-            println!("将要处理 {} 条微博\n", weibos_to_process.len());
+            let weibos_to_process = if interactive {
+                let selected = picker::select_weibos(weibos_to_process)?;
+                if selected.is_empty() {
+                    println!("{}", t("hide.none_picked"));
+                    return Ok(exit_code::SUCCESS);
+                }
+                selected
+            } else {
+                weibos_to_process
+            };
+
+            println!("{}\n", tf("hide.to_process", &[&weibos_to_process.len()]));
 
             if dry_run {
-                println!("预览前10条:");
-                for (idx, weibo) in weibos_to_process.iter().take(10).enumerate() {
-                    let text = weibo
-                        .text
-                        .as_ref()
-                        .map(|s| {
-                            let preview: String = s.chars().take(30).collect();
-                            preview
-                        })
-                        .unwrap_or_else(|| "无内容".to_string());
-                    println!(
-                        "  {}. ID: {} - {}...",
-                        idx + 1 + skip,
-                        weibo.id,
-                        text
-                    );
+                VisibilityCounts::from_weibos(&weibos_to_process).print();
+                println!();
+
+                if let Some(ref output_path) = output {
+                    if format == OutputFormat::Json {
+                        output::write_plan(
+                            output_path,
+                            &plan_entries(&weibos_to_process, &selection, visibility_level),
+                        )?;
+                        println!("{}\n", tf("plan.saved", &[output_path]));
+                    } else {
+                        output::write_file(output_path, &weibos_to_process, format)?;
+                        println!("{}\n", tf("preview.saved", &[output_path]));
+                    }
                 }
-                if weibos_to_process.len() > 10 {
-                    println!("  ... 还有 {} 条", weibos_to_process.len() - 10);
+
+                print_diff(&weibos_to_process, &selection.unchanged, visibility_level);
+                if let Some(ref dir) = run_dir {
+                    print_run_dir(dir);
                 }
-                println!("\n使用相同命令但不加 --dry-run 参数即可开始修改");
-                return Ok(());
+                println!("\n{}", t("hide.dry_run_hint"));
+                return Ok(exit_code::SUCCESS);
             }
 
             // 确认
-            println!("准备将这些微博设置为: {}", visibility_level.as_str());
-            println!("按 Ctrl+C 取消，或按回车继续...");
-            let mut input = String::new();
-            std::io::stdin().read_line(&mut input)?;
-
-            // 创建进度条
-            let pb = ProgressBar::new(weibos_to_process.len() as u64);
-            pb.set_style(
-                ProgressStyle::default_bar()
-                    .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
-                    .unwrap()
-                    .progress_chars("#>-"),
-            );
-
-            let mut success_count = 0;
-            let mut failed_count = 0;
-            let mut failed_ids = Vec::new();
+            println!("{}", tf("hide.about_to_set", &[&visibility_level.as_str()]));
+            wait_for_confirmation(yes, Some(weibos_to_process.len()), &confirm)?;
 
-            for weibo in weibos_to_process {
-                let result = client.set_weibo_privacy(&weibo.id, visibility_level).await;
+            let checkpoint = match checkpoint {
+                Some(ref path) => Some(Arc::new(Checkpoint::open(path, visibility_level)?)),
+                None => None,
+            };
+            let mut tasks: Vec<PrivacyTask> = weibos_to_process
+                .into_iter()
+                .map(|w| PrivacyTask {
+                    id: w.id,
+                    target: visibility_level,
+                    previous: w.visibility,
+                    comment_permission,
+                    verify,
+                })
+                .collect();
 
-                match result {
-                    Ok(_) => {
-                        success_count += 1;
-                        pb.set_message(format!("✓ {} 成功", weibo.id));
-                    }
-                    Err(e) => {
-                        failed_count += 1;
-                        failed_ids.push((weibo.id.clone(), e.to_string()));
-                        pb.set_message(format!("✗ {} 失败: {}", weibo.id, e));
+            let client = Arc::new(client);
+            interrupt::install();
+            let mut summary = match sample.map(|n| n as usize) {
+                Some(n) if n < tasks.len() => {
+                    let rest = tasks.split_off(n);
+                    let sample_ids: Vec<String> = tasks.iter().map(|t| t.id.clone()).collect();
+                    println!("{}", tf("sample.start", &[&n]));
+                    let mut summary = runner::run_privacy_updates(
+                        Arc::clone(&client),
+                        tasks,
+                        &run,
+                        checkpoint.clone(),
+                    )
+                    .await?;
+                    if summary.interrupted {
+                        summary.unprocessed += rest.len();
+                    } else {
+                        // 只核对设置成功的微博；失败、不支持与已删除的已在统计中单独列出
+                        let applied: Vec<String> = sample_ids
+                            .into_iter()
+                            .filter(|id| summary.succeeded(id))
+                            .collect();
+                        let mismatched = if applied.is_empty() {
+                            None
+                        } else {
+                            // 列表已获取成功过，此处的用户 ID 不会为空
+                            let user_id = user_id.unwrap_or_else(|| account.uid.clone());
+                            let options = fetch.options(max_pages);
+                            Some(
+                                verify_sample(
+                                    &client,
+                                    &user_id,
+                                    &options,
+                                    &applied,
+                                    visibility_level,
+                                )
+                                .await?,
+                            )
+                        };
+                        match mismatched {
+                            Some(mismatched) if mismatched.is_empty() => {
+                                println!(
+                                    "{}",
+                                    tf(
+                                        "sample.passed",
+                                        &[&applied.len(), &visibility_level.as_str(), &rest.len()]
+                                    )
+                                );
+                                let rest_summary = runner::run_privacy_updates(
+                                    Arc::clone(&client),
+                                    rest,
+                                    &run,
+                                    checkpoint,
+                                )
+                                .await?;
+                                summary.merge(rest_summary);
+                            }
+                            mismatched => {
+                                summary.print();
+                                if let Some(ref cache) = cache {
+                                    cache.invalidate();
+                                }
+                                match mismatched {
+                                    None => println!("\n{}", tf("sample.none_applied", &[&n])),
+                                    Some(mismatched) => {
+                                        println!(
+                                            "\n{}",
+                                            tf(
+                                                "sample.failed",
+                                                &[
+                                                    &applied.len(),
+                                                    &mismatched.len(),
+                                                    &visibility_level.as_str()
+                                                ]
+                                            )
+                                        );
+                                        for (id, current) in &mismatched {
+                                            match current {
+                                                Some(v) => {
+                                                    println!(
+                                                        "{}",
+                                                        tf("sample.current", &[id, &v.as_str()])
+                                                    )
+                                                }
+                                                None => {
+                                                    println!("{}", tf("sample.not_found", &[id]))
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                                return Err(anyhow::anyhow!(
+                                    "抽样验证失败，其余 {} 条微博未处理",
+                                    rest.len()
+                                ));
+                            }
+                        }
                     }
+                    summary
+                }
+                _ => {
+                    runner::run_privacy_updates(Arc::clone(&client), tasks, &run, checkpoint)
+                        .await?
+                }
+            };
+            summary.record_skipped(visibility_level, selection.already_at_target);
+            summary.print();
+            hint_other_account(other_account, &summary);
+            if let Some(ref cache) = cache {
+                if summary.success_count > 0 || !summary.missing_ids.is_empty() {
+                    cache.invalidate();
+                }
+            }
+            client.latency().print(started.elapsed());
+            if let Some(ref dir) = run_dir {
+                print_run_dir(dir);
+            }
+            warn_request_limit(&client);
+            update_session(session.as_ref(), &client);
+            code = summary.exit_code();
+            *report = Some(AccountReport { account, summary });
+        }
+
+        Commands::Delete {
+            user_id,
+            cookie,
+            cookie_file,
+            max_pages,
+            run,
+            skip,
+            limit,
+            dry_run,
+            yes,
+            i_understand_this_is_permanent,
+            fetch,
+            filter,
+            exclude,
+        } => {
+            info!("=== 永久删除微博 ===");
+
+            let cookie_data = load_cookie(&cookie, &cookie_file)?;
+            let excluded_ids = exclude.load()?;
+
+            if let Some(ref uid) = user_id {
+                info!("目标用户 ID: {}", uid);
+            }
+            if dry_run {
+                warn!("预览模式：只显示将要删除的微博，不实际删除");
+            }
+
+            let (client, account, session) = connect(cookie_data, client_args).await?;
+            let other_account = warn_if_other_account(user_id.as_deref(), &account);
+            let user_id = resolve_user_id(user_id, &account)?;
+
+            let cache = fetch.cache();
+            let weibos = fetch_filtered(
+                &client,
+                &account,
+                &user_id,
+                &fetch.options(max_pages),
+                &filter,
+                cache.as_ref(),
+            )
+            .await?;
+
+            let mut weibos_to_delete: Vec<_> = weibos.into_iter().skip(skip).collect();
+            let excluded = filter::remove_excluded(&mut weibos_to_delete, &excluded_ids);
+            if excluded > 0 {
+                info!("✓ 已排除指定的 {} 条微博", excluded);
+            }
+            if let Some(n) = limit {
+                weibos_to_delete.truncate(n);
+            }
+
+            if weibos_to_delete.is_empty() {
+                println!("{}", t("delete.none"));
+                return Ok(exit_code::SUCCESS);
+            }
+
+            println!("{}\n", tf("delete.to_process", &[&weibos_to_delete.len()]));
+
+            if dry_run {
+                print_preview(&weibos_to_delete, skip);
+                println!("\n{}", t("delete.dry_run_hint"));
+                return Ok(exit_code::SUCCESS);
+            }
+
+            confirm_permanent_deletion(
+                weibos_to_delete.len(),
+                yes && i_understand_this_is_permanent,
+            )?;
+
+            let ids = weibos_to_delete.into_iter().map(|w| w.id).collect();
+            let client = Arc::new(client);
+            interrupt::install();
+            let summary = runner::run_deletions(Arc::clone(&client), ids, &run).await?;
+            summary.print();
+            hint_other_account(other_account, &summary);
+            if let Some(ref cache) = cache {
+                if summary.success_count > 0 || !summary.missing_ids.is_empty() {
+                    cache.invalidate();
                 }
+            }
+            client.latency().print(started.elapsed());
+            warn_request_limit(&client);
+            update_session(session.as_ref(), &client);
+            code = summary.exit_code();
+        }
+
+        Commands::Restore {
+            cookie,
+            cookie_file,
+            input,
+            visibility,
+            run,
+            dry_run,
+            yes,
+            confirm,
+        } => {
+            info!("=== 恢复微博可见性 ===");
+
+            // 读取 Cookie
+            let cookie_data = load_cookie(&cookie, &cookie_file)?;
+            let fallback = parse_visibility(&visibility)?;
+
+            let entries = Checkpoint::load_entries(&input)?;
+            info!("✓ 从 {} 读取 {} 条记录", input, entries.len());
 
-                pb.inc(1);
+            let missing = entries.iter().filter(|(_, old)| old.is_none()).count();
+            if missing > 0 {
+                warn!(
+                    "{} 条记录没有原可见性，将恢复为: {}",
+                    missing,
+                    fallback.as_str()
+                );
+            }
+
+            if entries.is_empty() {
+                println!("{}", t("restore.none"));
+                return Ok(exit_code::SUCCESS);
+            }
+
+            let tasks: Vec<PrivacyTask> = entries
+                .into_iter()
+                .map(|(id, old)| PrivacyTask {
+                    id,
+                    target: old.unwrap_or(fallback),
+                    previous: None,
+                    comment_permission: None,
+                    verify: false,
+                })
+                .collect();
 
-                // 延迟
-                if delay > 0 {
-                    tokio::time::sleep(tokio::time::Duration::from_secs(delay)).await;
+            if dry_run {
+                println!("{}", t("preview.header"));
+                for (idx, task) in tasks.iter().take(10).enumerate() {
+                    println!("  {}. ID: {} → {}", idx + 1, task.id, task.target.as_str());
+                }
+                if tasks.len() > 10 {
+                    println!("{}", tf("preview.more", &[&(tasks.len() - 10)]));
                 }
+                println!("\n{}", t("restore.dry_run_hint"));
+                return Ok(exit_code::SUCCESS);
+            }
+
+            // 创建客户端
+            let (client, _, session) = connect(cookie_data, client_args).await?;
+
+            // 确认
+            println!("{}", tf("restore.about_to_restore", &[&tasks.len()]));
+            wait_for_confirmation(yes, Some(tasks.len()), &confirm)?;
+
+            let client = Arc::new(client);
+            interrupt::install();
+            let summary =
+                runner::run_privacy_updates(Arc::clone(&client), tasks, &run, None).await?;
+            summary.print();
+            client.latency().print(started.elapsed());
+            warn_request_limit(&client);
+            update_session(session.as_ref(), &client);
+            code = summary.exit_code();
+        }
+
+        Commands::Apply {
+            cookie,
+            cookie_file,
+            input,
+            run,
+            dry_run,
+            yes,
+            confirm,
+        } => {
+            info!("=== 按文件设置微博可见性 ===");
+
+            // 读取 Cookie
+            let cookie_data = load_cookie(&cookie, &cookie_file)?;
+
+            let targets = filter::read_targets_file(&input)?;
+            info!("✓ 从 {} 读取 {} 条微博", input, targets.len());
+            if targets.is_empty() {
+                println!("{}", t("apply.none"));
+                return Ok(exit_code::SUCCESS);
             }
 
-            pb.finish_with_message("完成");
+            // 按目标可见性分组（组的顺序与组内顺序都保持文件中的顺序），使每批请求尽量满额
+            let mut groups: Vec<(Visibility, Vec<String>)> = Vec::new();
+            for (id, target) in targets {
+                match groups.iter_mut().find(|(v, _)| *v == target) {
+                    Some((_, ids)) => ids.push(id),
+                    None => groups.push((target, vec![id])),
+                }
+            }
+            for (target, ids) in &groups {
+                println!("{}", tf("apply.group", &[&target.as_str(), &ids.len()]));
+            }
+            let tasks: Vec<PrivacyTask> = groups
+                .into_iter()
+                .flat_map(|(target, ids)| {
+                    ids.into_iter().map(move |id| PrivacyTask {
+                        id,
+                        target,
+                        previous: None,
+                        comment_permission: None,
+                        verify: false,
+                    })
+                })
+                .collect();
 
-            println!("\n=== 处理完成 ===");
-            println!("✓ 成功: {} 条", success_count);
-            if failed_count > 0 {
-                println!("✗ 失败: {} 条", failed_count);
-                println!("\n失败详情:");
-                for (id, err) in failed_ids.iter().take(10) {
-                    println!("  - ID {}: {}", id, err);
+            if dry_run {
+                println!("\n{}", t("preview.header"));
+                for (idx, task) in tasks.iter().take(10).enumerate() {
+                    println!("  {}. ID: {} → {}", idx + 1, task.id, task.target.as_str());
                 }
+                if tasks.len() > 10 {
+                    println!("{}", tf("preview.more", &[&(tasks.len() - 10)]));
+                }
+                println!("\n{}", t("apply.dry_run_hint"));
+                return Ok(exit_code::SUCCESS);
+            }
+
+            // 创建客户端
+            let (client, _, session) = connect(cookie_data, client_args).await?;
+
+            // 确认
+            println!("{}", tf("apply.about_to_set", &[&tasks.len()]));
+            wait_for_confirmation(yes, Some(tasks.len()), &confirm)?;
+
+            let client = Arc::new(client);
+            interrupt::install();
+            let summary =
+                runner::run_privacy_updates(Arc::clone(&client), tasks, &run, None).await?;
+            summary.print();
+            client.latency().print(started.elapsed());
+            warn_request_limit(&client);
+            update_session(session.as_ref(), &client);
+            code = summary.exit_code();
+        }
+
+        Commands::Count {
+            user_id,
+            cookie,
+            cookie_file,
+            max_pages,
+            fetch,
+            filter,
+        } => {
+            info!("=== 统计微博数量 ===");
+
+            let cookie_data = load_cookie(&cookie, &cookie_file)?;
+
+            let (client, account, _) = connect(cookie_data, client_args).await?;
+            let user_id = resolve_user_id(user_id, &account)?;
+
+            let options = fetch.options(max_pages);
+            let expected = expected_count(&account, &user_id, &options);
+            let mut fetched = 0;
+            let mut counts = VisibilityCounts::default();
+            if let Some(cache) = fetch.cache() {
+                // 使用缓存时需要保留完整列表
+                let weibos = fetch_all(&client, &user_id, &options, expected, Some(&cache)).await?;
+                fetched = weibos.len();
+                counts.add(&filter.apply(weibos)?);
+            } else {
+                // 逐页筛选并累计数量，不保留微博内容
+                let pb = runner::new_fetch_progress(expected);
+                let result = client
+                    .for_each_weibo_page(&user_id, &options, |weibos| {
+                        fetched += weibos.len();
+                        pb.inc(weibos.len() as u64);
+                        let result = filter.apply(weibos).map(|weibos| {
+                            counts.add(&weibos);
+                            ControlFlow::Continue(())
+                        });
+                        async { result }
+                    })
+                    .await;
+                pb.finish_and_clear();
+                result?;
+            }
+
+            println!("{}", tf("export.fetched", &[&fetched, &counts.total()]));
+            if counts.total() > 0 {
+                counts.print();
             }
         }
 
@@ -262,39 +2412,62 @@ async fn main() -> Result<()> {
             cookie_file,
             max_pages,
             output,
+            format,
+            sort,
+            after_id,
+            fetch,
+            filter,
         } => {
-            println!("=== 获取微博列表 ===\n");
+            info!("=== 获取微博列表 ===");
 
             // 读取 Cookie
             let cookie_data = load_cookie(&cookie, &cookie_file)?;
 
-            println!("目标用户 ID: {}", user_id);
-            println!("最大获取页数: {}\n", max_pages);
+            if let Some(ref uid) = user_id {
+                info!("目标用户 ID: {}", uid);
+            }
+            info!("最大获取页数: {}", max_pages);
 
             // 创建客户端
-            let client = WeiboPrivacyClient::new(cookie_data)?;
+            let (client, account, _) = connect(cookie_data, client_args).await?;
+            let user_id = resolve_user_id(user_id, &account)?;
 
             // 获取微博
-            let weibos = client.get_all_weibo_ids(&user_id, Some(max_pages)).await?;
-
-            println!("\n共获取 {} 条微博\n", weibos.len());
+            let options = FetchOptions {
+                after_id,
+                ..fetch.options(Some(max_pages))
+            };
+            if let Some(id) = after_id {
+                info!("只获取 ID 大于 {} 的微博", id);
+            }
+            let mut weibos = fetch_filtered(
+                &client,
+                &account,
+                &user_id,
+                &options,
+                &filter,
+                fetch.cache().as_ref(),
+            )
+            .await?;
+            if let Some(order) = sort {
+                filter::sort_weibos(&mut weibos, order);
+            }
+            let newest = weibos.iter().filter_map(|w| w.id.parse::<u64>().ok()).max();
+            if let (None, Some(id)) = (newest, after_id) {
+                info!("没有 ID 大于 {} 的微博", id);
+            }
 
-            // 显示或保存
-            if let Some(output_path) = output {
-                let mut content = String::new();
-                for (idx, weibo) in weibos.iter().enumerate() {
-                    content.push_str(&format!("{}. ID: {}\n", idx + 1, weibo.id));
-                    if let Some(ref text) = weibo.text {
-                        content.push_str(&format!("   内容: {}\n", text));
-                    }
-                    if let Some(ref created_at) = weibo.created_at {
-                        content.push_str(&format!("   时间: {}\n", created_at));
-                    }
-                    content.push_str("\n");
+            // 显示或保存；日志与进度都在 stderr，`--output -` 时 stdout 中只有列表本身
+            if output.as_deref() == Some(output::STDOUT) {
+                output::write_stdout(&weibos, format)?;
+            } else if let Some(output_path) = output {
+                output::write_file(&output_path, &weibos, format)?;
+                println!("{}", tf("export.saved", &[&output_path]));
+                if let Some(newest) = newest {
+                    println!("{}", tf("export.after_id_hint", &[&newest, &newest]));
                 }
-
-                fs::write(&output_path, content)?;
-                println!("✓ 已保存到: {}", output_path);
+            } else if format != OutputFormat::Text {
+                print!("{}", output::render(&weibos, format)?);
             } else {
                 for (idx, weibo) in weibos.iter().take(20).enumerate() {
                     let text = weibo
@@ -304,15 +2477,158 @@ async fn main() -> Result<()> {
                             let preview: String = s.chars().take(50).collect();
                             preview
                         })
-                        .unwrap_or_else(|| "无内容".to_string());
+                        .unwrap_or_else(|| t("weibo.no_text").to_string());
                     println!("{}. ID: {} - {}...", idx + 1, weibo.id, text);
+                    if let Some(url) = weibo.permalink() {
+                        println!("   {}", url);
+                    }
                 }
                 if weibos.len() > 20 {
-                    println!("... 还有 {} 条（使用 --output 参数保存完整列表）", weibos.len() - 20);
+                    println!("{}", tf("export.more", &[&(weibos.len() - 20)]));
                 }
             }
         }
+
+        Commands::Whoami {
+            cookie,
+            cookie_file,
+        } => {
+            let cookie_data = load_cookie(&cookie, &cookie_file)?;
+
+            // 没有 XSRF-TOKEN 时无法创建客户端，也无法设置可见性或删除微博
+            if WeiboPrivacyClient::extract_xsrf_token(&cookie_data).is_none() {
+                println!("{}", t("account.xsrf_missing"));
+                return Err(WeiboError::InvalidCookie.into());
+            }
+
+            let client = build_client(cookie_data, client_args)?;
+            let account = client.validate_cookie().await?;
+            println!("{}", tf("account.logged_in", &[&account.screen_name]));
+            println!("{}", tf("account.uid", &[&account.uid]));
+            let statuses = match account.statuses_count {
+                Some(n) => tf("summary.count", &[&n]),
+                None => t("visibility.unknown").to_string(),
+            };
+            println!("{}", tf("account.statuses", &[&statuses]));
+            println!("{}", t("account.xsrf_found"));
+        }
     }
 
-    Ok(())
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 解析命令行并应用配置，返回最终参数
+    fn args_with_settings(argv: &[&str], settings: &Settings) -> Args {
+        let matches = Args::command().get_matches_from(argv);
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        apply_settings(&mut args, &matches, settings);
+        args
+    }
+
+    #[test]
+    fn command_line_overrides_config_and_config_overrides_default() {
+        let settings: Settings = toml::from_str(
+            r#"
+visibility = "private"
+delay = 2
+batch-size = 10
+max-retries = 5
+backoff-base = 3
+"#,
+        )
+        .unwrap();
+
+        // 全局参数写在子命令之前或之后都算显式指定
+        let args = args_with_settings(
+            &[
+                "weibo_hide",
+                "--max-retries",
+                "2",
+                "hide",
+                "-u",
+                "1",
+                "-d",
+                "4",
+            ],
+            &settings,
+        );
+        assert_eq!(args.client.max_retries, 2);
+        assert_eq!(args.client.backoff_base, 3);
+        match args.command {
+            Commands::Hide {
+                visibility, run, ..
+            } => {
+                assert_eq!(visibility, "private");
+                assert_eq!(run.delay, Duration::from_secs(4));
+                assert_eq!(run.batch_size, 10);
+            }
+            command => panic!("{:?}", command),
+        }
+
+        let args = args_with_settings(
+            &["weibo_hide", "hide", "-u", "1", "--backoff-base", "1"],
+            &settings,
+        );
+        assert_eq!(args.client.max_retries, 5);
+        assert_eq!(args.client.backoff_base, 1);
+
+        // 没有配置时使用默认值
+        let args = args_with_settings(&["weibo_hide", "hide", "-u", "1"], &Settings::default());
+        assert_eq!(args.client.max_retries, DEFAULT_MAX_RETRIES);
+        assert_eq!(args.client.backoff_base, DEFAULT_BACKOFF_BASE);
+        match args.command {
+            Commands::Hide {
+                visibility, run, ..
+            } => {
+                assert_eq!(visibility, "friends");
+                assert_eq!(run.delay, Duration::from_secs(1));
+                assert_eq!(run.batch_size, 20);
+            }
+            command => panic!("{:?}", command),
+        }
+    }
+
+    #[test]
+    fn verbosity_flags_are_global() {
+        let args = Args::try_parse_from([
+            "weibo_hide",
+            "hide",
+            "-u",
+            "1",
+            "-vv",
+            "--visibility",
+            "private",
+        ])
+        .unwrap();
+        assert_eq!(args.verbose, 2);
+        match args.command {
+            Commands::Hide { visibility, .. } => assert_eq!(visibility, "private"),
+            command => panic!("{:?}", command),
+        }
+
+        let args = Args::try_parse_from(["weibo_hide", "hide", "-u", "1", "-q"]).unwrap();
+        assert!(args.quiet);
+        assert!(Args::try_parse_from(["weibo_hide", "hide", "-u", "1", "-q", "-v"]).is_err());
+        // --visibility 没有短参数，-v 总是 --verbose
+        assert!(Args::try_parse_from(["weibo_hide", "hide", "-u", "1", "-v", "private"]).is_err());
+    }
+
+    #[test]
+    fn parse_duration_accepts_seconds_and_units() {
+        assert_eq!(parse_duration("1.5"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1m30s"), Ok(Duration::from_secs(90)));
+    }
+
+    #[test]
+    fn parse_duration_rejects_invalid_input() {
+        for value in ["", "5x", "ms", "-1"] {
+            assert!(parse_duration(value).is_err(), "{:?}", value);
+        }
+    }
 }
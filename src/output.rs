@@ -0,0 +1,310 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+
+use weibo_hide::i18n::{t, tf};
+use weibo_hide::weibo_client::{Visibility, WeiboInfo};
+
+/// 微博列表输出格式
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// 便于阅读的文本
+    #[default]
+    Text,
+    /// JSON 数组（格式化缩进）
+    Json,
+    /// CSV 表格
+    Csv,
+}
+
+/// 按指定格式渲染完整的微博列表
+pub fn render(weibos: &[WeiboInfo], format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Text => Ok(render_text(weibos)),
+        OutputFormat::Json => {
+            let rows: Vec<JsonRow> = weibos.iter().map(JsonRow::from).collect();
+            Ok(serde_json::to_string_pretty(&rows)? + "\n")
+        }
+        OutputFormat::Csv => render_csv(weibos),
+    }
+}
+
+/// 按指定格式将完整的微博列表写入文件
+///
+/// CSV 文件开头写入 UTF-8 BOM，Excel 等表格软件才能正确识别中文。
+pub fn write_file(path: &str, weibos: &[WeiboInfo], format: OutputFormat) -> Result<()> {
+    let mut content = render(weibos, format)?;
+    if format == OutputFormat::Csv {
+        content.insert(0, '\u{feff}');
+    }
+    fs::write(path, content).context(format!("无法写入文件: {}", path))?;
+    Ok(())
+}
+
+/// `--output` 取该值时输出到标准输出
+pub const STDOUT: &str = "-";
+
+/// 按指定格式将完整的微博列表写到标准输出，用于管道
+///
+/// 与写入文件不同，CSV 不加 BOM；下游提前关闭管道（如 `| head`）时直接结束，不报错。
+pub fn write_stdout(weibos: &[WeiboInfo], format: OutputFormat) -> Result<()> {
+    let content = render(weibos, format)?;
+    let mut stdout = io::stdout().lock();
+    match stdout
+        .write_all(content.as_bytes())
+        .and_then(|()| stdout.flush())
+    {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result.context("无法写入标准输出"),
+    }
+}
+
+/// 预览计划清单中的一项：一条微博将要进行的操作，或跳过它的原因
+#[derive(Serialize)]
+pub struct PlanEntry<'a> {
+    id: &'a str,
+    /// 发布时间，能解析时为 ISO-8601 格式
+    created_at: Option<String>,
+    current_visibility: Option<&'static str>,
+    target_visibility: &'static str,
+    will_change: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skip_reason: Option<&'static str>,
+}
+
+impl<'a> PlanEntry<'a> {
+    /// `skip_reason` 为 None 时表示该微博将被设置为目标可见性
+    pub fn new(
+        weibo: &'a WeiboInfo,
+        target: Visibility,
+        skip_reason: Option<&'static str>,
+    ) -> Self {
+        Self {
+            id: &weibo.id,
+            created_at: weibo.created_at_iso().or_else(|| weibo.created_at.clone()),
+            current_visibility: weibo.visibility.map(|v| v.key()),
+            target_visibility: target.key(),
+            will_change: skip_reason.is_none(),
+            url: weibo.permalink(),
+            skip_reason,
+        }
+    }
+}
+
+/// 将预览计划清单以 JSON 数组写入文件
+pub fn write_plan(path: &str, entries: &[PlanEntry]) -> Result<()> {
+    let content = serde_json::to_string_pretty(entries)? + "\n";
+    fs::write(path, content).context(format!("无法写入文件: {}", path))?;
+    Ok(())
+}
+
+/// 按当前可见性累计的微博数量，可逐页累加
+#[derive(Debug, Default)]
+pub struct VisibilityCounts {
+    counts: HashMap<Option<Visibility>, usize>,
+}
+
+impl VisibilityCounts {
+    pub fn from_weibos(weibos: &[WeiboInfo]) -> Self {
+        let mut counts = Self::default();
+        counts.add(weibos);
+        counts
+    }
+
+    pub fn add(&mut self, weibos: &[WeiboInfo]) {
+        for weibo in weibos {
+            *self.counts.entry(weibo.visibility).or_default() += 1;
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// 按限制程度排序，未知可见性排在最后，不含数量为 0 的项
+    pub fn rows(&self) -> Vec<(Option<Visibility>, usize)> {
+        Visibility::ALL
+            .iter()
+            .map(|v| Some(*v))
+            .chain(std::iter::once(None))
+            .filter_map(|v| self.counts.get(&v).map(|n| (v, *n)))
+            .collect()
+    }
+
+    /// 打印按当前可见性分组的统计
+    pub fn print(&self) {
+        println!("{}", t("counts.by_current_visibility"));
+        for (visibility, n) in self.rows() {
+            let label = visibility
+                .map(|v| v.as_str())
+                .unwrap_or(t("visibility.unknown"));
+            println!("  {}: {}", label, tf("summary.count", &[&n]));
+        }
+    }
+}
+
+/// JSON 中的一项：微博的全部字段，另加 ISO-8601 格式的发布时间
+#[derive(Serialize)]
+struct JsonRow<'a> {
+    #[serde(flatten)]
+    weibo: &'a WeiboInfo,
+    created_at_iso: Option<String>,
+    /// 微博的网页链接，见 [`WeiboInfo::permalink`]
+    url: Option<String>,
+}
+
+impl<'a> From<&'a WeiboInfo> for JsonRow<'a> {
+    fn from(weibo: &'a WeiboInfo) -> Self {
+        Self {
+            weibo,
+            created_at_iso: weibo.created_at_iso(),
+            url: weibo.permalink(),
+        }
+    }
+}
+
+fn render_text(weibos: &[WeiboInfo]) -> String {
+    let mut content = String::new();
+    for (idx, weibo) in weibos.iter().enumerate() {
+        content.push_str(&format!("{}. ID: {}\n", idx + 1, weibo.id));
+        if let Some(ref text) = weibo.text {
+            content.push_str(&tf("export.text", &[text]));
+            content.push('\n');
+        }
+        if let Some(ref created_at) = weibo.created_at {
+            let time = weibo.created_at_iso().unwrap_or_else(|| created_at.clone());
+            content.push_str(&tf("export.time", &[&time]));
+            content.push('\n');
+        }
+        if let Some(visibility) = weibo.visibility {
+            content.push_str(&tf("export.visibility", &[&visibility.as_str()]));
+            content.push('\n');
+        }
+        if let Some(url) = weibo.permalink() {
+            content.push_str(&tf("export.link", &[&url]));
+            content.push('\n');
+        }
+        content.push('\n');
+    }
+    content
+}
+
+/// CSV 中的一行
+#[derive(Serialize)]
+struct CsvRow<'a> {
+    id: &'a str,
+    created_at: &'a str,
+    created_at_iso: String,
+    text: &'a str,
+    visibility: &'a str,
+    is_repost: bool,
+    reposts_count: Option<u64>,
+    comments_count: Option<u64>,
+    attitudes_count: Option<u64>,
+    source: &'a str,
+    /// 多张图片的地址以空格分隔
+    pic_urls: String,
+    video_url: &'a str,
+    url: String,
+}
+
+/// CSV 表头，与 [`CsvRow`] 的字段顺序一致
+const CSV_HEADER: [&str; 13] = [
+    "id",
+    "created_at",
+    "created_at_iso",
+    "text",
+    "visibility",
+    "is_repost",
+    "reposts_count",
+    "comments_count",
+    "attitudes_count",
+    "source",
+    "pic_urls",
+    "video_url",
+    "url",
+];
+
+/// 由 csv 库负责引号与转义，内容中的逗号、引号和换行都能原样保留
+fn render_csv(weibos: &[WeiboInfo]) -> Result<String> {
+    // 手动写表头，列表为空时也输出
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    writer.write_record(CSV_HEADER)?;
+    for weibo in weibos {
+        writer.serialize(CsvRow {
+            id: &weibo.id,
+            created_at: weibo.created_at.as_deref().unwrap_or(""),
+            created_at_iso: weibo.created_at_iso().unwrap_or_default(),
+            text: weibo.text.as_deref().unwrap_or(""),
+            visibility: weibo.visibility.map(|v| v.key()).unwrap_or(""),
+            is_repost: weibo.is_repost,
+            reposts_count: weibo.reposts_count,
+            comments_count: weibo.comments_count,
+            attitudes_count: weibo.attitudes_count,
+            source: weibo.source.as_deref().unwrap_or(""),
+            pic_urls: weibo.pic_urls.join(" "),
+            video_url: weibo.video_url.as_deref().unwrap_or(""),
+            url: weibo.permalink().unwrap_or_default(),
+        })?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_round_trips_multiline_chinese_text() {
+        let texts = [
+            "第一行，带逗号\n第二行带\"引号\"\n第三行",
+            "\"开头就是引号\"，结尾换行\n",
+            "普通的一条微博",
+        ];
+        let weibos: Vec<WeiboInfo> = texts
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                let mut weibo = WeiboInfo::from_id(format!("489000000000000{}", i));
+                weibo.text = Some(text.to_string());
+                weibo.created_at = Some("Wed Oct 09 15:30:00 +0800 2019".to_string());
+                weibo.visibility = Some(Visibility::FriendsOnly);
+                weibo.is_repost = i == 1;
+                weibo
+            })
+            .collect();
+
+        let csv = render_csv(&weibos).unwrap();
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let headers = reader.headers().unwrap().clone();
+        let column = |name: &str| headers.iter().position(|h| h == name).unwrap();
+        // 请求中约定的列依次出现，之后加入的列插在其间或追加在后面
+        let positions: Vec<usize> = ["id", "created_at", "text", "visibility", "is_repost"]
+            .iter()
+            .map(|name| column(name))
+            .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]), "{:?}", headers);
+        assert_eq!(headers.iter().collect::<Vec<_>>(), CSV_HEADER);
+
+        let records: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(records.len(), weibos.len());
+        for (record, weibo) in records.iter().zip(&weibos) {
+            assert_eq!(&record[column("id")], weibo.id);
+            assert_eq!(
+                &record[column("created_at")],
+                weibo.created_at.as_deref().unwrap()
+            );
+            assert_eq!(&record[column("text")], weibo.text.as_deref().unwrap());
+            assert_eq!(&record[column("visibility")], "friends");
+            assert_eq!(&record[column("is_repost")], weibo.is_repost.to_string());
+        }
+    }
+}
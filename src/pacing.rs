@@ -0,0 +1,166 @@
+use anyhow::{anyhow, Result};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::info;
+
+/// 连续成功多少次后缩短请求间隔
+const SPEEDUP_AFTER: u32 = 5;
+
+/// 请求间隔控制
+///
+/// 固定模式下始终使用 `--delay`；指定 `--min-interval` 后为自适应模式：
+/// 从 `--delay` 开始，连续成功 [`SPEEDUP_AFTER`] 次后间隔减半（不低于下限），
+/// 任何失败或限流都使间隔加倍（不超过上限）。
+#[derive(Debug)]
+pub struct Pacer {
+    state: Mutex<PacerState>,
+    /// 自适应模式的下限与上限，固定模式为 None
+    bounds: Option<(Duration, Duration)>,
+}
+
+#[derive(Debug)]
+struct PacerState {
+    interval: Duration,
+    streak: u32,
+}
+
+impl Pacer {
+    pub fn new(delay: Duration, min_interval: Option<f64>, max_interval: f64) -> Result<Self> {
+        let bounds = match min_interval {
+            Some(min) if min > max_interval => {
+                return Err(anyhow!(
+                    "--min-interval（{}）不能大于 --max-interval（{}）",
+                    min,
+                    max_interval
+                ))
+            }
+            Some(min) => Some((
+                Duration::from_secs_f64(min),
+                Duration::from_secs_f64(max_interval),
+            )),
+            None => None,
+        };
+        let interval = match bounds {
+            Some((floor, ceiling)) => delay.clamp(floor, ceiling),
+            None => delay,
+        };
+        Ok(Self {
+            state: Mutex::new(PacerState {
+                interval,
+                streak: 0,
+            }),
+            bounds,
+        })
+    }
+
+    /// 当前的请求间隔
+    pub fn interval(&self) -> Duration {
+        self.state.lock().unwrap().interval
+    }
+
+    /// 记录一次成功的请求
+    pub fn on_success(&self) {
+        let Some((floor, _)) = self.bounds else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap();
+        state.streak += 1;
+        if state.streak >= SPEEDUP_AFTER && state.interval > floor {
+            state.streak = 0;
+            state.interval = (state.interval / 2).max(floor);
+            info!(
+                "连续成功，请求间隔缩短为 {:.2}s",
+                state.interval.as_secs_f64()
+            );
+        }
+    }
+
+    /// 记录一次失败或限流的请求
+    pub fn on_failure(&self) {
+        let Some((floor, ceiling)) = self.bounds else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap();
+        state.streak = 0;
+        // 间隔为 0 时加倍没有意义，从下限或 1 秒开始
+        let doubled = if state.interval.is_zero() {
+            floor.max(Duration::from_secs(1))
+        } else {
+            state.interval * 2
+        };
+        let interval = doubled.min(ceiling);
+        if interval != state.interval {
+            state.interval = interval;
+            info!("请求失败，请求间隔延长为 {:.2}s", interval.as_secs_f64());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn succeed(pacer: &Pacer, times: u32) {
+        for _ in 0..times {
+            pacer.on_success();
+        }
+    }
+
+    #[test]
+    fn success_streak_halves_interval_down_to_min() {
+        let pacer = Pacer::new(Duration::from_secs(4), Some(1.5), 10.0).unwrap();
+        succeed(&pacer, SPEEDUP_AFTER - 1);
+        assert_eq!(pacer.interval(), Duration::from_secs(4));
+        succeed(&pacer, 1);
+        assert_eq!(pacer.interval(), Duration::from_secs(2));
+        // 再减半为 1 秒，低于下限，取下限
+        succeed(&pacer, SPEEDUP_AFTER);
+        assert_eq!(pacer.interval(), Duration::from_millis(1500));
+        succeed(&pacer, SPEEDUP_AFTER);
+        assert_eq!(pacer.interval(), Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn failure_doubles_interval_up_to_max() {
+        let pacer = Pacer::new(Duration::from_secs(2), Some(1.0), 5.0).unwrap();
+        pacer.on_failure();
+        assert_eq!(pacer.interval(), Duration::from_secs(4));
+        pacer.on_failure();
+        assert_eq!(pacer.interval(), Duration::from_secs(5));
+        pacer.on_failure();
+        assert_eq!(pacer.interval(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn failure_resets_success_streak() {
+        let pacer = Pacer::new(Duration::from_secs(2), Some(0.5), 10.0).unwrap();
+        succeed(&pacer, SPEEDUP_AFTER - 1);
+        pacer.on_failure();
+        succeed(&pacer, SPEEDUP_AFTER - 1);
+        assert_eq!(pacer.interval(), Duration::from_secs(4));
+    }
+
+    #[test]
+    fn zero_interval_grows_from_one_second() {
+        let pacer = Pacer::new(Duration::ZERO, Some(0.0), 10.0).unwrap();
+        pacer.on_failure();
+        assert_eq!(pacer.interval(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn initial_delay_is_clamped_to_bounds() {
+        let pacer = Pacer::new(Duration::from_secs(30), Some(1.0), 8.0).unwrap();
+        assert_eq!(pacer.interval(), Duration::from_secs(8));
+        let pacer = Pacer::new(Duration::from_millis(100), Some(1.0), 8.0).unwrap();
+        assert_eq!(pacer.interval(), Duration::from_secs(1));
+        assert!(Pacer::new(Duration::from_secs(1), Some(9.0), 8.0).is_err());
+    }
+
+    #[test]
+    fn fixed_mode_ignores_results() {
+        let pacer = Pacer::new(Duration::from_secs(3), None, 8.0).unwrap();
+        succeed(&pacer, SPEEDUP_AFTER);
+        pacer.on_failure();
+        assert_eq!(pacer.interval(), Duration::from_secs(3));
+    }
+}
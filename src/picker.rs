@@ -0,0 +1,170 @@
+use anyhow::Result;
+use console::{pad_str, style, truncate_str, Alignment, Key, Term};
+
+use weibo_hide::i18n::{t, tf};
+use weibo_hide::weibo_client::{parse_weibo_time, WeiboInfo};
+
+/// 列表之外占用的行数：顶部的按键提示与底部的状态行
+const CHROME_LINES: usize = 3;
+/// 终端很矮时列表至少显示的条数
+const MIN_VISIBLE: usize = 5;
+
+/// 以可滚动的列表让用户勾选要处理的微博，返回选中的部分，保持原有顺序
+///
+/// 默认一条都不选：↑/↓（或 k/j）移动，PgUp/PgDn 翻页，空格勾选，a 全选或全不选，
+/// 回车确认，q 或 Esc 取消。列表绘制在标准错误上，标准错误不是终端时报错。
+pub fn select_weibos(weibos: Vec<WeiboInfo>) -> Result<Vec<WeiboInfo>> {
+    let term = Term::stderr();
+    if !term.is_term() {
+        return Err(anyhow::anyhow!(
+            "--interactive 需要在终端中运行，请去掉该参数或改用 --ids-file 指定要处理的微博"
+        ));
+    }
+    if weibos.is_empty() {
+        return Ok(weibos);
+    }
+
+    let labels: Vec<String> = weibos.iter().map(label).collect();
+    let mut picker = Picker::new(labels.len());
+    term.hide_cursor()?;
+    let result = picker.run(&term, &labels);
+    term.show_cursor()?;
+    let checked = result?;
+
+    Ok(weibos
+        .into_iter()
+        .zip(checked)
+        .filter_map(|(weibo, checked)| checked.then_some(weibo))
+        .collect())
+}
+
+/// 列表中每条微博的显示内容：发布日期、当前可见性与正文开头
+fn label(weibo: &WeiboInfo) -> String {
+    let date = weibo
+        .created_at
+        .as_deref()
+        .and_then(|s| parse_weibo_time(s).ok())
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| {
+            pad_str(t("picker.unknown_date"), 10, Alignment::Left, None).into_owned()
+        });
+    let visibility = weibo
+        .visibility
+        .map(|v| v.as_str())
+        .unwrap_or(t("visibility.unknown"));
+    let text = weibo
+        .text
+        .as_deref()
+        .unwrap_or(t("weibo.no_text"))
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        "{}  {}  {}",
+        date,
+        pad_str(visibility, 6, Alignment::Left, None),
+        text
+    )
+}
+
+/// 勾选状态与光标位置
+struct Picker {
+    checked: Vec<bool>,
+    cursor: usize,
+    /// 当前显示的第一条
+    top: usize,
+}
+
+impl Picker {
+    fn new(len: usize) -> Self {
+        Self {
+            checked: vec![false; len],
+            cursor: 0,
+            top: 0,
+        }
+    }
+
+    /// 反复绘制列表并处理按键，直到确认（返回勾选状态）或取消（返回错误）
+    fn run(&mut self, term: &Term, labels: &[String]) -> Result<Vec<bool>> {
+        let mut drawn = 0;
+        loop {
+            let (rows, cols) = term.size();
+            let visible = (rows as usize)
+                .saturating_sub(CHROME_LINES)
+                .max(MIN_VISIBLE);
+            self.scroll_into_view(visible);
+            term.clear_last_lines(drawn)?;
+            drawn = self.draw(term, labels, visible, cols as usize)?;
+
+            match term.read_key()? {
+                Key::ArrowUp | Key::Char('k') => self.move_by(-1),
+                Key::ArrowDown | Key::Char('j') => self.move_by(1),
+                Key::PageUp => self.move_by(-(visible as isize)),
+                Key::PageDown => self.move_by(visible as isize),
+                Key::Home => self.cursor = 0,
+                Key::End => self.cursor = self.checked.len() - 1,
+                Key::Char(' ') => self.checked[self.cursor] = !self.checked[self.cursor],
+                Key::Char('a') => {
+                    let all = self.checked.iter().all(|&c| c);
+                    self.checked.iter_mut().for_each(|c| *c = !all);
+                }
+                Key::Enter => {
+                    term.clear_last_lines(drawn)?;
+                    return Ok(std::mem::take(&mut self.checked));
+                }
+                Key::Escape | Key::Char('q') | Key::CtrlC => {
+                    term.clear_last_lines(drawn)?;
+                    return Err(anyhow::anyhow!("已取消选择，未做任何修改"));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        let last = self.checked.len() as isize - 1;
+        self.cursor = (self.cursor as isize + delta).clamp(0, last) as usize;
+    }
+
+    /// 调整显示的起始位置，使光标所在的行可见
+    fn scroll_into_view(&mut self, visible: usize) {
+        if self.cursor < self.top {
+            self.top = self.cursor;
+        } else if self.cursor >= self.top + visible {
+            self.top = self.cursor + 1 - visible;
+        }
+    }
+
+    /// 绘制一屏列表，返回绘制的行数；每行截断到终端宽度以内，避免折行后清屏错位
+    fn draw(&self, term: &Term, labels: &[String], visible: usize, cols: usize) -> Result<usize> {
+        let width = cols.saturating_sub(1);
+        term.write_line(&format!(
+            "{}",
+            style(truncate_str(t("picker.help"), width, "…")).bold()
+        ))?;
+
+        let end = (self.top + visible).min(labels.len());
+        for (idx, label) in labels.iter().enumerate().take(end).skip(self.top) {
+            let mark = if self.checked[idx] { "[x]" } else { "[ ]" };
+            let pointer = if idx == self.cursor { "›" } else { " " };
+            let line = format!("{} {} {}", pointer, mark, label);
+            let line = truncate_str(&line, width, "…");
+            if idx == self.cursor {
+                term.write_line(&style(line).cyan().to_string())?;
+            } else if self.checked[idx] {
+                term.write_line(&style(line).green().to_string())?;
+            } else {
+                term.write_line(&line)?;
+            }
+        }
+
+        let selected = self.checked.iter().filter(|&&c| c).count();
+        let mut status = tf("picker.selected", &[&selected, &labels.len()]);
+        if labels.len() > visible {
+            status.push_str(&tf("picker.window", &[&(self.top + 1), &end]));
+        }
+        term.write_line(&format!("{}", style(status).dim()))?;
+
+        Ok(end - self.top + 2)
+    }
+}
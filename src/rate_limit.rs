@@ -0,0 +1,44 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 全局请求速率限制，多个任务并发时共同遵守同一个上限
+///
+/// 令牌桶的容量为 1：令牌按固定间隔补充，每次请求取走一个，没有令牌时等待下一个。
+/// 不允许突发请求，因此任意一分钟内的请求数都不会超过设定值。
+/// 各任务按调用先后依次分到补充时间，先到先得。
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// 补充一个令牌的间隔
+    interval: Duration,
+    /// 下一个令牌可用的时间
+    next_free: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    /// 每分钟最多 `requests` 次请求，`requests` 为 0 时按 1 处理
+    pub fn per_minute(requests: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(60) / requests.max(1),
+            next_free: Mutex::new(None),
+        }
+    }
+
+    /// 相邻两次请求之间的最短间隔
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// 取走一个令牌，必要时等待到令牌可用
+    pub async fn acquire(&self) {
+        let wait = {
+            let mut next_free = self.next_free.lock().unwrap();
+            let now = Instant::now();
+            let slot = next_free.map_or(now, |t| t.max(now));
+            *next_free = Some(slot + self.interval);
+            slot - now
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
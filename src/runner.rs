@@ -0,0 +1,1558 @@
+use anyhow::Result;
+use clap::Args;
+use console::{measure_text_width, pad_str, style, Alignment, StyledObject};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, Semaphore};
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::checkpoint::Checkpoint;
+use crate::exit_code;
+use crate::failure_log::FailureLog;
+use crate::hooks::Hooks;
+use crate::interrupt;
+use crate::logging;
+use crate::pacing::Pacer;
+use weibo_hide::i18n::{t, tf};
+use weibo_hide::weibo_client::{
+    BatchOutcome, CommentPermission, FailureCategory, Visibility, WeiboPrivacyClient,
+};
+
+/// 同一批微博因限流最多重试的次数
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// 逐行报告进度时，进度有变化的情况下至少每隔多久输出一行
+const PROGRESS_LINE_INTERVAL: Duration = Duration::from_secs(10);
+/// 逐行报告进度时，每增加多少条立即输出一行
+const PROGRESS_LINE_STEP: u64 = 100;
+
+/// 批量设置的运行参数（Hide 与 Restore 共用）
+#[derive(Args, Debug, Clone)]
+pub struct RunArgs {
+    /// 每次设置请求后的等待时间：秒数（可为小数）或带单位的时长，如 500ms、1.5s、2m
+    #[arg(short = 'd', long, default_value = "1", value_parser = crate::parse_duration)]
+    pub delay: Duration,
+
+    /// 每次请求批量设置的微博数量
+    #[arg(short = 'b', long, default_value = "20", value_parser = clap::value_parser!(u64).range(1..))]
+    pub batch_size: u64,
+
+    /// 同时进行的设置请求数
+    #[arg(long, default_value = "1", value_parser = clap::value_parser!(u64).range(1..))]
+    pub concurrency: u64,
+
+    /// 触发频率限制后的暂停时间（秒）
+    #[arg(long, default_value = "60")]
+    pub rate_limit_cooldown: u64,
+
+    /// 失败记录文件路径，每条失败以 JSON 行的形式即时追加
+    #[arg(long)]
+    pub failure_log: Option<String>,
+
+    /// 启用自适应间隔并设置其下限（秒，可为小数）：从 --delay 开始，连续成功时减半，失败或限流时加倍
+    #[arg(long, value_parser = crate::parse_seconds)]
+    pub min_interval: Option<f64>,
+
+    /// 自适应间隔的上限（秒，可为小数）
+    #[arg(long, default_value = "30", value_parser = crate::parse_seconds)]
+    pub max_interval: f64,
+
+    /// 全部处理完后，对仍然失败的微博自动重新处理的轮数
+    #[arg(long, default_value = "0")]
+    pub auto_retry_passes: u32,
+
+    /// 每轮自动重试开始前的等待时间（秒）
+    #[arg(long, default_value = "30")]
+    pub retry_pass_delay: u64,
+
+    /// 逐条输出每条微博的处理结果，并发时先缓冲、按输入顺序输出，便于比较两次运行的日志
+    #[arg(long, default_value = "false")]
+    pub ordered_output: bool,
+
+    /// 将已删除或不存在的微博计为失败（默认单独统计，不写入失败记录）
+    #[arg(long, default_value = "false")]
+    pub treat_missing_as_failure: bool,
+
+    /// 每条微博处理成功后运行的 shell 命令，微博 ID 与结果作为参数传入，并设置 WEIBO_ID 等环境变量
+    #[arg(long, value_name = "CMD")]
+    pub on_success: Option<String>,
+
+    /// 每条微博处理失败（含不支持目标可见性、已设置但未生效）后运行的 shell 命令，参数同 --on-success
+    #[arg(long, value_name = "CMD")]
+    pub on_failure: Option<String>,
+}
+
+impl RunArgs {
+    /// 按参数创建请求间隔控制
+    fn pacer(&self) -> Result<Arc<Pacer>> {
+        Ok(Arc::new(Pacer::new(
+            self.delay,
+            self.min_interval,
+            self.max_interval,
+        )?))
+    }
+}
+
+/// 单条待设置的微博
+#[derive(Debug, Clone)]
+pub struct PrivacyTask {
+    pub id: String,
+    /// 目标可见性
+    pub target: Visibility,
+    /// 设置前的可见性，写入检查点供 restore 使用
+    pub previous: Option<Visibility>,
+    /// 可见性设置成功后要设置的评论权限
+    pub comment_permission: Option<CommentPermission>,
+    /// 设置成功后重新读取详情，确认可见性确实变为目标（`--verify`）
+    pub verify: bool,
+}
+
+/// 可见性变化：设置前的可见性（未知为 None）与目标可见性
+type Transition = (Option<Visibility>, Visibility);
+
+/// 运行结果汇总
+#[derive(Debug, Default)]
+pub struct RunSummary {
+    pub success_count: usize,
+    /// 失败的微博 ID、失败原因的大类及错误信息
+    pub failed_ids: Vec<(String, FailureCategory, String)>,
+    /// 不支持目标可见性的微博 ID，与真正的错误分开统计
+    pub unsupported_ids: Vec<String>,
+    /// 已删除或不存在的微博 ID（列出后、处理前被删除），同样与真正的错误分开统计
+    pub missing_ids: Vec<String>,
+    /// 接口返回成功、但重新读取时可见性不是目标的微博及原因（`--verify`）
+    pub unverified_ids: Vec<(String, String)>,
+    /// 评论权限设置成功的数量
+    pub comment_success_count: usize,
+    /// 可见性已设置、但评论权限设置失败的微博
+    pub comment_failed_ids: Vec<(String, String)>,
+    /// 设置成功的微博按可见性变化计数
+    pub transitions: HashMap<Transition, usize>,
+    /// 已是目标可见性而跳过的微博数量
+    pub skipped: HashMap<Visibility, usize>,
+    /// 是否因 Ctrl+C 提前结束
+    pub interrupted: bool,
+    /// 中断时尚未处理的微博数量（边获取边处理时不含尚未获取的部分）
+    pub unprocessed: usize,
+    /// 实际进行的自动重试轮数
+    pub retry_passes: u32,
+}
+
+impl RunSummary {
+    /// 合并一轮自动重试的结果：失败列表替换为本轮仍然失败的微博，其余累加
+    fn merge_retry(&mut self, retry: RunSummary) {
+        self.success_count += retry.success_count;
+        self.failed_ids = retry.failed_ids;
+        self.unsupported_ids.extend(retry.unsupported_ids);
+        self.missing_ids.extend(retry.missing_ids);
+        self.unverified_ids.extend(retry.unverified_ids);
+        self.comment_success_count += retry.comment_success_count;
+        self.comment_failed_ids.extend(retry.comment_failed_ids);
+        for (transition, n) in retry.transitions {
+            *self.transitions.entry(transition).or_default() += n;
+        }
+        self.interrupted |= retry.interrupted;
+        self.unprocessed += retry.unprocessed;
+        self.retry_passes += 1;
+    }
+
+    /// 合并另一批微博的处理结果（如 `--sample` 抽样之后的其余部分），各项累加
+    pub fn merge(&mut self, other: RunSummary) {
+        self.success_count += other.success_count;
+        self.failed_ids.extend(other.failed_ids);
+        self.unsupported_ids.extend(other.unsupported_ids);
+        self.missing_ids.extend(other.missing_ids);
+        self.unverified_ids.extend(other.unverified_ids);
+        self.comment_success_count += other.comment_success_count;
+        self.comment_failed_ids.extend(other.comment_failed_ids);
+        for (transition, n) in other.transitions {
+            *self.transitions.entry(transition).or_default() += n;
+        }
+        for (visibility, n) in other.skipped {
+            *self.skipped.entry(visibility).or_default() += n;
+        }
+        self.interrupted |= other.interrupted;
+        self.unprocessed += other.unprocessed;
+        self.retry_passes = self.retry_passes.max(other.retry_passes);
+    }
+
+    /// 该微博是否已设置成功（不在失败、不支持、已删除与未生效的列表中）
+    pub fn succeeded(&self, id: &str) -> bool {
+        !self.failed_ids.iter().any(|(failed, _, _)| failed == id)
+            && !self.unsupported_ids.iter().any(|u| u == id)
+            && !self.missing_ids.iter().any(|m| m == id)
+            && !self.unverified_ids.iter().any(|(u, _)| u == id)
+    }
+
+    /// 失败按原因大类计数，按网络错误、认证错误、API 拒绝、未知的顺序排列
+    fn failure_categories(&self) -> Vec<(FailureCategory, usize)> {
+        let mut counts: BTreeMap<FailureCategory, usize> = BTreeMap::new();
+        for (_, category, _) in &self.failed_ids {
+            *counts.entry(*category).or_default() += 1;
+        }
+        counts.into_iter().collect()
+    }
+
+    /// 记录已是目标可见性而跳过的微博
+    pub fn record_skipped(&mut self, visibility: Visibility, count: usize) {
+        if count > 0 {
+            *self.skipped.entry(visibility).or_default() += count;
+        }
+    }
+
+    /// 本次运行对应的进程退出码
+    ///
+    /// 没有一条成功且有失败时为全部失败；有失败、不支持、未生效、未处理或评论权限失败时为部分成功。
+    pub fn exit_code(&self) -> u8 {
+        let failed = self.failed_ids.len() + self.unsupported_ids.len() + self.unverified_ids.len();
+        if self.success_count == 0 && failed > 0 {
+            exit_code::FAILURE
+        } else if failed > 0 || self.unprocessed > 0 || !self.comment_failed_ids.is_empty() {
+            exit_code::PARTIAL
+        } else {
+            exit_code::SUCCESS
+        }
+    }
+
+    /// 打印处理结果
+    ///
+    /// 成功为绿色、失败为红色、跳过与未处理为黄色；stdout 不是终端或设置了 `NO_COLOR` 时不使用颜色。
+    pub fn print(&self) {
+        if self.interrupted {
+            println!("\n{}", style(t("summary.interrupted")).yellow().bold());
+        } else {
+            println!("\n{}", style(t("summary.done")).bold());
+        }
+
+        let mut rows = vec![SummaryRow::new(
+            Outcome::Success,
+            t("summary.success"),
+            self.success_count,
+        )];
+        if !self.failed_ids.is_empty() {
+            let mut row =
+                SummaryRow::new(Outcome::Failure, t("summary.failed"), self.failed_ids.len());
+            let mut notes: Vec<String> = self
+                .failure_categories()
+                .into_iter()
+                .map(|(category, n)| format!("{} {}", category.as_str(), n))
+                .collect();
+            if self.retry_passes > 0 {
+                notes.push(tf("summary.retry_passes", &[&self.retry_passes]));
+            }
+            row.note = notes.join(t("summary.note_separator"));
+            rows.push(row);
+        }
+        if !self.unsupported_ids.is_empty() {
+            rows.push(SummaryRow::new(
+                Outcome::Skipped,
+                t("summary.unsupported"),
+                self.unsupported_ids.len(),
+            ));
+        }
+        if !self.missing_ids.is_empty() {
+            rows.push(SummaryRow::new(
+                Outcome::Skipped,
+                t("summary.missing"),
+                self.missing_ids.len(),
+            ));
+        }
+        if !self.unverified_ids.is_empty() {
+            rows.push(SummaryRow::new(
+                Outcome::Failure,
+                t("summary.unverified"),
+                self.unverified_ids.len(),
+            ));
+        }
+        let skipped: usize = self.skipped.values().sum();
+        if skipped > 0 {
+            rows.push(SummaryRow::new(
+                Outcome::Skipped,
+                t("summary.skipped"),
+                skipped,
+            ));
+        }
+        if self.interrupted {
+            rows.push(SummaryRow::new(
+                Outcome::Skipped,
+                t("summary.unprocessed"),
+                self.unprocessed,
+            ));
+        }
+        if self.comment_success_count > 0 || !self.comment_failed_ids.is_empty() {
+            rows.push(SummaryRow::new(
+                Outcome::Success,
+                t("summary.comment_success"),
+                self.comment_success_count,
+            ));
+        }
+        if !self.comment_failed_ids.is_empty() {
+            rows.push(SummaryRow::new(
+                Outcome::Failure,
+                t("summary.comment_failed"),
+                self.comment_failed_ids.len(),
+            ));
+        }
+        print_rows(&rows);
+
+        self.print_transitions();
+        if !self.failed_ids.is_empty() {
+            println!("\n{}", style(t("summary.failed_details")).red());
+            for (category, n) in self.failure_categories() {
+                println!(
+                    "  {}",
+                    tf("summary.category_count", &[&category.as_str(), &n])
+                );
+                let failures = self.failed_ids.iter().filter(|(_, c, _)| *c == category);
+                for (id, _, err) in failures.take(10) {
+                    println!("    - ID {}: {}", id, err);
+                }
+            }
+        }
+        if !self.unsupported_ids.is_empty() {
+            println!("\n{}", style(t("summary.unsupported_details")).yellow());
+            for id in self.unsupported_ids.iter().take(10) {
+                println!("  - ID {}", id);
+            }
+        }
+        if !self.missing_ids.is_empty() {
+            println!("\n{}", style(t("summary.missing_details")).yellow());
+            for id in self.missing_ids.iter().take(10) {
+                println!("  - ID {}", id);
+            }
+        }
+        if !self.unverified_ids.is_empty() {
+            println!("\n{}", style(t("summary.unverified_details")).red());
+            for (id, reason) in self.unverified_ids.iter().take(10) {
+                println!("  - ID {}: {}", id, reason);
+            }
+        }
+        if !self.comment_failed_ids.is_empty() {
+            println!("\n{}", style(t("summary.comment_failed_details")).red());
+            for (id, err) in self.comment_failed_ids.iter().take(10) {
+                println!("  - ID {}: {}", id, err);
+            }
+        }
+    }
+
+    /// 按可见性变化打印成功与跳过的数量
+    fn print_transitions(&self) {
+        if self.transitions.is_empty() && self.skipped.is_empty() {
+            return;
+        }
+
+        let label =
+            |v: Option<Visibility>| v.map(|v| v.as_str()).unwrap_or(t("visibility.unknown"));
+        let order = |v: Option<Visibility>| v.map(|v| v.restrictiveness() + 1).unwrap_or(0);
+
+        let mut rows: Vec<(Transition, usize, bool)> = self
+            .transitions
+            .iter()
+            .map(|(t, n)| (*t, *n, false))
+            .chain(self.skipped.iter().map(|(v, n)| ((Some(*v), *v), *n, true)))
+            .collect();
+        rows.sort_by_key(|((from, to), _, skipped)| (order(*from), to.restrictiveness(), *skipped));
+
+        println!("\n{}", t("summary.by_visibility"));
+        for ((from, to), n, skipped) in rows {
+            println!(
+                "  {} → {}: {}{}",
+                label(from),
+                to.as_str(),
+                n,
+                if skipped {
+                    style(t("summary.skipped_mark")).yellow().to_string()
+                } else {
+                    String::new()
+                }
+            );
+        }
+    }
+}
+
+/// 汇总表中一行的结果种类，决定标记与颜色
+#[derive(Debug, Clone, Copy)]
+enum Outcome {
+    Success,
+    Failure,
+    Skipped,
+}
+
+impl Outcome {
+    fn mark(self) -> StyledObject<&'static str> {
+        match self {
+            Outcome::Success => style("✓").green(),
+            Outcome::Failure => style("✗").red(),
+            Outcome::Skipped => style("⚠").yellow(),
+        }
+    }
+
+    fn paint(self, text: String) -> StyledObject<String> {
+        match self {
+            Outcome::Success => style(text).green(),
+            Outcome::Failure => style(text).red(),
+            Outcome::Skipped => style(text).yellow(),
+        }
+    }
+}
+
+/// 汇总表中的一行
+struct SummaryRow {
+    outcome: Outcome,
+    label: &'static str,
+    count: usize,
+    /// 数量后的补充说明
+    note: String,
+}
+
+impl SummaryRow {
+    fn new(outcome: Outcome, label: &'static str, count: usize) -> Self {
+        Self {
+            outcome,
+            label,
+            count,
+            note: String::new(),
+        }
+    }
+}
+
+/// 按列对齐打印汇总表：名称左对齐（按显示宽度计算，中文占两列），数量右对齐
+fn print_rows(rows: &[SummaryRow]) {
+    let label_width = rows
+        .iter()
+        .map(|r| measure_text_width(r.label))
+        .max()
+        .unwrap_or(0);
+    let count_width = rows
+        .iter()
+        .map(|r| r.count.to_string().len())
+        .max()
+        .unwrap_or(0);
+    for row in rows {
+        let label = pad_str(row.label, label_width, Alignment::Left, None);
+        let count = tf(
+            "summary.count",
+            &[&format!("{:>width$}", row.count, width = count_width)],
+        );
+        let note = if row.note.is_empty() {
+            String::new()
+        } else {
+            tf("summary.note", &[&row.note])
+        };
+        println!(
+            "  {} {}  {}{}",
+            row.outcome.mark(),
+            label,
+            row.outcome.paint(count),
+            note
+        );
+    }
+}
+
+/// 若处于限流冷却期，等待冷却结束
+async fn wait_for_cooldown(cooldown_until: &Mutex<Option<Instant>>) {
+    let until = *cooldown_until.lock().unwrap();
+    if let Some(until) = until {
+        tokio::time::sleep_until(until).await;
+    }
+}
+
+/// 记录失败：加入汇总列表，并写入失败记录文件（如有）
+fn record_failures(
+    failed_ids: &Mutex<Vec<(String, FailureCategory, String)>>,
+    failure_log: Option<&FailureLog>,
+    failures: Vec<(String, FailureCategory, String)>,
+) {
+    if let Some(log) = failure_log {
+        for (id, _, error) in &failures {
+            if let Err(e) = log.record(id, error) {
+                warn!("写入失败记录失败: {}", e);
+            }
+        }
+    }
+    failed_ids.lock().unwrap().extend(failures);
+}
+
+/// `--verify`：逐条重新读取设置成功的微博，返回可见性不是目标的微博及原因
+///
+/// 读取失败的同样视为未生效；收到 Ctrl+C 或达到请求数上限时停止，其余的按成功处理。
+async fn read_back(
+    client: &WeiboPrivacyClient,
+    ids: &[String],
+    target: Visibility,
+    pacer: &Pacer,
+    reporter: &ResultReporter,
+    results: &mut Vec<ResultEntry>,
+) -> Vec<(String, String)> {
+    let mut unverified = Vec::new();
+    for id in ids {
+        if interrupt::is_interrupted() {
+            break;
+        }
+        let interval = pacer.interval();
+        if !interval.is_zero() {
+            tokio::time::sleep(interval).await;
+        }
+        let reason = match client.get_weibo(id).await {
+            Ok(weibo) if weibo.visibility == Some(target) => None,
+            Ok(weibo) => Some(match weibo.visibility {
+                Some(current) => format!("当前为{}", current.as_str()),
+                None => "详情中没有可见性".to_string(),
+            }),
+            Err(e) if e.is_request_limit() => {
+                interrupt::request_stop();
+                break;
+            }
+            Err(e) => Some(format!("无法读取: {}", e)),
+        };
+        match reason {
+            None => reporter.record(results, id, "verify", "success", None),
+            Some(reason) => {
+                warn!("微博 {} 已设置但未生效: {}", id, reason);
+                reporter.record(results, id, "verify", "unverified", Some(&reason));
+                unverified.push((id.clone(), reason));
+            }
+        }
+    }
+    unverified
+}
+
+/// 记录失败的任务，供自动重试使用
+fn record_failed_tasks(
+    failed_tasks: &Mutex<Vec<PrivacyTask>>,
+    batch: &[PrivacyTask],
+    failures: &[(String, FailureCategory, String)],
+) {
+    let mut failed_tasks = failed_tasks.lock().unwrap();
+    failed_tasks.extend(
+        batch
+            .iter()
+            .filter(|t| failures.iter().any(|(id, _, _)| id == &t.id))
+            .cloned(),
+    );
+}
+
+/// 按输入顺序排列失败列表，使汇总不受并发完成顺序影响；不在 `positions` 中的排在最后
+fn sort_by_position<T>(
+    items: &mut [T],
+    positions: &HashMap<String, usize>,
+    id: impl Fn(&T) -> &str,
+) {
+    items.sort_by_key(|item| positions.get(id(item)).copied().unwrap_or(usize::MAX));
+}
+
+/// 一条微博的处理结果，见 [`logging::record_result`]
+struct ResultEntry {
+    id: String,
+    action: String,
+    result: &'static str,
+    error: Option<String>,
+}
+
+/// 输出每条微博的处理结果
+///
+/// 默认即时输出（仅 JSON 日志模式）；指定 `--ordered-output` 时每批的结果先缓冲，
+/// 按批次的输入顺序依次输出，前面的批次未完成时后面的等待。
+/// 指定了 `--on-success`、`--on-failure` 时每条结果产生后立即运行对应的命令，不受输出顺序影响。
+struct ResultReporter {
+    ordered: bool,
+    state: Mutex<ReorderState>,
+    hooks: Option<Arc<Hooks>>,
+}
+
+#[derive(Default)]
+struct ReorderState {
+    /// 下一个应输出的批次序号
+    next: usize,
+    /// 已完成、等待前面批次的结果
+    pending: BTreeMap<usize, Vec<ResultEntry>>,
+}
+
+impl ReorderState {
+    /// 暂存第 `seq` 批的结果，取出从当前位置起所有连续完成的批次
+    fn release(&mut self, seq: usize, entries: Vec<ResultEntry>) -> Vec<ResultEntry> {
+        self.pending.insert(seq, entries);
+        let mut ready = Vec::new();
+        while let Some(entries) = self.pending.remove(&self.next) {
+            ready.extend(entries);
+            self.next += 1;
+        }
+        ready
+    }
+}
+
+impl ResultReporter {
+    fn new(args: &RunArgs) -> Self {
+        Self {
+            ordered: args.ordered_output,
+            state: Mutex::new(ReorderState::default()),
+            hooks: Hooks::new(args.on_success.clone(), args.on_failure.clone()),
+        }
+    }
+
+    /// 记录一条结果：按顺序输出时暂存到本批的 `entries`，否则立即输出
+    fn record(
+        &self,
+        entries: &mut Vec<ResultEntry>,
+        id: &str,
+        action: &str,
+        result: &'static str,
+        error: Option<&str>,
+    ) {
+        if let Some(ref hooks) = self.hooks {
+            hooks.fire(id, action, result, error);
+        }
+        if self.ordered {
+            entries.push(ResultEntry {
+                id: id.to_string(),
+                action: action.to_string(),
+                result,
+                error: error.map(str::to_string),
+            });
+        } else {
+            logging::record_result(id, action, result, error);
+        }
+    }
+
+    /// 第 `seq` 批处理完成，输出从当前位置起所有连续完成的批次
+    fn complete(&self, seq: usize, entries: Vec<ResultEntry>) {
+        if !self.ordered {
+            return;
+        }
+        // 持有锁输出，避免两批结果交错
+        let mut state = self.state.lock().unwrap();
+        for entry in state.release(seq, entries) {
+            logging::print_result(
+                &entry.id,
+                &entry.action,
+                entry.result,
+                entry.error.as_deref(),
+            );
+        }
+    }
+
+    /// 本轮处理结束：输出剩余的结果，并等待已启动的钩子命令结束
+    async fn finish(&self) {
+        self.flush();
+        if let Some(ref hooks) = self.hooks {
+            hooks.wait().await;
+        }
+    }
+
+    /// 输出剩余的结果（某批未能完成时，其后的批次仍按顺序输出）
+    fn flush(&self) {
+        let mut state = self.state.lock().unwrap();
+        for entries in std::mem::take(&mut state.pending).into_values() {
+            for entry in entries {
+                logging::print_result(
+                    &entry.id,
+                    &entry.action,
+                    entry.result,
+                    entry.error.as_deref(),
+                );
+            }
+        }
+    }
+}
+
+/// 从队列中取出下一批任务：目标可见性相同、数量不超过 `batch_size`，保持原有顺序
+///
+/// 目标不同的任务暂存在 `carry` 中，作为下一批的第一条。队列关闭且没有剩余任务时返回 None。
+async fn next_batch(
+    rx: &mut mpsc::Receiver<PrivacyTask>,
+    carry: &mut Option<PrivacyTask>,
+    batch_size: usize,
+) -> Option<Vec<PrivacyTask>> {
+    let first = match carry.take() {
+        Some(task) => task,
+        None => rx.recv().await?,
+    };
+    let mut batch = vec![first];
+    while batch.len() < batch_size {
+        match rx.recv().await {
+            Some(task) if task.target == batch[0].target => batch.push(task),
+            Some(task) => {
+                *carry = Some(task);
+                break;
+            }
+            None => break,
+        }
+    }
+    Some(batch)
+}
+
+/// 中断后清空队列，返回未处理的任务数
+fn drain_unprocessed(rx: &mut mpsc::Receiver<PrivacyTask>, carry: Option<PrivacyTask>) -> usize {
+    rx.close();
+    let mut count = usize::from(carry.is_some());
+    while rx.try_recv().is_ok() {
+        count += 1;
+    }
+    count
+}
+
+/// 结束进度条：中断时保留当前进度
+fn finish_progress_bar(pb: &ProgressBar) {
+    if interrupt::is_interrupted() {
+        pb.abandon_with_message(t("progress.interrupted"));
+    } else {
+        pb.finish_with_message(t("progress.done"));
+    }
+}
+
+/// 创建进度条；逐行报告进度时返回不显示的进度条，由后台任务按进度输出文字
+fn new_progress_bar(len: usize) -> ProgressBar {
+    if logging::line_progress() {
+        let pb = ProgressBar::hidden();
+        pb.set_length(len as u64);
+        spawn_line_reporter(pb.clone(), |pos, len| match len {
+            Some(len) if len > 0 => tf("progress.processed", &[&pos, &len, &(pos * 100 / len)]),
+            _ => tf("progress.processed_count", &[&pos]),
+        });
+        return pb;
+    }
+    let pb = logging::progress().add(ProgressBar::new(len as u64));
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template(
+                "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})",
+            )
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+    pb
+}
+
+/// 创建获取列表阶段的进度显示
+///
+/// `expected` 为账号的微博总数（如已知），此时显示大致的进度条，否则只显示已获取的条数。
+pub fn new_fetch_progress(expected: Option<u64>) -> ProgressBar {
+    if logging::line_progress() {
+        let pb = ProgressBar::hidden();
+        if let Some(total) = expected {
+            pb.set_length(total);
+        }
+        spawn_line_reporter(pb.clone(), |pos, len| match len {
+            Some(len) => tf("progress.fetched", &[&pos, &len]),
+            None => tf("progress.fetched_count", &[&pos]),
+        });
+        return pb;
+    }
+    let (pb, template) = match expected {
+        Some(total) => (ProgressBar::new(total), t("progress.fetch_bar")),
+        None => (ProgressBar::new_spinner(), t("progress.fetch_spinner")),
+    };
+    let pb = logging::progress().add(pb);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template(template)
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+    pb.enable_steady_tick(Duration::from_millis(120));
+    pb
+}
+
+/// 以逐行文字报告 `pb` 的进度，直到进度条结束
+///
+/// 每增加 [`PROGRESS_LINE_STEP`] 条立即输出一行，否则有变化时每隔 [`PROGRESS_LINE_INTERVAL`] 输出一行；
+/// 结束时不再输出，最终结果见随后的汇总。`describe` 由当前位置与总数（如已知）生成一行文字。
+fn spawn_line_reporter(pb: ProgressBar, describe: fn(u64, Option<u64>) -> String) {
+    tokio::spawn(async move {
+        let mut reported = 0;
+        let mut reported_at = Instant::now();
+        loop {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            if pb.is_finished() {
+                break;
+            }
+            let pos = pb.position();
+            let due = pos >= reported + PROGRESS_LINE_STEP
+                || (pos != reported && reported_at.elapsed() >= PROGRESS_LINE_INTERVAL);
+            if due {
+                eprintln!("{}", describe(pos, pb.length()));
+                reported = pos;
+                reported_at = Instant::now();
+            }
+        }
+    });
+}
+
+/// 按参数打开失败记录文件
+fn open_failure_log(args: &RunArgs) -> Result<Option<Arc<FailureLog>>> {
+    match args.failure_log {
+        Some(ref path) => Ok(Some(Arc::new(FailureLog::open(path)?))),
+        None => Ok(None),
+    }
+}
+
+/// 批量设置微博隐私，显示进度条并汇总结果
+pub async fn run_privacy_updates(
+    client: Arc<WeiboPrivacyClient>,
+    tasks: Vec<PrivacyTask>,
+    args: &RunArgs,
+    checkpoint: Option<Arc<Checkpoint>>,
+) -> Result<RunSummary> {
+    let total = tasks.len();
+    let (tx, rx) = mpsc::channel(total.max(1));
+    for task in tasks {
+        // 容量与任务数相同，不会失败
+        let _ = tx.try_send(task);
+    }
+    drop(tx);
+    run_privacy_updates_from(client, rx, Some(total), args, checkpoint).await
+}
+
+/// 从队列中读取任务批量设置微博隐私，队列关闭后结束
+///
+/// 用于边获取列表边处理：`total` 未知时进度条总数随收到的任务增长。
+/// 指定了 `--auto-retry-passes` 时，之后对仍然失败的微博再处理若干轮。
+pub async fn run_privacy_updates_from(
+    client: Arc<WeiboPrivacyClient>,
+    rx: mpsc::Receiver<PrivacyTask>,
+    total: Option<usize>,
+    args: &RunArgs,
+    checkpoint: Option<Arc<Checkpoint>>,
+) -> Result<RunSummary> {
+    let failure_log = open_failure_log(args)?;
+    let (mut summary, mut failed_tasks) = update_pass(
+        Arc::clone(&client),
+        rx,
+        total,
+        args,
+        checkpoint.clone(),
+        failure_log,
+    )
+    .await?;
+
+    for pass in 1..=args.auto_retry_passes {
+        if failed_tasks.is_empty() || !wait_for_retry_pass(pass, failed_tasks.len(), args).await {
+            break;
+        }
+        let total = failed_tasks.len();
+        let (tx, rx) = mpsc::channel(total);
+        for task in failed_tasks {
+            let _ = tx.try_send(task);
+        }
+        drop(tx);
+        // 首轮已写入失败记录，重试时不再重复写入
+        let (retry, still_failed) = update_pass(
+            Arc::clone(&client),
+            rx,
+            Some(total),
+            args,
+            checkpoint.clone(),
+            None,
+        )
+        .await?;
+        summary.merge_retry(retry);
+        failed_tasks = still_failed;
+    }
+    Ok(summary)
+}
+
+/// 等待下一轮自动重试开始，期间收到中断信号时返回 false
+async fn wait_for_retry_pass(pass: u32, count: usize, args: &RunArgs) -> bool {
+    if interrupt::is_interrupted() {
+        return false;
+    }
+    info!(
+        "第 {}/{} 轮自动重试：{} 条失败的微博，{} 秒后开始",
+        pass, args.auto_retry_passes, count, args.retry_pass_delay
+    );
+    let deadline = Instant::now() + Duration::from_secs(args.retry_pass_delay);
+    while Instant::now() < deadline {
+        if interrupt::is_interrupted() {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(200).min(deadline - Instant::now())).await;
+    }
+    !interrupt::is_interrupted()
+}
+
+/// 处理一轮任务，返回汇总与本轮失败的任务（不含不支持与评论权限失败的部分）
+async fn update_pass(
+    client: Arc<WeiboPrivacyClient>,
+    mut rx: mpsc::Receiver<PrivacyTask>,
+    total: Option<usize>,
+    args: &RunArgs,
+    checkpoint: Option<Arc<Checkpoint>>,
+    failure_log: Option<Arc<FailureLog>>,
+) -> Result<(RunSummary, Vec<PrivacyTask>)> {
+    let pb = new_progress_bar(total.unwrap_or(0));
+    let pacer = args.pacer()?;
+    let rate_limit_cooldown = args.rate_limit_cooldown;
+    let semaphore = Arc::new(Semaphore::new(args.concurrency as usize));
+    let success_count = Arc::new(AtomicUsize::new(0));
+    let failed_ids = Arc::new(Mutex::new(Vec::new()));
+    let failed_tasks = Arc::new(Mutex::new(Vec::new()));
+    let unsupported_ids = Arc::new(Mutex::new(Vec::new()));
+    let missing_ids = Arc::new(Mutex::new(Vec::new()));
+    let unverified_ids = Arc::new(Mutex::new(Vec::new()));
+    let missing_as_failure = args.treat_missing_as_failure;
+    let comment_success_count = Arc::new(AtomicUsize::new(0));
+    let comment_failed_ids = Arc::new(Mutex::new(Vec::new()));
+    let transitions = Arc::new(Mutex::new(HashMap::new()));
+    let cooldown_until = Arc::new(Mutex::new(None));
+    let unprocessed = Arc::new(AtomicUsize::new(0));
+    let reporter = Arc::new(ResultReporter::new(args));
+    // 每条微博在输入中的位置，用于按输入顺序排列汇总中的失败
+    let mut positions = HashMap::new();
+    let mut handles = Vec::new();
+
+    let mut carry = None;
+    while let Some(batch) = next_batch(&mut rx, &mut carry, args.batch_size as usize).await {
+        if total.is_none() {
+            pb.inc_length(batch.len() as u64);
+        }
+        for task in &batch {
+            let position = positions.len();
+            positions.entry(task.id.clone()).or_insert(position);
+        }
+
+        // 获取许可后再启动任务，限制同时进行的请求数
+        let permit = Arc::clone(&semaphore).acquire_owned().await?;
+        if interrupt::is_interrupted() {
+            unprocessed.fetch_add(batch.len(), Ordering::Relaxed);
+            break;
+        }
+        let client = Arc::clone(&client);
+        let success_count = Arc::clone(&success_count);
+        let failed_ids = Arc::clone(&failed_ids);
+        let failed_tasks = Arc::clone(&failed_tasks);
+        let unsupported_ids = Arc::clone(&unsupported_ids);
+        let missing_ids = Arc::clone(&missing_ids);
+        let unverified_ids = Arc::clone(&unverified_ids);
+        let comment_success_count = Arc::clone(&comment_success_count);
+        let comment_failed_ids = Arc::clone(&comment_failed_ids);
+        let transitions = Arc::clone(&transitions);
+        let unprocessed = Arc::clone(&unprocessed);
+        let checkpoint = checkpoint.clone();
+        let failure_log = failure_log.clone();
+        let cooldown_until = Arc::clone(&cooldown_until);
+        let pacer = Arc::clone(&pacer);
+        let pb = pb.clone();
+        let reporter = Arc::clone(&reporter);
+        let seq = handles.len();
+
+        handles.push(tokio::spawn(async move {
+            let target = batch[0].target;
+            let action = format!("visibility:{}", target.key());
+            let mut pending: Vec<String> = batch.iter().map(|t| t.id.clone()).collect();
+            let mut rate_limit_hits = 0;
+            let mut commentable = Vec::new();
+            let mut results = Vec::new();
+            // 已在进度条中计入的微博数，其余（多次限流、中断或未提交的）在本批结束时计入
+            let mut reported = 0;
+
+            while !pending.is_empty() {
+                wait_for_cooldown(&cooldown_until).await;
+
+                let id_refs: Vec<&str> = pending.iter().map(String::as_str).collect();
+                let mut outcome = BatchOutcome::default();
+                client
+                    .set_weibo_privacy_batch_with(&id_refs, target, |id, result| {
+                        // 限流与未提交的微博稍后重试或计为未处理，此时还没有结果
+                        if !matches!(&result, Err(e) if e.is_rate_limited() || e.is_request_limit())
+                        {
+                            pb.inc(1);
+                            reported += 1;
+                        }
+                        match &result {
+                            Err(e) if e.is_rate_limited() || e.is_request_limit() => {}
+                            Ok(()) => reporter.record(&mut results, id, &action, "success", None),
+                            Err(e) if e.is_unsupported() => {
+                                reporter.record(&mut results, id, &action, "unsupported", None)
+                            }
+                            Err(e) if e.is_not_found() && !missing_as_failure => {
+                                reporter.record(&mut results, id, &action, "missing", None)
+                            }
+                            Err(e) => reporter.record(
+                                &mut results,
+                                id,
+                                &action,
+                                "failed",
+                                Some(&e.to_string()),
+                            ),
+                        }
+                        match result {
+                            Err(e) if e.is_not_found() && missing_as_failure => outcome
+                                .failed
+                                .push((id.to_string(), e.category(), e.to_string())),
+                            result => outcome.record(id, result),
+                        }
+                    })
+                    .await;
+
+                // 重新读取后可见性不是目标的从成功中移出，不计入检查点，写入失败记录供重新处理
+                let to_verify: Vec<String> = outcome
+                    .succeeded
+                    .iter()
+                    .filter(|id| batch.iter().any(|t| &t.id == *id && t.verify))
+                    .cloned()
+                    .collect();
+                if !to_verify.is_empty() {
+                    let unverified =
+                        read_back(&client, &to_verify, target, &pacer, &reporter, &mut results)
+                            .await;
+                    outcome
+                        .succeeded
+                        .retain(|id| !unverified.iter().any(|(u, _)| u == id));
+                    if let Some(ref log) = failure_log {
+                        for (id, reason) in &unverified {
+                            if let Err(e) = log.record(id, &format!("已设置但未生效: {}", reason))
+                            {
+                                warn!("写入失败记录失败: {}", e);
+                            }
+                        }
+                    }
+                    unverified_ids.lock().unwrap().extend(unverified);
+                }
+
+                success_count.fetch_add(outcome.succeeded.len(), Ordering::Relaxed);
+
+                for id in &outcome.succeeded {
+                    let previous = batch.iter().find(|t| &t.id == id).and_then(|t| t.previous);
+                    *transitions
+                        .lock()
+                        .unwrap()
+                        .entry((previous, target))
+                        .or_default() += 1;
+                    if let Some(ref checkpoint) = checkpoint {
+                        if let Err(e) = checkpoint.record(id, previous) {
+                            warn!("写入检查点失败: {}", e);
+                        }
+                    }
+                }
+                commentable.extend(batch.iter().filter_map(|t| {
+                    let permission = t.comment_permission?;
+                    outcome
+                        .succeeded
+                        .contains(&t.id)
+                        .then(|| (t.id.clone(), permission))
+                }));
+
+                // 不支持的可见性变化属于预期内的失败，不影响请求间隔
+                if outcome.failed.is_empty() && outcome.rate_limited.is_empty() {
+                    pacer.on_success();
+                } else {
+                    pacer.on_failure();
+                }
+
+                if outcome.failed.is_empty() && outcome.unsupported.is_empty() {
+                    pb.set_message(tf("progress.batch_ok", &[&outcome.succeeded.len()]));
+                } else {
+                    pb.set_message(tf(
+                        "progress.batch_failed",
+                        &[
+                            &outcome.succeeded.len(),
+                            &(outcome.failed.len() + outcome.unsupported.len()),
+                        ],
+                    ));
+                }
+                record_failed_tasks(&failed_tasks, &batch, &outcome.failed);
+                record_failures(&failed_ids, failure_log.as_deref(), outcome.failed);
+                // 不支持的可见性变化重试也不会成功，不写入失败记录
+                unsupported_ids.lock().unwrap().extend(outcome.unsupported);
+                missing_ids.lock().unwrap().extend(outcome.missing);
+
+                // 达到 --max-requests：停止发起新的请求，按中断处理
+                if !outcome.not_sent.is_empty() {
+                    interrupt::request_stop();
+                    unprocessed.fetch_add(outcome.not_sent.len(), Ordering::Relaxed);
+                }
+
+                pending = outcome.rate_limited;
+                if pending.is_empty() {
+                    break;
+                }
+                if interrupt::is_interrupted() {
+                    unprocessed.fetch_add(pending.len(), Ordering::Relaxed);
+                    break;
+                }
+
+                rate_limit_hits += 1;
+                if rate_limit_hits > MAX_RATE_LIMIT_RETRIES {
+                    let failures = pending
+                        .drain(..)
+                        .map(|id| {
+                            let error = "多次触发频率限制".to_string();
+                            (id, FailureCategory::ApiRejected, error)
+                        })
+                        .collect::<Vec<_>>();
+                    for (id, _, error) in &failures {
+                        reporter.record(&mut results, id, &action, "failed", Some(error));
+                    }
+                    record_failed_tasks(&failed_tasks, &batch, &failures);
+                    record_failures(&failed_ids, failure_log.as_deref(), failures);
+                    break;
+                }
+
+                // 触发限流：所有任务一起暂停，冷却结束后重试未完成的部分
+                let until = Instant::now() + Duration::from_secs(rate_limit_cooldown);
+                *cooldown_until.lock().unwrap() = Some(until);
+                warn!("触发微博频率限制，暂停 {} 秒后继续", rate_limit_cooldown);
+            }
+
+            // 评论权限接口不支持批量，逐条设置；只处理可见性已设置成功的微博
+            for (id, permission) in commentable {
+                if interrupt::is_interrupted() {
+                    break;
+                }
+                let interval = pacer.interval();
+                if !interval.is_zero() {
+                    tokio::time::sleep(interval).await;
+                }
+                let comment_action = format!("comment:{}", permission.key());
+                match client.set_comment_permission(&id, permission).await {
+                    Ok(()) => {
+                        reporter.record(&mut results, &id, &comment_action, "success", None);
+                        comment_success_count.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(e) if e.is_request_limit() => {
+                        interrupt::request_stop();
+                        break;
+                    }
+                    Err(e) => {
+                        warn!("微博 {} 评论权限设置失败: {}", id, e);
+                        reporter.record(
+                            &mut results,
+                            &id,
+                            &comment_action,
+                            "failed",
+                            Some(&e.to_string()),
+                        );
+                        comment_failed_ids.lock().unwrap().push((id, e.to_string()));
+                    }
+                }
+            }
+
+            pb.inc((batch.len() - reported) as u64);
+            // 同一批内按输入顺序排列，同一条微博的可见性结果在评论权限之前
+            results.sort_by_key(|r| batch.iter().position(|t| t.id == r.id));
+            reporter.complete(seq, results);
+
+            // 延迟：持有许可期间等待，保证每个并发槽位的请求间隔
+            let interval = pacer.interval();
+            if !interval.is_zero() && !interrupt::is_interrupted() {
+                tokio::time::sleep(interval).await;
+            }
+            drop(permit);
+        }));
+    }
+
+    for handle in handles {
+        handle.await?;
+    }
+
+    finish_progress_bar(&pb);
+    reporter.finish().await;
+
+    let interrupted = interrupt::is_interrupted();
+    if interrupted {
+        unprocessed.fetch_add(drain_unprocessed(&mut rx, carry), Ordering::Relaxed);
+    }
+    let mut failed_ids = std::mem::take(&mut *failed_ids.lock().unwrap());
+    let mut failed_tasks = std::mem::take(&mut *failed_tasks.lock().unwrap());
+    let mut unsupported_ids = std::mem::take(&mut *unsupported_ids.lock().unwrap());
+    let mut missing_ids = std::mem::take(&mut *missing_ids.lock().unwrap());
+    let mut unverified_ids = std::mem::take(&mut *unverified_ids.lock().unwrap());
+    let mut comment_failed_ids = std::mem::take(&mut *comment_failed_ids.lock().unwrap());
+    sort_by_position(&mut failed_ids, &positions, |(id, _, _)| id);
+    sort_by_position(&mut failed_tasks, &positions, |t: &PrivacyTask| &t.id);
+    sort_by_position(&mut unsupported_ids, &positions, |id: &String| id);
+    sort_by_position(&mut missing_ids, &positions, |id: &String| id);
+    sort_by_position(&mut unverified_ids, &positions, |(id, _)| id);
+    sort_by_position(&mut comment_failed_ids, &positions, |(id, _)| id);
+    let transitions = std::mem::take(&mut *transitions.lock().unwrap());
+    let summary = RunSummary {
+        success_count: success_count.load(Ordering::Relaxed),
+        failed_ids,
+        unsupported_ids,
+        missing_ids,
+        unverified_ids,
+        comment_success_count: comment_success_count.load(Ordering::Relaxed),
+        comment_failed_ids,
+        transitions,
+        interrupted,
+        unprocessed: unprocessed.load(Ordering::Relaxed),
+        ..Default::default()
+    };
+    Ok((summary, failed_tasks))
+}
+
+/// 逐条永久删除微博，显示进度条并汇总结果
+///
+/// 删除接口不支持批量，`--batch-size` 不起作用；并发、延迟、限流冷却与失败记录同 hide。
+pub async fn run_deletions(
+    client: Arc<WeiboPrivacyClient>,
+    ids: Vec<String>,
+    args: &RunArgs,
+) -> Result<RunSummary> {
+    let failure_log = open_failure_log(args)?;
+    let mut summary = delete_pass(Arc::clone(&client), ids, args, failure_log).await?;
+
+    for pass in 1..=args.auto_retry_passes {
+        if summary.failed_ids.is_empty()
+            || !wait_for_retry_pass(pass, summary.failed_ids.len(), args).await
+        {
+            break;
+        }
+        let ids = summary
+            .failed_ids
+            .iter()
+            .map(|(id, _, _)| id.clone())
+            .collect();
+        let retry = delete_pass(Arc::clone(&client), ids, args, None).await?;
+        summary.merge_retry(retry);
+    }
+    Ok(summary)
+}
+
+/// 逐条删除一轮微博
+async fn delete_pass(
+    client: Arc<WeiboPrivacyClient>,
+    ids: Vec<String>,
+    args: &RunArgs,
+    failure_log: Option<Arc<FailureLog>>,
+) -> Result<RunSummary> {
+    let pb = new_progress_bar(ids.len());
+    let pacer = args.pacer()?;
+    let rate_limit_cooldown = args.rate_limit_cooldown;
+    let semaphore = Arc::new(Semaphore::new(args.concurrency as usize));
+    let success_count = Arc::new(AtomicUsize::new(0));
+    let failed_ids = Arc::new(Mutex::new(Vec::new()));
+    let missing_ids = Arc::new(Mutex::new(Vec::new()));
+    let missing_as_failure = args.treat_missing_as_failure;
+    let cooldown_until = Arc::new(Mutex::new(None));
+    let unprocessed = Arc::new(AtomicUsize::new(0));
+    let total = ids.len();
+    let reporter = Arc::new(ResultReporter::new(args));
+    let positions: HashMap<String, usize> = ids
+        .iter()
+        .enumerate()
+        .map(|(position, id)| (id.clone(), position))
+        .collect();
+    let mut started = 0;
+    let mut handles = Vec::new();
+
+    for id in ids {
+        let permit = Arc::clone(&semaphore).acquire_owned().await?;
+        if interrupt::is_interrupted() {
+            break;
+        }
+        started += 1;
+        let unprocessed = Arc::clone(&unprocessed);
+        let client = Arc::clone(&client);
+        let success_count = Arc::clone(&success_count);
+        let failed_ids = Arc::clone(&failed_ids);
+        let missing_ids = Arc::clone(&missing_ids);
+        let failure_log = failure_log.clone();
+        let cooldown_until = Arc::clone(&cooldown_until);
+        let pacer = Arc::clone(&pacer);
+        let pb = pb.clone();
+        let reporter = Arc::clone(&reporter);
+        let seq = handles.len();
+
+        handles.push(tokio::spawn(async move {
+            let mut rate_limit_hits = 0;
+            let mut results = Vec::new();
+
+            loop {
+                wait_for_cooldown(&cooldown_until).await;
+
+                match client.delete_weibo(&id).await {
+                    Ok(()) => {
+                        pacer.on_success();
+                        success_count.fetch_add(1, Ordering::Relaxed);
+                        reporter.record(&mut results, &id, "delete", "success", None);
+                        pb.set_message(tf("progress.deleted", &[&id]));
+                    }
+                    // 已经不存在的微博无需再删除
+                    Err(e) if e.is_not_found() && !missing_as_failure => {
+                        pacer.on_success();
+                        reporter.record(&mut results, &id, "delete", "missing", None);
+                        missing_ids.lock().unwrap().push(id.clone());
+                    }
+                    Err(e) if e.is_rate_limited() && interrupt::is_interrupted() => {
+                        unprocessed.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(e) if e.is_request_limit() => {
+                        interrupt::request_stop();
+                        unprocessed.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(e) if e.is_rate_limited() => {
+                        pacer.on_failure();
+                        rate_limit_hits += 1;
+                        if rate_limit_hits <= MAX_RATE_LIMIT_RETRIES {
+                            let until = Instant::now() + Duration::from_secs(rate_limit_cooldown);
+                            *cooldown_until.lock().unwrap() = Some(until);
+                            warn!("触发微博频率限制，暂停 {} 秒后继续", rate_limit_cooldown);
+                            continue;
+                        }
+                        reporter.record(
+                            &mut results,
+                            &id,
+                            "delete",
+                            "failed",
+                            Some("多次触发频率限制"),
+                        );
+                        let failures = vec![(
+                            id.clone(),
+                            FailureCategory::ApiRejected,
+                            "多次触发频率限制".to_string(),
+                        )];
+                        record_failures(&failed_ids, failure_log.as_deref(), failures);
+                    }
+                    Err(e) => {
+                        pacer.on_failure();
+                        pb.set_message(tf("progress.delete_failed", &[&id]));
+                        reporter.record(
+                            &mut results,
+                            &id,
+                            "delete",
+                            "failed",
+                            Some(&e.to_string()),
+                        );
+                        let failures = vec![(id.clone(), e.category(), e.to_string())];
+                        record_failures(&failed_ids, failure_log.as_deref(), failures);
+                    }
+                }
+                break;
+            }
+
+            pb.inc(1);
+            reporter.complete(seq, results);
+
+            let interval = pacer.interval();
+            if !interval.is_zero() && !interrupt::is_interrupted() {
+                tokio::time::sleep(interval).await;
+            }
+            drop(permit);
+        }));
+    }
+
+    for handle in handles {
+        handle.await?;
+    }
+
+    finish_progress_bar(&pb);
+    reporter.finish().await;
+
+    let mut failed_ids = std::mem::take(&mut *failed_ids.lock().unwrap());
+    sort_by_position(&mut failed_ids, &positions, |(id, _, _)| id);
+    let mut missing_ids = std::mem::take(&mut *missing_ids.lock().unwrap());
+    sort_by_position(&mut missing_ids, &positions, |id: &String| id);
+    Ok(RunSummary {
+        success_count: success_count.load(Ordering::Relaxed),
+        failed_ids,
+        missing_ids,
+        interrupted: interrupt::is_interrupted(),
+        unprocessed: total - started + unprocessed.load(Ordering::Relaxed),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failures(ids: &[&str]) -> Vec<(String, FailureCategory, String)> {
+        ids.iter()
+            .map(|id| {
+                (
+                    id.to_string(),
+                    FailureCategory::Network,
+                    "请求失败".to_string(),
+                )
+            })
+            .collect()
+    }
+
+    fn failed_ids(summary: &RunSummary) -> Vec<&str> {
+        summary
+            .failed_ids
+            .iter()
+            .map(|(id, _, _)| id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn retry_pass_replaces_failures_and_accumulates_counts() {
+        let mut summary = RunSummary {
+            success_count: 3,
+            failed_ids: failures(&["1", "2", "3"]),
+            transitions: HashMap::from([((Some(Visibility::Public), Visibility::Private), 3)]),
+            ..Default::default()
+        };
+
+        summary.merge_retry(RunSummary {
+            success_count: 2,
+            failed_ids: failures(&["3"]),
+            unsupported_ids: vec!["9".to_string()],
+            transitions: HashMap::from([
+                ((Some(Visibility::Public), Visibility::Private), 1),
+                ((None, Visibility::Private), 1),
+            ]),
+            ..Default::default()
+        });
+        assert_eq!(summary.success_count, 5);
+        assert_eq!(failed_ids(&summary), ["3"]);
+        assert_eq!(summary.unsupported_ids, ["9"]);
+        assert_eq!(
+            summary.transitions[&(Some(Visibility::Public), Visibility::Private)],
+            4
+        );
+        assert_eq!(summary.transitions[&(None, Visibility::Private)], 1);
+        assert_eq!(summary.retry_passes, 1);
+        assert!(!summary.interrupted);
+
+        // 重试中途中断：记录中断状态与未处理的数量
+        summary.merge_retry(RunSummary {
+            interrupted: true,
+            unprocessed: 1,
+            ..Default::default()
+        });
+        assert_eq!(summary.success_count, 5);
+        assert!(summary.failed_ids.is_empty());
+        assert!(summary.interrupted);
+        assert_eq!(summary.unprocessed, 1);
+        assert_eq!(summary.retry_passes, 2);
+    }
+
+    #[test]
+    fn exit_code_follows_results() {
+        let summary = |success_count, failed: &[&str], unprocessed| RunSummary {
+            success_count,
+            failed_ids: failures(failed),
+            interrupted: unprocessed > 0,
+            unprocessed,
+            ..Default::default()
+        };
+        assert_eq!(summary(3, &[], 0).exit_code(), exit_code::SUCCESS);
+        // 筛选后没有需要处理的微博
+        assert_eq!(summary(0, &[], 0).exit_code(), exit_code::SUCCESS);
+        assert_eq!(summary(2, &["3"], 0).exit_code(), exit_code::PARTIAL);
+        assert_eq!(summary(0, &["1", "2"], 0).exit_code(), exit_code::FAILURE);
+        // 中断后仍有未处理的微博，不论之前是否有成功
+        assert_eq!(summary(2, &[], 3).exit_code(), exit_code::PARTIAL);
+        assert_eq!(summary(0, &[], 3).exit_code(), exit_code::PARTIAL);
+
+        let unsupported = RunSummary {
+            unsupported_ids: vec!["1".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(unsupported.exit_code(), exit_code::FAILURE);
+        let comment_failed = RunSummary {
+            success_count: 1,
+            comment_failed_ids: vec![("1".to_string(), "请求失败".to_string())],
+            ..Default::default()
+        };
+        assert_eq!(comment_failed.exit_code(), exit_code::PARTIAL);
+    }
+
+    #[test]
+    fn failures_are_counted_by_category() {
+        let mut failed_ids = failures(&["1", "2"]);
+        failed_ids.push((
+            "3".to_string(),
+            FailureCategory::Auth,
+            "登录失效".to_string(),
+        ));
+        failed_ids.push((
+            "4".to_string(),
+            FailureCategory::Unknown,
+            "未知错误".to_string(),
+        ));
+        let summary = RunSummary {
+            failed_ids,
+            ..Default::default()
+        };
+        assert_eq!(
+            summary.failure_categories(),
+            [
+                (FailureCategory::Network, 2),
+                (FailureCategory::Auth, 1),
+                (FailureCategory::Unknown, 1),
+            ]
+        );
+    }
+
+    fn entry(id: &str) -> ResultEntry {
+        ResultEntry {
+            id: id.to_string(),
+            action: "visibility:friends".to_string(),
+            result: "success",
+            error: None,
+        }
+    }
+
+    fn entry_ids(entries: &[ResultEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.id.as_str()).collect()
+    }
+
+    #[test]
+    fn ordered_results_are_released_in_batch_order() {
+        let mut state = ReorderState::default();
+        // 后面的批次先完成，等待第 0 批
+        assert!(state.release(2, vec![entry("4")]).is_empty());
+        assert!(state.release(1, vec![entry("3")]).is_empty());
+        let ready = state.release(0, vec![entry("1"), entry("2")]);
+        assert_eq!(entry_ids(&ready), ["1", "2", "3", "4"]);
+
+        // 没有结果的批次同样推进位置
+        assert!(state.release(3, Vec::new()).is_empty());
+        assert!(state.release(5, vec![entry("6")]).is_empty());
+        assert_eq!(entry_ids(&state.release(4, vec![entry("5")])), ["5", "6"]);
+        assert!(state.pending.is_empty());
+    }
+
+    #[test]
+    fn failures_are_sorted_by_input_order() {
+        let positions = HashMap::from([
+            ("1".to_string(), 0),
+            ("2".to_string(), 1),
+            ("3".to_string(), 2),
+        ]);
+        let mut failed = failures(&["3", "x", "1", "2"]);
+        sort_by_position(&mut failed, &positions, |(id, _, _)| id);
+        let ids: Vec<&str> = failed.iter().map(|(id, _, _)| id.as_str()).collect();
+        // 不在输入中的排在最后
+        assert_eq!(ids, ["1", "2", "3", "x"]);
+    }
+
+    fn task(id: &str, target: Visibility) -> PrivacyTask {
+        PrivacyTask {
+            id: id.to_string(),
+            target,
+            previous: None,
+            comment_permission: None,
+            verify: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn next_batch_groups_consecutive_tasks_by_target() {
+        let (tx, mut rx) = mpsc::channel(8);
+        for (id, target) in [
+            ("1", Visibility::Private),
+            ("2", Visibility::Private),
+            ("3", Visibility::Private),
+            ("4", Visibility::Public),
+            ("5", Visibility::Private),
+        ] {
+            tx.send(task(id, target)).await.unwrap();
+        }
+        drop(tx);
+
+        let mut carry = None;
+        let mut batches = Vec::new();
+        while let Some(batch) = next_batch(&mut rx, &mut carry, 2).await {
+            batches.push(batch.iter().map(|t| t.id.clone()).collect::<Vec<_>>());
+        }
+        assert_eq!(batches, [vec!["1", "2"], vec!["3"], vec!["4"], vec!["5"]]);
+    }
+}
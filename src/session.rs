@@ -0,0 +1,319 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use weibo_hide::weibo_client::{WeiboAccount, WeiboPrivacyClient};
+
+/// 会话文件的格式版本，格式变化时递增，旧版本的会话视为无效
+const SESSION_VERSION: u32 = 2;
+
+/// 登录会话（`--session`），连续多次运行时不必每次重新验证 Cookie
+///
+/// 保存验证通过的账号与当前使用的 Cookie（含运行中重新获取的 XSRF-TOKEN）。下次运行时若用户提供的
+/// Cookie 未变且未超过有效期，使用保存的 Cookie（由调用方先用一次请求确认仍然有效）；否则照常验证并覆盖。
+#[derive(Debug)]
+pub struct SessionFile {
+    path: String,
+    /// 有效期，超过后重新验证
+    ttl: Duration,
+}
+
+/// 会话文件的内容
+#[derive(Serialize, Deserialize)]
+struct SessionData {
+    version: u32,
+    /// 用户提供的 Cookie 的指纹，Cookie 文件更换后会话无效
+    source: String,
+    /// 验证通过的时间（RFC 3339）
+    validated_at: String,
+    uid: String,
+    screen_name: String,
+    statuses_count: Option<i64>,
+    /// 请求使用的完整 Cookie
+    cookie: String,
+}
+
+/// 有效会话中保存的登录状态
+pub struct Session {
+    pub cookie: String,
+    pub account: WeiboAccount,
+}
+
+/// 用户提供的 Cookie 的指纹（64 位 FNV-1a），用于判断 Cookie 文件是否更换
+///
+/// 不使用标准库的 `DefaultHasher`：其结果在不同的 Rust 版本间不保证一致，升级工具链后会话会全部失效。
+fn fingerprint(cookie: &str) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let hash = cookie.trim().bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    });
+    format!("{:016x}", hash)
+}
+
+impl SessionFile {
+    pub fn new(path: String, ttl: Duration) -> Self {
+        Self { path, ttl }
+    }
+
+    /// 读取有效的会话；不存在、已过期或 Cookie 已更换时返回 None，由调用方重新验证
+    pub fn load(&self, cookie: &str) -> Option<Session> {
+        let data = self.read()?;
+        if data.version != SESSION_VERSION {
+            info!("会话 {} 由其他版本生成，将重新验证 Cookie", self.path);
+            return None;
+        }
+        if data.source != fingerprint(cookie) {
+            info!("Cookie 与会话 {} 保存时不同，将重新验证", self.path);
+            return None;
+        }
+        let validated_at = match DateTime::parse_from_rfc3339(&data.validated_at) {
+            Ok(time) => time,
+            Err(_) => {
+                warn!("会话 {} 的验证时间无效，将重新验证 Cookie", self.path);
+                return None;
+            }
+        };
+        let age = (Local::now().fixed_offset() - validated_at)
+            .to_std()
+            .unwrap_or_default();
+        if age > self.ttl {
+            info!(
+                "会话 {} 验证于 {}，已超过有效期，将重新验证 Cookie",
+                self.path,
+                validated_at.format("%Y-%m-%d %H:%M:%S")
+            );
+            return None;
+        }
+
+        info!(
+            "使用会话 {}（验证于 {}）",
+            self.path,
+            validated_at.format("%Y-%m-%d %H:%M:%S")
+        );
+        Some(Session {
+            cookie: data.cookie,
+            account: WeiboAccount {
+                uid: data.uid,
+                screen_name: data.screen_name,
+                statuses_count: data.statuses_count,
+            },
+        })
+    }
+
+    /// 写入刚验证通过的会话，`cookie` 为用户提供的 Cookie
+    pub fn save(&self, cookie: &str, client: &WeiboPrivacyClient, account: &WeiboAccount) {
+        let data = SessionData {
+            version: SESSION_VERSION,
+            source: fingerprint(cookie),
+            validated_at: Local::now().to_rfc3339(),
+            uid: account.uid.clone(),
+            screen_name: account.screen_name.clone(),
+            statuses_count: account.statuses_count,
+            cookie: client.cookie(),
+        };
+        match self.write(&data) {
+            Ok(()) => info!("✓ 会话已保存到: {}", self.path),
+            Err(e) => warn!("{:#}，下次运行将重新验证 Cookie", e),
+        }
+    }
+
+    /// 运行结束时更新会话中的 Cookie：运行中重新获取了 XSRF-TOKEN 时写入新值，验证时间不变
+    pub fn update(&self, client: &WeiboPrivacyClient) {
+        let Some(mut data) = self.read() else {
+            return;
+        };
+        let cookie = client.cookie();
+        if data.cookie == cookie {
+            return;
+        }
+        data.cookie = cookie;
+        match self.write(&data) {
+            Ok(()) => info!("✓ 已将重新获取的 XSRF-TOKEN 保存到会话 {}", self.path),
+            Err(e) => warn!("{:#}", e),
+        }
+    }
+
+    /// 读取会话文件，不存在或无法解析时返回 None
+    fn read(&self) -> Option<SessionData> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return None,
+            Err(e) => {
+                warn!("无法读取会话 {}: {}，将重新验证 Cookie", self.path, e);
+                return None;
+            }
+        };
+        match serde_json::from_str(&content) {
+            Ok(data) => Some(data),
+            Err(e) => {
+                warn!("会话 {} 格式无效: {}，将重新验证 Cookie", self.path, e);
+                None
+            }
+        }
+    }
+
+    /// 写入会话文件；其中含有完整的 Cookie，Unix 上只允许本人读写（已有的文件同样改为此权限）
+    fn write(&self, data: &SessionData) -> Result<()> {
+        let content = serde_json::to_string_pretty(data)?;
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options
+            .open(&self.path)
+            .context(format!("无法写入会话文件: {}", self.path))?;
+        // mode 只在创建文件时生效，之前创建的文件需要单独改权限
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))
+                .context(format!("无法设置会话文件的权限: {}", self.path))?;
+        }
+        file.write_all(content.as_bytes())
+            .context(format!("无法写入会话文件: {}", self.path))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOKIE: &str = "SUB=_2A25test; XSRF-TOKEN=dG9rZW4=";
+
+    /// 临时目录下本测试专用的会话文件，结束时删除
+    struct TempSession(SessionFile);
+
+    impl TempSession {
+        fn new(name: &str, ttl: Duration) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "weibo_hide_session_{}_{}.json",
+                std::process::id(),
+                name
+            ));
+            let _ = fs::remove_file(&path);
+            Self(SessionFile::new(path.to_string_lossy().into_owned(), ttl))
+        }
+    }
+
+    impl Drop for TempSession {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0.path);
+        }
+    }
+
+    fn client(cookie: &str) -> WeiboPrivacyClient {
+        WeiboPrivacyClient::builder(cookie).build().unwrap()
+    }
+
+    fn account() -> WeiboAccount {
+        WeiboAccount {
+            uid: "42".to_string(),
+            screen_name: "测试账号".to_string(),
+            statuses_count: Some(6),
+        }
+    }
+
+    /// 直接写入一份会话内容，用于构造过期或旧版本的会话
+    fn write_data(session: &SessionFile, version: u32, validated_at: DateTime<Local>) {
+        let data = SessionData {
+            version,
+            source: fingerprint(COOKIE),
+            validated_at: validated_at.to_rfc3339(),
+            uid: "42".to_string(),
+            screen_name: "测试账号".to_string(),
+            statuses_count: None,
+            cookie: COOKIE.to_string(),
+        };
+        session.write(&data).unwrap();
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let temp = TempSession::new("round_trip", Duration::from_secs(3600));
+        assert!(temp.0.load(COOKIE).is_none());
+
+        temp.0.save(COOKIE, &client(COOKIE), &account());
+        let session = temp.0.load(COOKIE).unwrap();
+        assert_eq!(session.cookie, COOKIE);
+        assert_eq!(session.account.uid, "42");
+        assert_eq!(session.account.screen_name, "测试账号");
+        assert_eq!(session.account.statuses_count, Some(6));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&temp.0.path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn other_cookie_is_not_loaded() {
+        let temp = TempSession::new("fingerprint", Duration::from_secs(3600));
+        temp.0.save(COOKIE, &client(COOKIE), &account());
+        assert!(temp.0.load("SUB=other; XSRF-TOKEN=dG9rZW4=").is_none());
+        // 首尾空白不影响指纹
+        assert!(temp.0.load(&format!("{}\n", COOKIE)).is_some());
+    }
+
+    #[test]
+    fn expired_session_is_not_loaded() {
+        let temp = TempSession::new("ttl", Duration::from_secs(3600));
+        write_data(
+            &temp.0,
+            SESSION_VERSION,
+            Local::now() - chrono::Duration::hours(2),
+        );
+        assert!(temp.0.load(COOKIE).is_none());
+
+        write_data(
+            &temp.0,
+            SESSION_VERSION,
+            Local::now() - chrono::Duration::minutes(30),
+        );
+        assert!(temp.0.load(COOKIE).is_some());
+    }
+
+    #[test]
+    fn other_version_is_not_loaded() {
+        let temp = TempSession::new("version", Duration::from_secs(3600));
+        write_data(&temp.0, SESSION_VERSION - 1, Local::now());
+        assert!(temp.0.load(COOKIE).is_none());
+    }
+
+    #[test]
+    fn update_rewrites_only_the_cookie() {
+        let temp = TempSession::new("update", Duration::from_secs(3600));
+        temp.0.save(COOKIE, &client(COOKIE), &account());
+        let before = temp.0.read().unwrap();
+
+        let refreshed = "SUB=_2A25test; XSRF-TOKEN=bmV3";
+        temp.0.update(&client(refreshed));
+        let after = temp.0.read().unwrap();
+        assert_eq!(after.cookie, refreshed);
+        assert_eq!(after.source, before.source);
+        assert_eq!(after.validated_at, before.validated_at);
+        assert_eq!(after.uid, before.uid);
+        assert_eq!(after.screen_name, before.screen_name);
+        assert_eq!(after.statuses_count, before.statuses_count);
+
+        // 用户提供的 Cookie 指纹不变，会话仍然有效，使用的是更新后的 Cookie
+        assert_eq!(temp.0.load(COOKIE).unwrap().cookie, refreshed);
+    }
+
+    #[test]
+    fn fingerprint_is_stable() {
+        // FNV-1a 的标准测试值，确保指纹不随实现或工具链变化
+        assert_eq!(fingerprint(""), "cbf29ce484222325");
+        assert_eq!(fingerprint("a"), "af63dc4c8601ec8c");
+    }
+}
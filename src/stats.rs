@@ -0,0 +1,61 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::i18n::tf;
+
+/// 请求耗时统计，多个任务并发记录
+#[derive(Debug, Default)]
+pub struct LatencyStats {
+    samples: Mutex<Vec<Duration>>,
+}
+
+impl LatencyStats {
+    /// 记录一次请求的耗时
+    pub fn record(&self, elapsed: Duration) {
+        self.samples.lock().unwrap().push(elapsed);
+    }
+
+    /// 打印请求耗时的最小值、中位数、P95、最大值以及总用时
+    pub fn print(&self, wall_time: Duration) {
+        let mut samples = self.samples.lock().unwrap().clone();
+        println!(
+            "\n{}",
+            tf("stats.wall_time", &[&format_duration(wall_time)])
+        );
+        if samples.is_empty() {
+            return;
+        }
+        samples.sort();
+
+        println!(
+            "{}",
+            tf(
+                "stats.latency",
+                &[
+                    &samples.len(),
+                    &format_duration(samples[0]),
+                    &format_duration(percentile(&samples, 0.5)),
+                    &format_duration(percentile(&samples, 0.95)),
+                    &format_duration(samples[samples.len() - 1]),
+                ]
+            )
+        );
+    }
+}
+
+/// 最近秩法求百分位数，`sorted` 需已排序且非空
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// 将耗时格式化为便于阅读的形式，如 `0.35s`、`2m05s`
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs_f64();
+    if secs < 60.0 {
+        format!("{:.2}s", secs)
+    } else {
+        let total = duration.as_secs();
+        format!("{}m{:02}s", total / 60, total % 60)
+    }
+}
@@ -1,10 +1,30 @@
-use anyhow::{anyhow, Context, Result};
-use reqwest::{Client, Response};
-use serde::Deserialize;
-use std::time::Duration;
+use chrono::{DateTime, FixedOffset};
+use rand::Rng;
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::ops::ControlFlow;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::Poll;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, trace, warn};
+
+use crate::i18n;
+use crate::rate_limit::RateLimiter;
+use crate::stats::LatencyStats;
+
+/// 客户端方法的返回类型，错误默认为 [`WeiboError`]
+pub type Result<T, E = WeiboError> = std::result::Result<T, E>;
 
 /// 微博可见性设置
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Visibility {
     /// 公开
     Public = 0,
@@ -17,296 +37,3776 @@ pub enum Visibility {
 }
 
 impl Visibility {
-    pub fn as_str(&self) -> &str {
+    /// 所有可见性，按限制程度从低到高排列
+    pub const ALL: [Visibility; 4] = [
+        Visibility::Public,
+        Visibility::FansOnly,
+        Visibility::FriendsOnly,
+        Visibility::Private,
+    ];
+
+    /// 当前界面语言（见 [`crate::i18n`]）下的名称
+    pub fn as_str(&self) -> &'static str {
+        i18n::t(match self {
+            Visibility::Public => "visibility.public",
+            Visibility::FriendsOnly => "visibility.friends",
+            Visibility::Private => "visibility.private",
+            Visibility::FansOnly => "visibility.fans",
+        })
+    }
+
+    /// 命令行与文件中使用的英文标识
+    pub fn key(&self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::FriendsOnly => "friends",
+            Visibility::Private => "private",
+            Visibility::FansOnly => "fans",
+        }
+    }
+
+    /// 根据英文标识解析，与 `key` 互逆
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "public" => Some(Visibility::Public),
+            "friends" => Some(Visibility::FriendsOnly),
+            "private" => Some(Visibility::Private),
+            "fans" => Some(Visibility::FansOnly),
+            _ => None,
+        }
+    }
+
+    /// 根据列表接口 `visible.type` 的取值解析
+    ///
+    /// 列表中仅好友可见可能以 2 或 6（好友圈）表示，未知取值返回 None。
+    pub fn from_visible_type(value: i64) -> Option<Self> {
+        match value {
+            0 => Some(Visibility::Public),
+            1 => Some(Visibility::Private),
+            2 | 6 => Some(Visibility::FriendsOnly),
+            10 => Some(Visibility::FansOnly),
+            _ => None,
+        }
+    }
+
+    /// 限制程度，数值越大可见范围越小
+    pub fn restrictiveness(&self) -> u8 {
         match self {
-            Visibility::Public => "公开",
-            Visibility::FriendsOnly => "仅好友可见",
-            Visibility::Private => "仅自己可见",
-            Visibility::FansOnly => "仅粉丝可见",
+            Visibility::Public => 0,
+            Visibility::FansOnly => 1,
+            Visibility::FriendsOnly => 2,
+            Visibility::Private => 3,
+        }
+    }
+}
+
+impl Serialize for Visibility {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.key())
+    }
+}
+
+/// 微博评论权限，即谁可以评论该微博，与可见性相互独立
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommentPermission {
+    /// 所有人可评论
+    Everyone = 0,
+    /// 我关注的人可评论
+    Following = 1,
+    /// 我的粉丝可评论
+    Fans = 2,
+    /// 关闭评论
+    Closed = 3,
+}
+
+impl CommentPermission {
+    /// 当前界面语言（见 [`crate::i18n`]）下的名称
+    pub fn as_str(&self) -> &'static str {
+        i18n::t(match self {
+            CommentPermission::Everyone => "comment.everyone",
+            CommentPermission::Following => "comment.following",
+            CommentPermission::Fans => "comment.fans",
+            CommentPermission::Closed => "comment.closed",
+        })
+    }
+
+    /// 命令行与文件中使用的英文标识
+    pub fn key(&self) -> &'static str {
+        match self {
+            CommentPermission::Everyone => "everyone",
+            CommentPermission::Following => "following",
+            CommentPermission::Fans => "fans",
+            CommentPermission::Closed => "closed",
+        }
+    }
+
+    /// 根据英文标识解析，与 `key` 互逆
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "everyone" => Some(CommentPermission::Everyone),
+            "following" => Some(CommentPermission::Following),
+            "fans" => Some(CommentPermission::Fans),
+            "closed" => Some(CommentPermission::Closed),
+            _ => None,
         }
     }
 }
 
 /// 微博信息
-#[derive(Debug, Deserialize, Clone)]
+///
+/// 从列表接口的字段反序列化（见 [`RawWeiboInfo`]），序列化时输出下列字段。
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(from = "RawWeiboInfo")]
 pub struct WeiboInfo {
-    #[serde(deserialize_with = "deserialize_number_to_string")]
     pub id: String,
     pub text: Option<String>,
     pub created_at: Option<String>,
+    /// 列表中的内容是否被截断
+    #[serde(rename = "isLongText")]
+    pub is_long_text: bool,
+    /// 当前可见性，解析自列表接口的 `visible` 字段
+    #[serde(rename = "visible")]
+    pub visibility: Option<Visibility>,
+    /// 是否为转发，依据列表接口中是否带有非空的 `retweeted_status` 字段
+    pub is_repost: bool,
+    /// 是否为置顶微博，解析自列表接口的 `isTop` 字段
+    pub is_top: bool,
+    /// 转发数，列表中缺失或无法解析时为 None
+    pub reposts_count: Option<u64>,
+    /// 评论数
+    pub comments_count: Option<u64>,
+    /// 点赞数
+    pub attitudes_count: Option<u64>,
+    /// 本条微博自身是否带有图片或视频，依据 `pic_ids`（非空）与 `page_info`（类型为视频）
+    pub has_media: bool,
+    /// 转发的原微博是否带有图片或视频，依据 `retweeted_status` 中的同样字段；不是转发时为 false
+    pub repost_has_media: bool,
+    /// 本条微博图片的地址（尽量取最大尺寸），按图片顺序排列
+    pub pic_urls: Vec<String>,
+    /// 本条微博视频的播放地址（如有）
+    pub video_url: Option<String>,
+    /// 发布来源，如 "iPhone客户端"，已去除接口返回的 HTML 标签
+    pub source: Option<String>,
+    /// 发布者的用户 ID，列表接口按用户获取时填入，用于拼出微博链接
+    pub user_id: Option<String>,
 }
 
-/// 微博列表响应
-#[derive(Debug, Deserialize)]
-struct WeiboListResponse {
-    pub ok: i32,
-    pub data: WeiboListData,
+/// 列表接口中的一条微博，字段名与取值格式按接口原样解析后转换为 [`WeiboInfo`]
+#[derive(Deserialize)]
+struct RawWeiboInfo {
+    #[serde(deserialize_with = "deserialize_number_to_string")]
+    id: String,
+    text: Option<String>,
+    created_at: Option<String>,
+    #[serde(rename = "isLongText", default)]
+    is_long_text: bool,
+    #[serde(rename = "visible", default, deserialize_with = "deserialize_visible")]
+    visibility: Option<Visibility>,
+    #[serde(flatten)]
+    media: RawMedia,
+    #[serde(default, deserialize_with = "deserialize_retweeted")]
+    retweeted_status: Option<RawMedia>,
+    #[serde(rename = "isTop", default, deserialize_with = "deserialize_flag")]
+    is_top: bool,
+    #[serde(default, deserialize_with = "deserialize_count")]
+    reposts_count: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_count")]
+    comments_count: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_count")]
+    attitudes_count: Option<u64>,
+    #[serde(default)]
+    source: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
-struct WeiboListData {
-    pub list: Vec<WeiboInfo>,
+/// 图片与视频相关的字段，取值格式不固定，按 JSON 值检查
+#[derive(Deserialize, Default)]
+struct RawMedia {
+    /// 网页版接口的图片 ID 列表
+    #[serde(default)]
+    pic_ids: Option<serde_json::Value>,
+    /// 网页版接口中各图片的多种尺寸，以图片 ID 为键
+    #[serde(default)]
+    pic_infos: Option<serde_json::Value>,
+    /// 移动版接口的图片列表
+    #[serde(default)]
+    pics: Option<serde_json::Value>,
+    #[serde(default)]
+    page_info: Option<serde_json::Value>,
 }
 
-/// 设置隐私响应
-#[derive(Debug, Deserialize)]
-struct PrivacyResponse {
-    pub ok: Option<i32>,
-    pub msg: Option<String>,
-}
+/// 网页版 `pic_infos` 中的尺寸，从大到小依次尝试
+const PIC_SIZES: [&str; 5] = ["largest", "original", "mw2000", "large", "bmiddle"];
+/// 视频 `media_info` 中的播放地址字段，从清晰到模糊依次尝试
+const VIDEO_URL_FIELDS: [&str; 5] = [
+    "mp4_720p_mp4",
+    "mp4_hd_url",
+    "mp4_sd_url",
+    "stream_url_hd",
+    "stream_url",
+];
 
-// 自定义反序列化：将数字转换为字符串
-fn deserialize_number_to_string<'de, D>(deserializer: D) -> Result<String, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    use serde::de::{self, Visitor};
-    use std::fmt;
+impl RawMedia {
+    /// 有图片，或 `page_info` 为视频（`type` 为 video 或 11，或 `object_type` 为 video）
+    ///
+    /// 文章、链接卡片等其他 `page_info` 不算作媒体。
+    fn has_media(&self) -> bool {
+        let non_empty = |value: &Option<serde_json::Value>| match value {
+            Some(serde_json::Value::Array(items)) => !items.is_empty(),
+            Some(serde_json::Value::Object(items)) => !items.is_empty(),
+            _ => false,
+        };
+        let has_video = self.page_info.as_ref().is_some_and(|info| {
+            let kind = info.get("type").and_then(value_to_id);
+            matches!(kind.as_deref(), Some("video" | "11"))
+                || info.get("object_type").and_then(|t| t.as_str()) == Some("video")
+        });
+        non_empty(&self.pic_ids) || non_empty(&self.pics) || has_video
+    }
 
-    struct StringOrNumber;
+    /// 图片地址：网页版按 `pic_ids` 的顺序取 `pic_infos` 中最大的尺寸，缺少 `pic_infos` 时按图片 ID
+    /// 拼出大图地址；移动版取 `pics` 中的 `large.url`，没有时取 `url`
+    fn pic_urls(&self) -> Vec<String> {
+        let url_of = |value: &serde_json::Value| {
+            value
+                .get("url")
+                .and_then(|u| u.as_str())
+                .filter(|u| !u.is_empty())
+                .map(str::to_string)
+        };
+        if let Some(serde_json::Value::Array(pics)) = &self.pics {
+            return pics
+                .iter()
+                .filter_map(|pic| pic.get("large").and_then(url_of).or_else(|| url_of(pic)))
+                .collect();
+        }
+        let Some(serde_json::Value::Array(ids)) = &self.pic_ids else {
+            return Vec::new();
+        };
+        ids.iter()
+            .filter_map(value_to_id)
+            .map(|pid| {
+                self.pic_infos
+                    .as_ref()
+                    .and_then(|infos| infos.get(&pid))
+                    .and_then(|info| {
+                        PIC_SIZES
+                            .iter()
+                            .find_map(|size| info.get(*size).and_then(url_of))
+                    })
+                    .unwrap_or_else(|| format!("https://wx1.sinaimg.cn/large/{}.jpg", pid))
+            })
+            .collect()
+    }
 
-    impl<'de> Visitor<'de> for StringOrNumber {
-        type Value = String;
+    /// 视频的播放地址，取 `page_info.media_info` 中最清晰的一个
+    fn video_url(&self) -> Option<String> {
+        let media_info = self.page_info.as_ref()?.get("media_info")?;
+        VIDEO_URL_FIELDS.iter().find_map(|field| {
+            media_info
+                .get(*field)
+                .and_then(|u| u.as_str())
+                .filter(|u| !u.is_empty())
+                .map(str::to_string)
+        })
+    }
+}
 
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("a string or number")
+/// 去除 HTML 标签，用于 `source` 这类带链接的字段；结果为空时返回 None
+fn strip_tags(html: &str) -> Option<String> {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
         }
+    }
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
 
-        fn visit_i64<E>(self, value: i64) -> Result<String, E>
-        where
-            E: de::Error,
-        {
-            Ok(value.to_string())
+impl From<RawWeiboInfo> for WeiboInfo {
+    fn from(raw: RawWeiboInfo) -> Self {
+        Self {
+            id: raw.id,
+            text: raw.text,
+            created_at: raw.created_at,
+            is_long_text: raw.is_long_text,
+            visibility: raw.visibility,
+            is_repost: raw.retweeted_status.is_some(),
+            is_top: raw.is_top,
+            reposts_count: raw.reposts_count,
+            comments_count: raw.comments_count,
+            attitudes_count: raw.attitudes_count,
+            has_media: raw.media.has_media(),
+            repost_has_media: raw.retweeted_status.is_some_and(|r| r.has_media()),
+            pic_urls: raw.media.pic_urls(),
+            video_url: raw.media.video_url(),
+            source: raw.source.as_deref().and_then(strip_tags),
+            user_id: None,
         }
+    }
+}
 
-        fn visit_u64<E>(self, value: u64) -> Result<String, E>
-        where
-            E: de::Error,
-        {
-            Ok(value.to_string())
+impl WeiboInfo {
+    /// 只有 ID 的微博信息，用于直接按 ID 处理而不获取列表的场景
+    pub fn from_id(id: String) -> Self {
+        Self {
+            id,
+            text: None,
+            created_at: None,
+            is_long_text: false,
+            visibility: None,
+            is_repost: false,
+            is_top: false,
+            reposts_count: None,
+            comments_count: None,
+            attitudes_count: None,
+            has_media: false,
+            repost_has_media: false,
+            pic_urls: Vec::new(),
+            video_url: None,
+            source: None,
+            user_id: None,
         }
+    }
 
-        fn visit_str<E>(self, value: &str) -> Result<String, E>
-        where
-            E: de::Error,
-        {
-            Ok(value.to_string())
+    /// 微博的网页链接 `https://weibo.com/<uid>/<bid>`；不知道发布者或 ID 不是数字时为 None
+    pub fn permalink(&self) -> Option<String> {
+        let uid = self.user_id.as_deref().filter(|uid| !uid.is_empty())?;
+        Some(format!(
+            "https://weibo.com/{}/{}",
+            uid,
+            mid_to_bid(&self.id)?
+        ))
+    }
+
+    /// 发布时间的 ISO-8601 形式（如 `2019-10-09T15:30:00+08:00`），无法解析时为 None
+    pub fn created_at_iso(&self) -> Option<String> {
+        let created_at = self.created_at.as_deref()?;
+        parse_weibo_time(created_at).ok().map(|t| t.to_rfc3339())
+    }
+}
+
+/// base62 编码使用的字符表
+const BASE62_ALPHABET: &[u8; 62] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// 把数字形式的微博 ID（mid）转换为链接中使用的短 ID（bid），如 `3501756485200075` → `z0JH2lOMb`
+///
+/// 从右往左每 7 位十进制数字为一组，每组转换为 base62，除最左一组外补足 4 位。
+/// ID 为空或含非数字字符时返回 None。
+pub fn mid_to_bid(mid: &str) -> Option<String> {
+    if mid.is_empty() || !mid.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let mut groups = Vec::new();
+    let mut end = mid.len();
+    while end > 0 {
+        let start = end.saturating_sub(7);
+        let mut value: u64 = mid[start..end].parse().ok()?;
+        let mut digits = Vec::new();
+        while value > 0 {
+            digits.push(BASE62_ALPHABET[(value % 62) as usize]);
+            value /= 62;
         }
+        if start > 0 {
+            digits.resize(4, b'0');
+        }
+        digits.reverse();
+        groups.push(String::from_utf8(digits).ok()?);
+        end = start;
     }
+    groups.reverse();
+    let bid = groups.concat();
+    Some(if bid.is_empty() { "0".to_string() } else { bid })
+}
 
-    deserializer.deserialize_any(StringOrNumber)
+/// 微博 created_at 字段的时间格式，例如 "Wed Oct 09 15:30:00 +0800 2019"
+const WEIBO_TIME_FORMAT: &str = "%a %b %d %H:%M:%S %z %Y";
+
+/// 解析微博的 created_at 时间字符串
+pub fn parse_weibo_time(created_at: &str) -> Result<DateTime<FixedOffset>, chrono::ParseError> {
+    DateTime::parse_from_str(created_at.trim(), WEIBO_TIME_FORMAT)
 }
 
-pub struct WeiboPrivacyClient {
-    client: Client,
-    cookie: String,
-    xsrf_token: String,
+/// 获取微博列表使用的接口
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Api {
+    /// 网页版接口 weibo.com/ajax
+    #[default]
+    Desktop,
+    /// 移动版接口 m.weibo.cn/api/container
+    Mobile,
 }
 
-const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
-const REQUEST_TIMEOUT_SECS: u64 = 30;
-const MAX_RETRIES: u32 = 3;
+/// 移动版接口的地址
+const MOBILE_BASE_URL: &str = "https://m.weibo.cn";
 
-impl WeiboPrivacyClient {
-    /// 创建新客户端
-    pub fn new(cookie: String) -> Result<Self> {
-        let xsrf_token = Self::extract_xsrf_token(&cookie)
-            .ok_or_else(|| anyhow!("无法从 Cookie 中提取 XSRF-TOKEN，请确保 Cookie 完整"))?;
-
-        let client = Client::builder()
-            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
-            .cookie_store(true)
-            .user_agent(USER_AGENT)
-            .build()
-            .context("Failed to build HTTP client")?;
+impl Api {
+    /// 第 `page` 页的列表地址，`since_id` 为上一页返回的游标
+    ///
+    /// 网页版接口使用客户端的 `base_url`，移动版接口固定为 m.weibo.cn 且不支持 `feature` 与 `page_size`。
+    fn list_url(
+        &self,
+        base_url: &str,
+        user_id: &str,
+        page: u32,
+        options: &FetchOptions,
+        since_id: Option<&str>,
+    ) -> String {
+        let mut url = match self {
+            Api::Desktop => {
+                let mut url = format!(
+                    "{}/ajax/statuses/mymblog?uid={}&page={}&feature={}",
+                    base_url, user_id, page, options.feature
+                );
+                if let Some(size) = options.page_size {
+                    url.push_str(&format!("&count={}", size.clamp(1, MAX_PAGE_SIZE)));
+                }
+                url
+            }
+            Api::Mobile => format!(
+                "{}/api/container/getIndex?containerid=107603{}&page={}",
+                MOBILE_BASE_URL, user_id, page
+            ),
+        };
+        if let Some(cursor) = since_id {
+            url.push_str(&format!("&since_id={}", cursor));
+        }
+        url
+    }
 
-        Ok(Self {
-            client,
-            cookie,
-            xsrf_token,
-        })
+    fn referer(&self, base_url: &str, user_id: &str) -> String {
+        match self {
+            Api::Desktop => format!("{}/u/{}", base_url, user_id),
+            Api::Mobile => format!("{}/u/{}", MOBILE_BASE_URL, user_id),
+        }
     }
 
-    /// 从 Cookie 中提取 XSRF-TOKEN
-    fn extract_xsrf_token(cookie: &str) -> Option<String> {
-        cookie
-            .split(';')
-            .find(|s| s.trim().starts_with("XSRF-TOKEN="))
-            .and_then(|s| s.split('=').nth(1))
-            .map(|s| s.trim().to_string())
+    /// 按接口对应的格式解析一页响应
+    fn parse_page(&self, text: &str, page: u32) -> Result<ListPage> {
+        // 列表接口正常时总会返回 JSON，空响应说明被拦截
+        if text.trim().is_empty() {
+            return Err(WeiboError::login_wall());
+        }
+        let context = format!("无法解析第 {} 页的列表响应", page);
+        match self {
+            Api::Desktop => serde_json::from_str::<WeiboListResponse>(text)
+                .map_err(WeiboError::parse(context))?
+                .into_page(page),
+            Api::Mobile => serde_json::from_str::<MobileContainerResponse>(text)
+                .map_err(WeiboError::parse(context))?
+                .into_page(page),
+        }
     }
+}
 
-    /// 获取用户所有微博 ID 列表
-    pub async fn get_all_weibo_ids(&self, user_id: &str, max_pages: Option<u32>) -> Result<Vec<WeiboInfo>> {
-        let mut all_weibos = Vec::new();
-        let mut page = 1;
-        let max_pages = max_pages.unwrap_or(u32::MAX);
+/// 解析后的一页微博列表
+#[derive(Debug)]
+struct ListPage {
+    weibos: Vec<WeiboInfo>,
+    /// 下一页的游标，为空表示没有更多
+    since_id: Option<String>,
+    /// 本页返回的微博总数；超话中只保留本人的微博，`weibos` 可能为空而本页并非最后一页
+    scanned: usize,
+    /// 结构异常、无法解析而跳过的微博数量（计入 `scanned`）
+    malformed: usize,
+}
 
-        loop {
-            if page > max_pages {
-                break;
+impl ListPage {
+    fn new(weibos: Vec<WeiboInfo>, since_id: Option<String>) -> Self {
+        let scanned = weibos.len();
+        Self {
+            weibos,
+            since_id,
+            scanned,
+            malformed: 0,
+        }
+    }
+
+    /// 逐条解析后的一页，`malformed` 为跳过的条数
+    fn with_malformed(weibos: Vec<WeiboInfo>, since_id: Option<String>, malformed: usize) -> Self {
+        Self {
+            scanned: weibos.len() + malformed,
+            malformed,
+            ..Self::new(weibos, since_id)
+        }
+    }
+}
+
+/// 逐条解析列表中的微博，结构异常的跳过并计数，不影响同一页的其他微博
+fn parse_items<T: DeserializeOwned>(items: Vec<serde_json::Value>, page: u32) -> (Vec<T>, usize) {
+    let mut parsed = Vec::with_capacity(items.len());
+    let mut malformed = 0;
+    for item in items {
+        let id = match item.get("id") {
+            Some(serde_json::Value::String(id)) => id.clone(),
+            Some(id) => id.to_string(),
+            None => "（无 ID）".to_string(),
+        };
+        match serde_json::from_value(item) {
+            Ok(value) => parsed.push(value),
+            Err(e) => {
+                malformed += 1;
+                warn!("第 {} 页的微博 {} 无法解析，已跳过: {}", page, id, e);
             }
+        }
+    }
+    (parsed, malformed)
+}
 
-            let url = format!(
-                "https://weibo.com/ajax/statuses/mymblog?uid={}&page={}&feature=0",
-                user_id, page
-            );
+/// 超话在移动版容器接口中的 containerid 前缀
+const TOPIC_CONTAINER_PREFIX: &str = "100808";
 
-            let response = self.get_with_retry(&url, user_id).await?;
-            let response_text = response.text().await?;
+/// 超话微博流的地址，`topic` 为超话 ID（可带或不带 100808 前缀）
+fn topic_url(topic: &str, page: u32, since_id: Option<&str>) -> String {
+    let mut url = format!(
+        "{}/api/container/getIndex?containerid={}_-_feed&page={}",
+        MOBILE_BASE_URL,
+        topic_container_id(topic),
+        page
+    );
+    if let Some(cursor) = since_id {
+        url.push_str(&format!("&since_id={}", cursor));
+    }
+    url
+}
 
-            let weibo_response: WeiboListResponse = serde_json::from_str(&response_text)
-                .context(format!("Failed to parse JSON response at page {}", page))?;
+fn topic_container_id(topic: &str) -> String {
+    if topic.starts_with(TOPIC_CONTAINER_PREFIX) {
+        topic.to_string()
+    } else {
+        format!("{}{}", TOPIC_CONTAINER_PREFIX, topic)
+    }
+}
 
-            if weibo_response.ok != 1 {
-                return Err(anyhow!("API 返回错误: ok={}", weibo_response.ok));
-            }
+/// 解析超话微博流的一页，只保留 `user_id` 发布的微博
+///
+/// 响应与移动版列表相同为卡片结构，微博卡片可能嵌套在 `card_group` 中。
+/// 第一页就无法获取时说明超话不存在或无权访问。
+fn parse_topic_page(text: &str, page: u32, topic: &str, user_id: &str) -> Result<ListPage> {
+    if text.trim().is_empty() {
+        return Err(WeiboError::login_wall());
+    }
+    let resp: MobileContainerResponse = serde_json::from_str(text).map_err(WeiboError::parse(
+        format!("无法解析超话第 {} 页的响应", page),
+    ))?;
+    let data = match (resp.ok, resp.data) {
+        (1, Some(data)) => data,
+        (_, Some(data)) if page > 1 && data.cards.is_empty() => {
+            return Ok(ListPage::new(Vec::new(), None))
+        }
+        (ok, _) => {
+            return Err(WeiboError::api(format!(
+                "超话 {} 不存在或无法访问（ok={} {}）",
+                topic,
+                ok,
+                resp.msg.unwrap_or_default()
+            )))
+        }
+    };
 
-            let weibos = weibo_response.data.list;
+    let since_id = data.cardlist_info.and_then(|i| i.since_id);
+    let items = data
+        .cards
+        .into_iter()
+        .flat_map(MobileCard::into_mblogs)
+        .collect();
+    let (mblogs, malformed): (Vec<MobileMblog>, _) = parse_items(items, page);
+    let scanned = mblogs.len() + malformed;
+    let weibos = mblogs
+        .into_iter()
+        .filter(|m| m.user.as_ref().is_some_and(|u| u.id == user_id))
+        .map(|m| m.weibo)
+        .collect();
+    Ok(ListPage {
+        weibos,
+        since_id,
+        scanned,
+        malformed,
+    })
+}
 
-            if weibos.is_empty() {
-                break;
-            }
+/// 不同接口的列表响应，统一转换为 [`ListPage`]
+trait IntoListPage {
+    fn into_page(self, page: u32) -> Result<ListPage>;
+}
 
-            println!("✓ 第 {} 页: 获取 {} 条微博", page, weibos.len());
-            all_weibos.extend(weibos);
+/// 微博列表响应
+#[derive(Debug, Deserialize)]
+struct WeiboListResponse {
+    pub ok: i32,
+    pub data: WeiboListData,
+}
 
-            page += 1;
+#[derive(Debug, Deserialize)]
+struct WeiboListData {
+    /// 逐条解析，见 [`parse_items`]
+    pub list: Vec<serde_json::Value>,
+    /// 下一页的游标，为空表示没有更多
+    #[serde(default, deserialize_with = "deserialize_cursor")]
+    pub since_id: Option<String>,
+}
 
-            // 避免请求过快
-            tokio::time::sleep(Duration::from_secs(1)).await;
+impl IntoListPage for WeiboListResponse {
+    fn into_page(self, page: u32) -> Result<ListPage> {
+        if self.ok != 1 {
+            return Err(WeiboError::api(format!("ok={}", self.ok)));
         }
-
-        Ok(all_weibos)
+        let (weibos, malformed) = parse_items(self.data.list, page);
+        Ok(ListPage::with_malformed(
+            weibos,
+            self.data.since_id,
+            malformed,
+        ))
     }
+}
 
-    /// 设置微博隐私
-    pub async fn set_weibo_privacy(&self, weibo_id: &str, visibility: Visibility) -> Result<()> {
-        // 微博设置隐私的 API 端点（根据实际抓包结果）
-        let url = "https://weibo.com/ajax/statuses/modifyVisible";
+/// 移动版容器接口响应
+#[derive(Debug, Deserialize)]
+struct MobileContainerResponse {
+    pub ok: i32,
+    pub msg: Option<String>,
+    pub data: Option<MobileContainerData>,
+}
 
-        let visible_value = match visibility {
-            Visibility::Public => 0,
-            Visibility::FriendsOnly => 2,
-            Visibility::Private => 1,
-            Visibility::FansOnly => 10,
-        };
+#[derive(Debug, Deserialize)]
+struct MobileContainerData {
+    #[serde(default)]
+    pub cards: Vec<MobileCard>,
+    #[serde(rename = "cardlistInfo")]
+    pub cardlist_info: Option<MobileCardlistInfo>,
+}
 
-        // 使用 form 格式，参数名是 ids（复数）不是 id
-        let visible_str = visible_value.to_string();
-        let params = vec![("ids", weibo_id), ("visible", visible_str.as_str())];
+/// 卡片列表中的一项，只有微博卡片带有 `mblog`；超话等页面中微博卡片嵌套在 `card_group` 里
+#[derive(Debug, Deserialize)]
+struct MobileCard {
+    /// 逐条解析，见 [`parse_items`]
+    pub mblog: Option<serde_json::Value>,
+    #[serde(default)]
+    pub card_group: Vec<MobileCard>,
+}
 
-        //println!("\n[DEBUG] 设置微博 {} 的隐私，参数: ids={}, visible={}", weibo_id, weibo_id, visible_str);
+impl MobileCard {
+    /// 本卡片及其嵌套卡片中的全部微博
+    fn into_mblogs(self) -> Vec<serde_json::Value> {
+        let mut mblogs: Vec<serde_json::Value> = self.mblog.into_iter().collect();
+        mblogs.extend(
+            self.card_group
+                .into_iter()
+                .flat_map(MobileCard::into_mblogs),
+        );
+        mblogs
+    }
+}
 
-        for retry in 0..MAX_RETRIES {
-            let request = self
-                .client
-                .post(url)
-                .header("Accept", "application/json, text/plain, */*")
-                .header("Accept-Language", "zh-CN,zh;q=0.9,en;q=0.8")
-                .header("Content-Type", "application/x-www-form-urlencoded")
-                .header("Cookie", &self.cookie)
-                .header("X-Xsrf-Token", &self.xsrf_token)
-                .header("X-Requested-With", "XMLHttpRequest")
-                .header("Referer", "https://weibo.com")
-                .header("Origin", "https://weibo.com")
-                .header("Client-Version", "3.0.0")
-                .header("Sec-Fetch-Dest", "empty")
-                .header("Sec-Fetch-Mode", "cors")
-                .header("Sec-Fetch-Site", "same-origin")
-                .form(&params);
+/// 移动版接口中的微博，另带发布者信息
+#[derive(Debug, Deserialize)]
+struct MobileMblog {
+    #[serde(flatten)]
+    pub weibo: WeiboInfo,
+    pub user: Option<MobileUser>,
+}
 
-            match request.send().await {
-                Ok(response) => {
-                    let status = response.status();
+#[derive(Debug, Deserialize)]
+struct MobileUser {
+    #[serde(deserialize_with = "deserialize_number_to_string")]
+    pub id: String,
+}
 
-                    if status.is_success() {
-                        // 尝试解析响应
-                        let text = response.text().await?;
-
-                        // 打印响应内容用于调试
-                        println!("\n[DEBUG] 微博 {} 响应: {}", weibo_id, &text[..std::cmp::min(200, text.len())]);
-
-                        // 微博 API 可能返回不同格式，我们尝试解析
-                        if let Ok(privacy_resp) = serde_json::from_str::<PrivacyResponse>(&text) {
-                            if let Some(ok) = privacy_resp.ok {
-                                if ok == 1 {
-                                    return Ok(());
-                                } else {
-                                    return Err(anyhow!(
-                                        "设置失败: {}",
-                                        privacy_resp.msg.unwrap_or_else(|| "未知错误".to_string())
-                                    ));
-                                }
-                            }
-                        }
-
-                        // 如果成功但无法解析，也视为成功
-                        return Ok(());
-                    }
+#[derive(Debug, Deserialize)]
+struct MobileCardlistInfo {
+    #[serde(default, deserialize_with = "deserialize_cursor")]
+    pub since_id: Option<String>,
+}
 
-                    if retry == MAX_RETRIES - 1 {
-                        let error_body = response.text().await.unwrap_or_default();
-                        println!("\n[DEBUG] HTTP 错误 {}: {}", status, &error_body[..std::cmp::min(500, error_body.len())]);
-                        return Err(anyhow!("HTTP error {}: {}", status, error_body));
-                    }
-                }
-                Err(e) => {
-                    if retry == MAX_RETRIES - 1 {
-                        return Err(anyhow!("请求失败: {}", e));
-                    }
-                }
+impl IntoListPage for MobileContainerResponse {
+    fn into_page(self, page: u32) -> Result<ListPage> {
+        let data = match (self.ok, self.data) {
+            (1, Some(data)) => data,
+            // 翻到最后一页之后接口返回 ok=0 与空列表（"这里还没有内容"）
+            (_, Some(data)) if data.cards.is_empty() => return Ok(ListPage::new(Vec::new(), None)),
+            (ok, _) => {
+                return Err(WeiboError::api(format!(
+                    "ok={} {}",
+                    ok,
+                    self.msg.unwrap_or_default()
+                )))
             }
+        };
+        let items = data
+            .cards
+            .into_iter()
+            .flat_map(MobileCard::into_mblogs)
+            .collect();
+        let (mblogs, malformed): (Vec<MobileMblog>, _) = parse_items(items, page);
+        Ok(ListPage::with_malformed(
+            mblogs.into_iter().map(|m| m.weibo).collect(),
+            data.cardlist_info.and_then(|i| i.since_id),
+            malformed,
+        ))
+    }
+}
 
-            // 指数退避
-            let delay = Duration::from_secs(2u64.pow(retry));
-            tokio::time::sleep(delay).await;
-        }
+/// 客户端错误
+///
+/// 带 `code` 的几类来自接口返回的错误，`code` 为 errno / error_code（如有），`msg` 为接口返回的说明。
+#[derive(Debug, thiserror::Error)]
+pub enum WeiboError {
+    /// Cookie 不完整，无法提取 XSRF-TOKEN
+    #[error("无法从 Cookie 中提取 XSRF-TOKEN，请确保 Cookie 完整")]
+    InvalidCookie,
+    /// 未登录或 Cookie 已失效
+    #[error("未登录或 Cookie 已失效: {msg}{}", code_suffix(*.code, .msg))]
+    NotLoggedIn { code: Option<i64>, msg: String },
+    /// XSRF-TOKEN 无效或已过期（长时间运行期间被微博更换），且无法重新获取
+    #[error("XSRF-TOKEN 无效或已过期: {msg}{}", code_suffix(*.code, .msg))]
+    TokenExpired { code: Option<i64>, msg: String },
+    /// 请求过于频繁，被微博限流
+    #[error("触发频率限制: {msg}{}", code_suffix(*.code, .msg))]
+    RateLimited { code: Option<i64>, msg: String },
+    /// 微博不存在或已被删除
+    #[error("微博不存在: {msg}{}", code_suffix(*.code, .msg))]
+    NotFound { code: Option<i64>, msg: String },
+    /// 该微博不支持设置为目标可见性（如部分微博不能设为仅粉丝可见），属于预期内的失败
+    #[error("不支持目标可见性: {msg}{}", code_suffix(*.code, .msg))]
+    Unsupported { code: Option<i64>, msg: String },
+    /// 接口返回的其他错误
+    #[error("接口返回错误: {msg}{}", code_suffix(*.code, .msg))]
+    Api { code: Option<i64>, msg: String },
+    /// 连接失败、超时等网络错误
+    #[error("请求失败: {0}")]
+    Network(#[from] reqwest::Error),
+    /// 重试后仍为非成功的 HTTP 状态码
+    #[error("HTTP 错误 {status}: {body}")]
+    Http { status: StatusCode, body: String },
+    /// 响应无法解析
+    #[error("{context}: {source}")]
+    Parse {
+        context: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// 响应使用了当前构建不支持的压缩方式
+    #[error("响应解码失败，可能缺少压缩支持（Content-Encoding: {0}）")]
+    Decode(String),
+    /// 客户端参数无效（如代理地址）
+    #[error("{0}")]
+    Config(String),
+    /// 已达到请求数上限，不再发起新的请求
+    #[error("已达到请求数上限（{limit} 次），为安全起见停止发起新的请求")]
+    RequestLimit { limit: u64 },
+}
 
-        unreachable!()
+/// 失败原因的大类，用于在汇总中区分网络问题与微博拒绝
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FailureCategory {
+    /// 连接失败、超时或重试后仍为错误的 HTTP 状态码
+    Network,
+    /// 未登录、Cookie 失效或 XSRF-TOKEN 过期
+    Auth,
+    /// 接口明确拒绝了本次操作（ok≠1、限流等）
+    ApiRejected,
+    /// 无法解析的响应等其他错误
+    Unknown,
+}
+
+impl FailureCategory {
+    /// 汇总中显示的名称
+    pub fn as_str(&self) -> &'static str {
+        i18n::t(match self {
+            FailureCategory::Network => "failure.network",
+            FailureCategory::Auth => "failure.auth",
+            FailureCategory::ApiRejected => "failure.api",
+            FailureCategory::Unknown => "failure.unknown",
+        })
     }
+}
 
-    /// 带重试的 GET 请求
-    async fn get_with_retry(&self, url: &str, user_id: &str) -> Result<Response> {
-        for retry in 0..MAX_RETRIES {
-            let request = self
-                .client
-                .get(url)
-                .header("Accept", "application/json, text/plain, */*")
-                .header("Accept-Language", "zh-CN,zh;q=0.9")
-                .header("Referer", format!("https://weibo.com/u/{}", user_id))
-                .header("X-Requested-With", "XMLHttpRequest")
-                .header("Cookie", &self.cookie)
-                .header("X-Xsrf-Token", &self.xsrf_token)
-                .header("Accept-Encoding", "gzip, deflate, br, zstd")
-                .header("Client-Version", "v2.47.139")
-                .header("Sec-Fetch-Dest", "empty")
-                .header("Sec-Fetch-Mode", "cors")
-                .header("Sec-Fetch-Site", "same-origin");
+/// msg 中同时出现 `token` 与以下说法之一时识别为 [`WeiboError::TokenExpired`]
+const TOKEN_EXPIRED_HINTS: [&str; 5] = ["无效", "过期", "失效", "invalid", "expired"];
 
-            match request.send().await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        return Ok(response);
-                    }
+/// 设置可见性被拒绝时 msg 中常见的说法，据此识别为 [`WeiboError::Unsupported`]
+const UNSUPPORTED_VISIBILITY_HINTS: [&str; 5] =
+    ["不支持", "无法设置", "不能设置", "不可设置", "不允许"];
 
-                    if retry == MAX_RETRIES - 1 {
-                        let status = response.status();
-                        let error_body = response.text().await.unwrap_or_default();
-                        return Err(anyhow!("HTTP error {}: {}", status, error_body));
-                    }
-                }
-                Err(e) => {
-                    if retry == MAX_RETRIES - 1 {
-                        return Err(anyhow!("Failed to request: {}", e));
-                    }
-                }
-            }
+/// 常见错误码对应的说明
+pub fn describe_error_code(code: i64) -> Option<&'static str> {
+    match code {
+        10022 => Some("IP 请求频次超过上限"),
+        10023 => Some("用户请求频次超过上限"),
+        10024 => Some("用户请求特殊接口频次超过上限"),
+        20003 => Some("用户不存在"),
+        20101 => Some("微博不存在或已被删除"),
+        20112 => Some("没有权限操作此微博"),
+        21301 => Some("认证失败，请重新登录"),
+        21327 => Some("登录状态已过期"),
+        -100 => Some("未登录"),
+        _ => None,
+    }
+}
 
-            let delay = Duration::from_secs(2u64.pow(retry));
-            tokio::time::sleep(delay).await;
+/// 错误码及其说明，说明与 msg 相同时只显示错误码
+fn code_suffix(code: Option<i64>, msg: &str) -> String {
+    match code {
+        Some(code) => match describe_error_code(code) {
+            Some(desc) if desc != msg => format!("（错误码 {}：{}）", code, desc),
+            _ => format!("（错误码 {}）", code),
+        },
+        None => String::new(),
+    }
+}
+
+impl WeiboError {
+    /// 根据接口返回的错误码和 msg 判断错误类型，错误码优先
+    pub fn from_response(code: Option<i64>, msg: Option<&str>) -> Self {
+        let msg = msg
+            .filter(|m| !m.is_empty())
+            .map(str::to_string)
+            .or_else(|| code.and_then(describe_error_code).map(str::to_string))
+            .unwrap_or_else(|| "未知错误".to_string());
+
+        match code {
+            Some(10022..=10024) => return WeiboError::RateLimited { code, msg },
+            Some(20101) => return WeiboError::NotFound { code, msg },
+            Some(21301 | 21327 | -100) => return WeiboError::NotLoggedIn { code, msg },
+            _ => {}
+        }
+
+        let lower = msg.to_lowercase();
+        if lower.contains("token") && TOKEN_EXPIRED_HINTS.iter().any(|h| lower.contains(h)) {
+            WeiboError::TokenExpired { code, msg }
+        } else if msg.contains("频繁") || msg.contains("稍后再试") {
+            WeiboError::RateLimited { code, msg }
+        } else if msg.contains("登录") {
+            WeiboError::NotLoggedIn { code, msg }
+        } else if msg.contains("不存在") || msg.contains("已删除") || msg.contains("已被删除")
+        {
+            WeiboError::NotFound { code, msg }
+        } else {
+            WeiboError::Api { code, msg }
+        }
+    }
+
+    /// 接口返回 HTML 登录页或空响应，通常是 Cookie 已过期
+    fn login_wall() -> Self {
+        WeiboError::NotLoggedIn {
+            code: None,
+            msg: "Cookie 可能已过期，请重新登录获取".to_string(),
+        }
+    }
+
+    /// 设置可见性时的错误：接口拒绝该可见性变化的归为 `Unsupported`
+    fn for_visibility_change(self) -> Self {
+        match self {
+            WeiboError::Api { code, msg }
+                if UNSUPPORTED_VISIBILITY_HINTS
+                    .iter()
+                    .any(|hint| msg.contains(hint)) =>
+            {
+                WeiboError::Unsupported { code, msg }
+            }
+            other => other,
+        }
+    }
+
+    /// 不带错误码的限流错误，用于 HTTP 429 等情况
+    fn rate_limited(msg: String) -> Self {
+        WeiboError::RateLimited { code: None, msg }
+    }
+
+    /// 不带错误码的接口错误，用于只返回 ok 字段的接口
+    fn api(msg: String) -> Self {
+        WeiboError::Api { code: None, msg }
+    }
+
+    /// JSON 解析错误，`context` 说明解析的是哪个响应
+    fn parse(context: impl Into<String>) -> impl FnOnce(serde_json::Error) -> Self {
+        let context = context.into();
+        move |source| WeiboError::Parse { context, source }
+    }
+
+    /// 是否为限流
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, WeiboError::RateLimited { .. })
+    }
+
+    /// 是否为 XSRF-TOKEN 无效或已过期
+    pub fn is_token_expired(&self) -> bool {
+        matches!(self, WeiboError::TokenExpired { .. })
+    }
+
+    /// 是否为微博不存在（已被删除）
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, WeiboError::NotFound { .. })
+    }
+
+    /// 是否为不支持目标可见性
+    pub fn is_unsupported(&self) -> bool {
+        matches!(self, WeiboError::Unsupported { .. })
+    }
+
+    /// 是否因达到请求数上限而未发出请求
+    pub fn is_request_limit(&self) -> bool {
+        matches!(self, WeiboError::RequestLimit { .. })
+    }
+
+    /// 失败原因的大类；HTTP 401、403 视为认证错误，其余 HTTP 状态码视为网络错误
+    pub fn category(&self) -> FailureCategory {
+        match self {
+            WeiboError::Network(_) => FailureCategory::Network,
+            WeiboError::Http { status, .. }
+                if *status == StatusCode::UNAUTHORIZED || *status == StatusCode::FORBIDDEN =>
+            {
+                FailureCategory::Auth
+            }
+            WeiboError::Http { .. } => FailureCategory::Network,
+            WeiboError::InvalidCookie
+            | WeiboError::NotLoggedIn { .. }
+            | WeiboError::TokenExpired { .. } => FailureCategory::Auth,
+            WeiboError::RateLimited { .. }
+            | WeiboError::NotFound { .. }
+            | WeiboError::Unsupported { .. }
+            | WeiboError::Api { .. } => FailureCategory::ApiRejected,
+            WeiboError::Parse { .. }
+            | WeiboError::Decode(_)
+            | WeiboError::Config(_)
+            | WeiboError::RequestLimit { .. } => FailureCategory::Unknown,
+        }
+    }
+}
+
+/// 长微博全文响应
+#[derive(Debug, Deserialize)]
+struct LongTextResponse {
+    pub ok: i32,
+    pub data: Option<LongTextData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LongTextData {
+    #[serde(rename = "longTextContent")]
+    pub long_text_content: Option<String>,
+}
+
+/// 当前登录的微博账号
+#[derive(Debug, Deserialize, Clone)]
+pub struct WeiboAccount {
+    #[serde(rename = "id", deserialize_with = "deserialize_number_to_string")]
+    pub uid: String,
+    pub screen_name: String,
+    /// 账号的微博总数（含转发），接口未返回时为 None
+    #[serde(default, deserialize_with = "deserialize_lenient_i64")]
+    pub statuses_count: Option<i64>,
+}
+
+/// 用户资料响应
+#[derive(Debug, Deserialize)]
+struct ProfileResponse {
+    pub ok: i32,
+    pub data: Option<ProfileData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfileData {
+    pub user: Option<WeiboAccount>,
+}
+
+/// 设置隐私、删除等写操作的响应
+#[derive(Debug, Deserialize)]
+struct PrivacyResponse {
+    pub ok: Option<i32>,
+    pub msg: Option<String>,
+    /// 错误码，不同接口字段名为 errno 或 error_code
+    #[serde(
+        default,
+        alias = "error_code",
+        deserialize_with = "deserialize_lenient_i64"
+    )]
+    pub errno: Option<i64>,
+    /// 批量请求时可能包含逐条结果
+    pub data: Option<serde_json::Value>,
+}
+
+/// 批量设置隐私的结果
+#[derive(Debug, Default)]
+pub struct BatchOutcome {
+    /// 设置成功的微博 ID
+    pub succeeded: Vec<String>,
+    /// 设置失败的微博 ID、失败原因的大类及错误信息
+    pub failed: Vec<(String, FailureCategory, String)>,
+    /// 不支持目标可见性的微博 ID
+    pub unsupported: Vec<String>,
+    /// 不存在（列出后已被删除）的微博 ID
+    pub missing: Vec<String>,
+    /// 因触发限流而未完成的微博 ID
+    pub rate_limited: Vec<String>,
+    /// 因达到请求数上限而未提交的微博 ID
+    pub not_sent: Vec<String>,
+}
+
+impl BatchOutcome {
+    /// 按结果的种类记入对应的列表，用于汇总 [`WeiboPrivacyClient::set_weibo_privacy_batch_with`] 的回调
+    pub fn record(&mut self, weibo_id: &str, result: Result<()>) {
+        let id = weibo_id.to_string();
+        match result {
+            Ok(()) => self.succeeded.push(id),
+            Err(e) if e.is_rate_limited() => self.rate_limited.push(id),
+            Err(e) if e.is_request_limit() => self.not_sent.push(id),
+            Err(e) if e.is_unsupported() => self.unsupported.push(id),
+            Err(e) if e.is_not_found() => self.missing.push(id),
+            Err(e) => self.failed.push((id, e.category(), e.to_string())),
+        }
+    }
+}
+
+/// 整批停止提交（限流或达到请求数上限）时，为每个未完成的 ID 回调一次同样的错误
+fn report_all(
+    weibo_ids: &[&str],
+    error: &WeiboError,
+    on_result: &mut impl FnMut(&str, Result<()>),
+) {
+    for id in weibo_ids {
+        let error = match error {
+            WeiboError::RateLimited { code, msg } => WeiboError::RateLimited {
+                code: *code,
+                msg: msg.clone(),
+            },
+            WeiboError::RequestLimit { limit } => WeiboError::RequestLimit { limit: *limit },
+            other => WeiboError::Api {
+                code: None,
+                msg: other.to_string(),
+            },
+        };
+        on_result(id, Err(error));
+    }
+}
+
+/// 从写操作响应的 data 字段中找出失败的 ID 及其说明（如有）
+///
+/// 单条与批量请求的响应形式可能不同，支持：
+/// - `{"fail_ids": [..]}` 这类失败列表，或 `{"succ_ids": [..]}` 这类成功列表；
+/// - `{"<id>": 1, ..}` 这类逐条状态表，值可以是数字、布尔、字符串或 `{"ok": 0, "msg": ..}`；
+/// - `[{"id": .., "ok": ..}, ..]` 这类逐条结果数组（ID 字段也可能为 `mid` 或 `idstr`）。
+///
+/// 无法识别时返回 None，由调用方按 ok 字段整体判断。
+fn failed_ids_from_data(
+    data: &serde_json::Value,
+    weibo_ids: &[&str],
+) -> Option<Vec<(String, Option<String>)>> {
+    if let Some(items) = data.as_array() {
+        return failed_ids_from_items(items, weibo_ids);
+    }
+    let obj = data.as_object()?;
+
+    for key in ["fail_ids", "failed_ids", "fail", "failed"] {
+        if let Some(list) = obj.get(key).and_then(|v| v.as_array()) {
+            return Some(
+                list.iter()
+                    .filter_map(value_to_id)
+                    .map(|id| (id, None))
+                    .collect(),
+            );
+        }
+    }
+
+    for key in ["succ_ids", "success_ids", "succeed_ids", "ok_ids"] {
+        if let Some(list) = obj.get(key).and_then(|v| v.as_array()) {
+            let succeeded: HashSet<String> = list.iter().filter_map(value_to_id).collect();
+            let failed = weibo_ids
+                .iter()
+                .filter(|id| !succeeded.contains(**id))
+                .map(|id| (id.to_string(), None))
+                .collect();
+            return Some(failed);
+        }
+    }
+
+    for key in ["results", "result", "list"] {
+        if let Some(items) = obj.get(key).and_then(|v| v.as_array()) {
+            if let Some(failed) = failed_ids_from_items(items, weibo_ids) {
+                return Some(failed);
+            }
+        }
+    }
+
+    if weibo_ids.iter().any(|id| obj.contains_key(*id)) {
+        let failed = weibo_ids
+            .iter()
+            .filter_map(|id| match obj.get(*id) {
+                Some(value) if is_success_value(value) => None,
+                value => Some((id.to_string(), value.and_then(item_message))),
+            })
+            .collect();
+        return Some(failed);
+    }
+
+    None
+}
+
+/// 逐条结果数组中失败的 ID；数组项中找不到 ID 时返回 None（无法识别）
+fn failed_ids_from_items(
+    items: &[serde_json::Value],
+    weibo_ids: &[&str],
+) -> Option<Vec<(String, Option<String>)>> {
+    let mut reported = HashMap::new();
+    for item in items {
+        let id = ["id", "mid", "idstr"]
+            .iter()
+            .find_map(|key| item.get(*key).and_then(value_to_id))?;
+        reported.insert(id, item);
+    }
+    if !weibo_ids.iter().any(|id| reported.contains_key(*id)) {
+        return None;
+    }
+    let failed = weibo_ids
+        .iter()
+        .filter_map(|id| match reported.get(*id) {
+            Some(item) if is_success_value(item) => None,
+            item => Some((id.to_string(), item.and_then(|i| item_message(i)))),
+        })
+        .collect();
+    Some(failed)
+}
+
+/// 逐条结果中的错误说明
+fn item_message(value: &serde_json::Value) -> Option<String> {
+    ["msg", "message", "error"]
+        .iter()
+        .find_map(|key| value.get(*key).and_then(|v| v.as_str()))
+        .filter(|msg| !msg.is_empty())
+        .map(str::to_string)
+}
+
+fn value_to_id(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// 逐条状态是否表示成功；对象形式时依次查看 `ok`、`result`、`success` 字段
+fn is_success_value(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Bool(b) => *b,
+        serde_json::Value::Number(n) => n.as_i64() == Some(1),
+        serde_json::Value::String(s) => s == "1" || s == "ok" || s == "true",
+        serde_json::Value::Object(o) => ["ok", "result", "success"]
+            .iter()
+            .find_map(|key| o.get(*key))
+            .is_some_and(is_success_value),
+        _ => false,
+    }
+}
+
+/// 截取字符串前 max_chars 个字符，用于日志中显示响应内容
+fn truncate(text: &str, max_chars: usize) -> &str {
+    match text.char_indices().nth(max_chars) {
+        Some((idx, _)) => &text[..idx],
+        None => text,
+    }
+}
+
+/// 同时等待多个 future，按输入顺序返回结果
+async fn join_all<F: Future>(futures: Vec<F>) -> Vec<F::Output> {
+    let mut futures: Vec<Pin<Box<F>>> = futures.into_iter().map(Box::pin).collect();
+    let mut outputs: Vec<Option<F::Output>> = futures.iter().map(|_| None).collect();
+    std::future::poll_fn(|cx| {
+        let mut pending = false;
+        for (future, output) in futures.iter_mut().zip(outputs.iter_mut()) {
+            if output.is_none() {
+                match future.as_mut().poll(cx) {
+                    Poll::Ready(value) => *output = Some(value),
+                    Poll::Pending => pending = true,
+                }
+            }
+        }
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    })
+    .await;
+    outputs.into_iter().flatten().collect()
+}
+
+/// 写操作的响应是否表示 XSRF-TOKEN 无效或已过期
+fn is_token_rejected(text: &str) -> bool {
+    serde_json::from_str::<PrivacyResponse>(text).is_ok_and(|resp| {
+        resp.ok.is_some_and(|ok| ok != 1)
+            && WeiboError::from_response(resp.errno, resp.msg.as_deref()).is_token_expired()
+    })
+}
+
+/// Cookie 字符串中名为 `name` 的全部条目的原始值，按出现顺序
+fn cookie_values<'a>(cookie: &'a str, name: &str) -> Vec<&'a str> {
+    cookie
+        .split(';')
+        .filter_map(|s| s.trim().strip_prefix(name)?.strip_prefix('='))
+        .map(str::trim)
+        .collect()
+}
+
+/// 把 Cookie 字符串中名为 `name` 的全部条目换成一个 `name=value`，没有该条目时追加在末尾
+fn replace_cookie_value(cookie: &str, name: &str, value: &str) -> String {
+    let entry = format!("{}={}", name, value);
+    let mut parts: Vec<String> = Vec::new();
+    let mut replaced = false;
+    for part in cookie.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+        let is_target = part
+            .strip_prefix(name)
+            .is_some_and(|rest| rest.starts_with('='));
+        if !is_target {
+            parts.push(part.to_string());
+        } else if !replaced {
+            parts.push(entry.clone());
+            replaced = true;
+        }
+    }
+    if !replaced {
+        parts.push(entry);
+    }
+    parts.join("; ")
+}
+
+/// URL 解码 `%XX` 形式的转义，不合法的转义原样保留
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+// 自定义反序列化：解析 visible 字段
+//
+// 接口返回 `{"type": 0, "list_id": 0}` 形式；本工具导出的 JSON 中为 "public" 等英文标识，两者都支持。
+fn deserialize_visible<'de, D>(deserializer: D) -> Result<Option<Visibility>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(match value {
+        Some(serde_json::Value::Object(obj)) => obj
+            .get("type")
+            .and_then(|t| t.as_i64())
+            .and_then(Visibility::from_visible_type),
+        Some(serde_json::Value::Number(n)) => n.as_i64().and_then(Visibility::from_visible_type),
+        Some(serde_json::Value::String(key)) => Visibility::from_key(&key),
+        _ => None,
+    })
+}
+
+/// 读取响应内容，检测未能解压的响应
+///
+/// reqwest 解压后会移除 `Content-Encoding` 头；若该头仍在且内容不像 JSON，
+/// 说明响应使用了当前构建不支持的压缩方式，返回明确的错误而不是让后续的 JSON 解析报错。
+///
+/// Cookie 过期时接口会跳转到 HTML 登录页，内容以 `<` 开头，此时返回 [`WeiboError::NotLoggedIn`]，
+/// 避免报出令人困惑的 JSON 解析错误，也避免写操作把登录页当作"成功但无法解析"的响应。
+async fn read_body(response: Response) -> Result<String> {
+    let encoding = response
+        .headers()
+        .get(reqwest::header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let bytes = response.bytes().await?;
+
+    let first = bytes.iter().find(|b| !b.is_ascii_whitespace());
+    let looks_like_json = first.is_some_and(|b| *b == b'{' || *b == b'[');
+    match encoding {
+        Some(encoding) if !looks_like_json && !encoding.eq_ignore_ascii_case("identity") => {
+            Err(WeiboError::Decode(encoding))
+        }
+        _ if first == Some(&b'<') => {
+            debug!(
+                "响应为 HTML: {}",
+                truncate(&String::from_utf8_lossy(&bytes), 200)
+            );
+            Err(WeiboError::login_wall())
+        }
+        _ => Ok(String::from_utf8_lossy(&bytes).into_owned()),
+    }
+}
+
+/// 被转发的原微博：字段存在且不为 null 即为转发，内容不是对象时视为不带媒体
+fn deserialize_retweeted<'de, D>(deserializer: D) -> Result<Option<RawMedia>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.map(|value| serde_json::from_value(value).unwrap_or_default()))
+}
+
+// 自定义反序列化：标志字段可能是 0/1、布尔值或字符串，缺失或 null 视为 false
+fn deserialize_flag<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(match value {
+        Some(serde_json::Value::Bool(b)) => b,
+        Some(serde_json::Value::Number(n)) => n.as_i64().is_some_and(|n| n != 0),
+        Some(serde_json::Value::String(s)) => matches!(s.trim(), "1" | "true"),
+        _ => false,
+    })
+}
+
+// 自定义反序列化：错误码、计数等整数字段可能是数字或数字字符串
+fn deserialize_lenient_i64<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(match value {
+        Some(serde_json::Value::Number(n)) => n.as_i64(),
+        Some(serde_json::Value::String(s)) => s.trim().parse().ok(),
+        _ => None,
+    })
+}
+
+// 自定义反序列化：互动计数可能是数字、数字字符串，或 "1万+"、"100万+" 这样的概数（取下限）
+fn deserialize_count<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(match value {
+        Some(serde_json::Value::Number(n)) => n.as_u64(),
+        Some(serde_json::Value::String(s)) => parse_count(&s),
+        _ => None,
+    })
+}
+
+/// 解析计数字符串，支持 "1234"、"1.2万"、"100万+"、"1亿+"
+fn parse_count(s: &str) -> Option<u64> {
+    let s = s.trim().trim_end_matches('+');
+    let (number, unit) = if let Some(n) = s.strip_suffix('万') {
+        (n, 10_000.0)
+    } else if let Some(n) = s.strip_suffix('亿') {
+        (n, 100_000_000.0)
+    } else {
+        return s.parse().ok();
+    };
+    let number: f64 = number.trim().parse().ok()?;
+    (number >= 0.0).then_some((number * unit) as u64)
+}
+
+// 自定义反序列化：分页游标可能是字符串或数字，空字符串与 0 视为没有游标
+fn deserialize_cursor<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value
+        .as_ref()
+        .and_then(value_to_id)
+        .filter(|cursor| !cursor.is_empty() && cursor != "0"))
+}
+
+// 自定义反序列化：将数字转换为字符串
+fn deserialize_number_to_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::{self, Visitor};
+
+    struct StringOrNumber;
+
+    impl<'de> Visitor<'de> for StringOrNumber {
+        type Value = String;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a string or number")
+        }
+
+        fn visit_i64<E>(self, value: i64) -> Result<String, E>
+        where
+            E: de::Error,
+        {
+            Ok(value.to_string())
+        }
+
+        fn visit_u64<E>(self, value: u64) -> Result<String, E>
+        where
+            E: de::Error,
+        {
+            Ok(value.to_string())
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<String, E>
+        where
+            E: de::Error,
+        {
+            Ok(value.to_string())
+        }
+    }
+
+    deserializer.deserialize_any(StringOrNumber)
+}
+
+pub struct WeiboPrivacyClient {
+    client: Client,
+    /// 网页版接口的地址，不含末尾的 `/`
+    base_url: String,
+    /// 所有请求使用的 User-Agent
+    user_agent: String,
+    /// 用户指定的附加请求头，替换同名的内置请求头
+    extra_headers: HeaderMap,
+    /// 请求使用的 Cookie 与 XSRF-TOKEN，重新获取 token 后更新
+    credentials: Mutex<Credentials>,
+    /// 收集响应中的 Set-Cookie，重新获取 XSRF-TOKEN 时从中读取
+    cookie_jar: Arc<Jar>,
+    /// 同一时间只进行一次 XSRF-TOKEN 的重新获取
+    refresh_lock: tokio::sync::Mutex<()>,
+    /// 每个请求的最大尝试次数（含首次请求）
+    max_retries: u32,
+    /// 指数退避的底数（秒），第 n 次重试前等待 backoff_base^n 秒
+    backoff_base: u64,
+    /// 已获取的长微博全文，按微博 ID 缓存
+    long_text_cache: Mutex<HashMap<String, String>>,
+    /// 每次 HTTP 请求（含重试）的耗时
+    latency: LatencyStats,
+    /// 已发起的 HTTP 请求数（含重试）
+    request_count: AtomicU64,
+    /// 请求数上限，None 表示不限
+    max_requests: Option<u64>,
+    /// 是否有请求因达到上限而未发出
+    request_limit_hit: AtomicBool,
+    /// 所有请求共用的速率限制，None 表示不限
+    rate_limiter: Option<RateLimiter>,
+}
+
+/// 每个请求带上的 Cookie 与 XSRF-TOKEN
+#[derive(Debug, Clone)]
+struct Credentials {
+    /// 用户提供的完整 Cookie，原样放在每个请求的 `Cookie` 头中；重新获取 token 后只替换其中的 XSRF-TOKEN
+    cookie: String,
+    xsrf_token: String,
+}
+
+/// 列表接口 `feature` 参数的已知取值：全部微博
+pub const FEATURE_ALL: u32 = 0;
+/// 原创微博（不含转发）
+pub const FEATURE_ORIGINAL: u32 = 1;
+/// 带图片的微博
+pub const FEATURE_PHOTO: u32 = 2;
+/// 带视频的微博
+pub const FEATURE_VIDEO: u32 = 3;
+/// 带音乐的微博
+pub const FEATURE_MUSIC: u32 = 4;
+
+/// 网页版列表接口 `count` 参数（每页条数）接受的最大值，更大的值按此请求
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// 翻页时累计扫描的微博数上限（含重复），超过后视为接口异常、停止翻页
+const MAX_SCANNED_WEIBOS: usize = 200_000;
+/// 连续多少页全部是已获取过的微博时停止翻页
+const MAX_STALE_PAGES: u32 = 3;
+
+/// 默认翻页间隔（秒）
+pub const DEFAULT_PAGE_DELAY_SECS: f64 = 1.0;
+
+/// 获取微博列表的选项
+#[derive(Debug, Clone)]
+pub struct FetchOptions {
+    /// 最大获取页数，None 表示获取全部
+    pub max_pages: Option<u32>,
+    /// 是否为被截断的长微博额外请求全文
+    pub expand_long_text: bool,
+    /// 获取列表使用的接口
+    pub api: Api,
+    /// 每页请求之间的等待时间，为零时不等待
+    pub page_delay: Duration,
+    /// 最多获取的微博条数（去重后），达到后不再请求下一页；None 表示不限制
+    pub max_weibos: Option<usize>,
+    /// 网页版列表接口的 `feature` 参数，按内容类型筛选（见 [`FEATURE_ALL`] 等常量）
+    pub feature: u32,
+    /// 只获取该超话中本人发布的微博（超话 ID），此时不使用 `api` 与 `feature`
+    pub topic: Option<String>,
+    /// 按页码翻页时同时请求的页数，1 表示逐页请求
+    pub parallel_pages: u32,
+    /// 只获取 ID 大于该值的微博：列表按发布时间从新到旧排列，
+    /// 遇到 ID 不大于该值的非置顶微博后不再请求下一页；None 表示不限制
+    pub after_id: Option<u64>,
+    /// 网页版列表接口每页的微博条数（`count` 参数），超过 [`MAX_PAGE_SIZE`] 时按上限请求；
+    /// None 表示不传，使用接口默认值（约 20 条）
+    pub page_size: Option<u32>,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            max_pages: None,
+            expand_long_text: false,
+            api: Api::default(),
+            page_delay: Duration::from_secs_f64(DEFAULT_PAGE_DELAY_SECS),
+            max_weibos: None,
+            feature: FEATURE_ALL,
+            topic: None,
+            parallel_pages: 1,
+            after_id: None,
+            page_size: None,
+        }
+    }
+}
+
+/// 默认的网页版接口地址
+pub const DEFAULT_BASE_URL: &str = "https://weibo.com";
+
+/// 默认 User-Agent（Windows 上的 Chrome）
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+/// 内置的常见浏览器 User-Agent，按名称选用
+pub const USER_AGENT_PRESETS: [(&str, &str); 4] = [
+    ("chrome", DEFAULT_USER_AGENT),
+    (
+        "edge",
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.0.0",
+    ),
+    (
+        "firefox",
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0",
+    ),
+    (
+        "safari",
+        "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.2 Safari/605.1.15",
+    ),
+];
+
+/// 按名称（不区分大小写）查找内置的 User-Agent
+pub fn user_agent_preset(name: &str) -> Option<&'static str> {
+    USER_AGENT_PRESETS
+        .iter()
+        .find(|(preset, _)| preset.eq_ignore_ascii_case(name))
+        .map(|(_, user_agent)| *user_agent)
+}
+/// 默认请求超时（秒）
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+/// 默认最大尝试次数
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+/// 默认退避底数（秒）
+pub const DEFAULT_BACKOFF_BASE: u64 = 2;
+/// 单次退避等待的上限（秒），避免重试次数较大时溢出或等待过久
+const MAX_BACKOFF_SECS: u64 = 64;
+/// 退避时间的随机系数范围
+const BACKOFF_JITTER: std::ops::Range<f64> = 0.5..1.5;
+
+/// HTTP 连接参数
+#[derive(Debug, Clone)]
+pub struct HttpOptions {
+    /// 单个请求的总超时（含读取响应）
+    pub timeout: Duration,
+    /// 建立连接的超时，None 时只受总超时限制
+    pub connect_timeout: Option<Duration>,
+    /// 代理地址（http://、https://、socks5:// 或 socks5h://），
+    /// None 时沿用 reqwest 默认行为读取 HTTPS_PROXY 等环境变量
+    pub proxy: Option<String>,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            connect_timeout: None,
+            proxy: None,
+        }
+    }
+}
+
+/// 网页版接口需要的登录 Cookie：缺少 SUB 时未登录，缺少 WBPSESS 时部分写操作会间歇返回 ok=0
+pub const REQUIRED_COOKIES: [&str; 2] = ["SUB", "WBPSESS"];
+
+/// Cookie 中缺少的 [`REQUIRED_COOKIES`]
+pub fn missing_cookies(cookie: &str) -> Vec<&'static str> {
+    let names: HashSet<&str> = cookie
+        .split(';')
+        .filter_map(|pair| pair.split_once('=').map(|(name, _)| name.trim()))
+        .collect();
+    REQUIRED_COOKIES
+        .iter()
+        .copied()
+        .filter(|name| !names.contains(name))
+        .collect()
+}
+
+/// 解析 `名称: 值` 形式的自定义请求头，检查名称与值是否合法
+pub fn parse_header_line(line: &str) -> Result<(String, String)> {
+    let invalid = |reason: &str| WeiboError::Config(format!("请求头无效: {}（{}）", line, reason));
+    let (name, value) = line
+        .split_once(':')
+        .ok_or_else(|| invalid("应为 \"名称: 值\" 的形式"))?;
+    let (name, value) = (name.trim(), value.trim());
+    HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid("名称不合法"))?;
+    HeaderValue::from_str(value).map_err(|_| invalid("值不合法"))?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// 支持的代理协议
+const PROXY_SCHEMES: &[&str] = &["http", "https", "socks5", "socks5h"];
+
+/// 检查代理地址的格式与协议，返回规范化后的地址
+pub fn validate_proxy(proxy: &str) -> Result<String> {
+    let url = reqwest::Url::parse(proxy)
+        .map_err(|e| WeiboError::Config(format!("代理地址无效: {}（{}）", proxy, e)))?;
+    if !PROXY_SCHEMES.contains(&url.scheme()) {
+        return Err(WeiboError::Config(format!(
+            "不支持的代理协议: {}，可选: {}",
+            url.scheme(),
+            PROXY_SCHEMES.join(", ")
+        )));
+    }
+    if url.host_str().is_none() {
+        return Err(WeiboError::Config(format!("代理地址缺少主机名: {}", proxy)));
+    }
+    Ok(url.to_string())
+}
+
+/// 检查接口地址的格式，返回去掉末尾 `/` 的地址
+///
+/// 只支持 http:// 与 https://；地址可以带路径前缀（如 `https://mirror.example.com/weibo`），
+/// 但不能带查询参数。
+pub fn validate_base_url(base_url: &str) -> Result<String> {
+    let url = reqwest::Url::parse(base_url)
+        .map_err(|e| WeiboError::Config(format!("接口地址无效: {}（{}）", base_url, e)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(WeiboError::Config(format!(
+            "接口地址只支持 http:// 与 https://: {}",
+            base_url
+        )));
+    }
+    if url.host_str().is_none() {
+        return Err(WeiboError::Config(format!(
+            "接口地址缺少主机名: {}",
+            base_url
+        )));
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(WeiboError::Config(format!(
+            "接口地址不能带查询参数: {}",
+            base_url
+        )));
+    }
+    Ok(url.as_str().trim_end_matches('/').to_string())
+}
+
+/// [`WeiboPrivacyClient`] 的构建器，未设置的选项使用默认值
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use weibo_hide::WeiboPrivacyClient;
+///
+/// # fn example() -> Result<(), weibo_hide::WeiboError> {
+/// let client = WeiboPrivacyClient::builder("SUB=...; XSRF-TOKEN=...")
+///     .timeout(Duration::from_secs(10))
+///     .max_retries(5)
+///     .proxy("socks5h://127.0.0.1:1080")
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct WeiboPrivacyClientBuilder {
+    cookie: String,
+    base_url: String,
+    http: HttpOptions,
+    user_agent: String,
+    max_retries: u32,
+    backoff_base: u64,
+    max_requests: Option<u64>,
+    rate_limit: Option<u32>,
+    headers: Vec<(String, String)>,
+}
+
+impl WeiboPrivacyClientBuilder {
+    pub fn new(cookie: impl Into<String>) -> Self {
+        Self {
+            cookie: cookie.into(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            http: HttpOptions::default(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            max_retries: DEFAULT_MAX_RETRIES,
+            backoff_base: DEFAULT_BACKOFF_BASE,
+            max_requests: None,
+            rate_limit: None,
+            headers: Vec::new(),
+        }
+    }
+
+    /// 网页版接口的地址（默认 [`DEFAULT_BASE_URL`]），用于镜像或测试用的模拟服务，在 `build` 时校验
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// 一次设置全部连接参数
+    pub fn http_options(mut self, http: HttpOptions) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// 单个请求的总超时（含读取响应）
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.http.timeout = timeout;
+        self
+    }
+
+    /// 建立连接的超时
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.http.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// 代理地址（http://、https://、socks5:// 或 socks5h://），在 `build` 时校验
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.http.proxy = Some(proxy.into());
+        self
+    }
+
+    /// 请求使用的 User-Agent（默认 [`DEFAULT_USER_AGENT`]），内置的常见值见 [`USER_AGENT_PRESETS`]
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// 每个请求的最大尝试次数（含首次请求，至少为 1）
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries.max(1);
+        self
+    }
+
+    /// 重试退避的底数（秒），第 n 次重试前等待约 底数^n 秒
+    pub fn backoff_base(mut self, backoff_base: u64) -> Self {
+        self.backoff_base = backoff_base;
+        self
+    }
+
+    /// 附加到每个请求的请求头，与内置请求头同名时替换内置值；可多次调用，在 `build` 时校验
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// HTTP 请求总数（列表、全文与写操作，含重试）的上限，达到后的请求返回
+    /// [`WeiboError::RequestLimit`]
+    pub fn max_requests(mut self, max_requests: u64) -> Self {
+        self.max_requests = Some(max_requests);
+        self
+    }
+
+    /// 每分钟最多发起的 HTTP 请求数（列表、全文与写操作，含重试），同一客户端的所有并发请求共同遵守
+    pub fn rate_limit(mut self, requests_per_minute: u32) -> Self {
+        self.rate_limit = Some(requests_per_minute);
+        self
+    }
+
+    pub fn build(self) -> Result<WeiboPrivacyClient> {
+        let xsrf_token = WeiboPrivacyClient::extract_xsrf_token(&self.cookie)
+            .ok_or(WeiboError::InvalidCookie)?;
+        let mut tokens = cookie_values(&self.cookie, "XSRF-TOKEN");
+        tokens.retain(|t| !t.is_empty());
+        tokens.dedup();
+        if tokens.len() > 1 {
+            warn!("Cookie 中有多个不同的 XSRF-TOKEN，使用最后一个；设置时若被拒绝会自动重新获取");
+        }
+        let base_url = validate_base_url(&self.base_url)?;
+        let mut extra_headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let invalid = || WeiboError::Config(format!("请求头无效: {}: {}", name, value));
+            let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| invalid())?;
+            let value = HeaderValue::from_str(value.trim()).map_err(|_| invalid())?;
+            extra_headers.append(name, value);
+        }
+
+        let http = &self.http;
+        let cookie_jar = Arc::new(Jar::default());
+        let mut builder = Client::builder()
+            .timeout(http.timeout)
+            // 每个请求都显式带上完整的 Cookie 头，此时 reqwest 不会用 cookie store 中的值替换或合并，
+            // 用户提供的 Cookie（含 SUB、WBPSESS 等）原样发送；store 只收集响应中的 Set-Cookie
+            .cookie_provider(Arc::clone(&cookie_jar))
+            .user_agent(&self.user_agent);
+        if let Some(connect_timeout) = http.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(ref proxy) = http.proxy {
+            let proxy = reqwest::Proxy::all(validate_proxy(proxy)?)
+                .map_err(|e| WeiboError::Config(format!("代理地址无效: {}（{}）", proxy, e)))?;
+            builder = builder.proxy(proxy);
+        }
+        let client = builder.build()?;
+
+        Ok(WeiboPrivacyClient {
+            client,
+            base_url,
+            user_agent: self.user_agent,
+            extra_headers,
+            credentials: Mutex::new(Credentials {
+                cookie: self.cookie,
+                xsrf_token,
+            }),
+            cookie_jar,
+            refresh_lock: tokio::sync::Mutex::new(()),
+            max_retries: self.max_retries,
+            backoff_base: self.backoff_base,
+            long_text_cache: Mutex::new(HashMap::new()),
+            latency: LatencyStats::default(),
+            request_count: AtomicU64::new(0),
+            max_requests: self.max_requests,
+            request_limit_hit: AtomicBool::new(false),
+            rate_limiter: self.rate_limit.map(RateLimiter::per_minute),
+        })
+    }
+}
+
+impl WeiboPrivacyClient {
+    /// 按指定的连接参数创建新客户端，其余选项使用默认值
+    pub fn new(cookie: String, http: &HttpOptions) -> Result<Self> {
+        Self::builder(cookie).http_options(http.clone()).build()
+    }
+
+    /// 通过构建器创建客户端
+    pub fn builder(cookie: impl Into<String>) -> WeiboPrivacyClientBuilder {
+        WeiboPrivacyClientBuilder::new(cookie)
+    }
+
+    /// 网页版接口的地址
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// 请求使用的 User-Agent
+    pub fn user_agent(&self) -> &str {
+        &self.user_agent
+    }
+
+    /// 网页版接口的完整地址，`path` 以 `/` 开头
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// 目前为止所有请求的耗时统计
+    pub fn latency(&self) -> &LatencyStats {
+        &self.latency
+    }
+
+    /// 当前使用的 Cookie；运行中重新获取过 XSRF-TOKEN 时，其中的 XSRF-TOKEN 已替换为新值
+    pub fn cookie(&self) -> String {
+        self.credentials().cookie
+    }
+
+    /// 目前为止发起的 HTTP 请求数（含重试）
+    pub fn request_count(&self) -> u64 {
+        self.request_count.load(Ordering::Relaxed)
+    }
+
+    /// 是否有请求因达到请求数上限而未发出，即运行被上限中止
+    pub fn request_limit_hit(&self) -> bool {
+        self.request_limit_hit.load(Ordering::Relaxed)
+    }
+
+    /// 发起请求前计数并按速率限制等待，已达到请求数上限时不再发起
+    async fn before_request(&self) -> Result<()> {
+        self.count_request()?;
+        if let Some(ref limiter) = self.rate_limiter {
+            limiter.acquire().await;
+        }
+        Ok(())
+    }
+
+    /// 发起请求前计数，已达到上限时不再发起
+    fn count_request(&self) -> Result<()> {
+        let Some(limit) = self.max_requests else {
+            self.request_count.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
+        };
+        let counted = self
+            .request_count
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+                (n < limit).then_some(n + 1)
+            });
+        match counted {
+            Ok(n) if n + 1 == limit => {
+                warn!(
+                    "已发起 {} 次请求，达到请求数上限，之后不再发起新的请求",
+                    limit
+                );
+                Ok(())
+            }
+            Ok(_) => Ok(()),
+            Err(_) => {
+                self.request_limit_hit.store(true, Ordering::Relaxed);
+                Err(WeiboError::RequestLimit { limit })
+            }
+        }
+    }
+
+    /// 设置重试策略：最大尝试次数（至少为 1）与退避底数
+    pub fn with_retry_policy(mut self, max_retries: u32, backoff_base: u64) -> Self {
+        self.max_retries = max_retries.max(1);
+        self.backoff_base = backoff_base;
+        self
+    }
+
+    /// 第 retry 次失败后的退避时间，不超过 MAX_BACKOFF_SECS
+    ///
+    /// 在 底数^retry 秒的基础上乘以 [0.5, 1.5) 内的随机系数，避免并发任务在同一时刻集中重试。
+    fn backoff_delay(&self, retry: u32) -> Duration {
+        let base = Duration::from_secs(self.backoff_base.saturating_pow(retry));
+        let jitter = rand::rng().random_range(BACKOFF_JITTER);
+        base.mul_f64(jitter)
+            .min(Duration::from_secs(MAX_BACKOFF_SECS))
+    }
+
+    /// 从 Cookie 中提取 XSRF-TOKEN
+    ///
+    /// 只按第一个 `=` 分隔名称与值（token 本身可能以 base64 的 `==` 结尾），
+    /// 浏览器或插件导出时若做了 URL 编码（如 `%3D`）则先解码。
+    /// 有多个 XSRF-TOKEN 时（如 `weibo.com` 与 `.weibo.com` 各有一个）使用最后一个非空的值，
+    /// 同名 Cookie 按设置的先后排列，最后一个是最新的。
+    /// 找不到或为空时返回 None，此时 [`WeiboPrivacyClientBuilder::build`] 返回 [`WeiboError::InvalidCookie`]。
+    pub fn extract_xsrf_token(cookie: &str) -> Option<String> {
+        cookie_values(cookie, "XSRF-TOKEN")
+            .into_iter()
+            .rev()
+            .map(|value| percent_decode(value.trim_matches('"')))
+            .find(|token| !token.is_empty())
+    }
+
+    /// 当前使用的 Cookie 与 XSRF-TOKEN
+    fn credentials(&self) -> Credentials {
+        self.credentials.lock().unwrap().clone()
+    }
+
+    /// 重新获取 XSRF-TOKEN：请求一次首页，从响应设置的 Cookie 中读取新的 token
+    ///
+    /// `stale` 为被拒绝的请求所用的 token。多个并发请求同时被拒绝时只有第一个真正重新获取，
+    /// 其余发现 token 已经更新后直接返回。新 token 同时替换 Cookie 头中的 XSRF-TOKEN。
+    async fn refresh_xsrf_token(&self, stale: &str) -> Result<()> {
+        let _guard = self.refresh_lock.lock().await;
+        if self.credentials().xsrf_token != stale {
+            return Ok(());
+        }
+
+        let url = format!("{}/", self.base_url);
+        // 首页通常是 HTML，只需要响应中的 Set-Cookie，由 cookie store 收集
+        self.get_with_retry(&url, &self.base_url).await?;
+
+        let stored = Url::parse(&url)
+            .ok()
+            .and_then(|url| self.cookie_jar.cookies(&url))
+            .and_then(|value| value.to_str().map(str::to_string).ok())
+            .unwrap_or_default();
+        let refreshed = cookie_values(&stored, "XSRF-TOKEN")
+            .into_iter()
+            .rev()
+            .find_map(|raw| {
+                let token = percent_decode(raw.trim_matches('"'));
+                (!token.is_empty() && token != stale).then(|| (raw.to_string(), token))
+            });
+        let Some((raw, token)) = refreshed else {
+            return Err(WeiboError::TokenExpired {
+                code: None,
+                msg: "无法重新获取 XSRF-TOKEN，请重新登录微博并复制 Cookie".to_string(),
+            });
+        };
+
+        let mut credentials = self.credentials.lock().unwrap();
+        credentials.cookie = replace_cookie_value(&credentials.cookie, "XSRF-TOKEN", &raw);
+        credentials.xsrf_token = token;
+        info!("✓ 已重新获取 XSRF-TOKEN");
+        Ok(())
+    }
+
+    /// 校验 Cookie 是否有效，返回当前登录的账号信息
+    pub async fn validate_cookie(&self) -> Result<WeiboAccount> {
+        let url = self.endpoint("/ajax/profile/info");
+        let response = self.get_with_retry(&url, &self.base_url).await?;
+        let response_text = read_body(response).await?;
+
+        let not_logged_in = |msg: String| WeiboError::NotLoggedIn { code: None, msg };
+        let profile: ProfileResponse = serde_json::from_str(&response_text).map_err(|_| {
+            not_logged_in("Cookie 无效或已过期，请重新登录微博并复制 Cookie".to_string())
+        })?;
+
+        match (profile.ok, profile.data.and_then(|d| d.user)) {
+            (1, Some(user)) => Ok(user),
+            _ => Err(not_logged_in(format!(
+                "Cookie 已过期或未登录（ok={}），请重新登录微博并复制 Cookie",
+                profile.ok
+            ))),
+        }
+    }
+
+    /// 获取用户所有微博 ID 列表
+    ///
+    /// 一次取回全部页面，用法与 [`Self::for_each_weibo_page`] 相同。
+    pub async fn get_all_weibo_ids(
+        &self,
+        user_id: &str,
+        options: &FetchOptions,
+    ) -> Result<Vec<WeiboInfo>> {
+        let mut all_weibos = Vec::new();
+        self.for_each_weibo_page(user_id, options, |weibos| {
+            all_weibos.extend(weibos);
+            async { Ok::<_, WeiboError>(ControlFlow::Continue(())) }
+        })
+        .await?;
+        Ok(all_weibos)
+    }
+
+    /// 逐页获取用户的微博，每获取一页调用一次 `on_page`
+    ///
+    /// 回调完成后才会请求下一页，调用方可借此边获取边处理；
+    /// 回调返回 `ControlFlow::Break` 时停止获取。
+    /// 按 `options.api` 选择网页版或移动版接口。
+    /// 优先使用响应中的 `since_id` 游标翻页，响应不含游标时退回按页码翻页。
+    /// 两种方式可能返回重叠的微博，按 ID 去重，传给回调的页面不含重复项。
+    /// 为防止接口异常时无限翻页，本页与上一页完全相同、连续 3 页没有新微博，
+    /// 或累计扫描超过 20 万条时，记录警告并停止。
+    ///
+    /// `options.parallel_pages` 大于 1 且第 1 页不含游标（按页码翻页）时，之后每次同时请求多页，
+    /// 仍按页码顺序逐页调用回调，每组请求之间等待 `page_delay`；按游标翻页与超话始终逐页请求。
+    ///
+    /// 回调的错误类型 `E` 由调用方决定，只需能由 [`WeiboError`] 转换而来。
+    pub async fn for_each_weibo_page<F, Fut, E>(
+        &self,
+        user_id: &str,
+        options: &FetchOptions,
+        mut on_page: F,
+    ) -> Result<(), E>
+    where
+        F: FnMut(Vec<WeiboInfo>) -> Fut,
+        Fut: Future<Output = Result<ControlFlow<()>, E>>,
+        E: From<WeiboError>,
+    {
+        let mut seen_ids = HashSet::new();
+        let mut duplicates = 0;
+        let mut page = 1;
+        let mut since_id: Option<String> = None;
+        let mut cursor_seen = false;
+        let max_pages = options.max_pages.unwrap_or(u32::MAX);
+        let max_weibos = options.max_weibos.unwrap_or(usize::MAX);
+        let mut collected = 0;
+        let mut scanned = 0;
+        let mut malformed = 0;
+        let mut previous_ids: Vec<String> = Vec::new();
+        let mut stale_pages = 0;
+        // 同时请求的后续页面，按页码顺序排列
+        let mut prefetched: VecDeque<Result<ListPage>> = VecDeque::new();
+
+        loop {
+            if page > max_pages {
+                break;
+            }
+
+            if prefetched.is_empty() {
+                let parallel = options.topic.is_none() && page > 1 && !cursor_seen;
+                let count = if parallel {
+                    options.parallel_pages.max(1).min(max_pages - page + 1)
+                } else {
+                    1
+                };
+                let pages = (page..page + count)
+                    .map(|p| self.fetch_list_page(user_id, options, p, since_id.as_deref()));
+                prefetched.extend(join_all(pages.collect()).await);
+            }
+            let list_page = prefetched.pop_front().expect("至少请求了一页")?;
+            let next_cursor = list_page.since_id;
+            let mut weibos = list_page.weibos;
+            for weibo in &mut weibos {
+                weibo.user_id = Some(user_id.to_string());
+            }
+
+            if list_page.scanned == 0 {
+                break;
+            }
+
+            if options.topic.is_some() {
+                info!(
+                    "✓ 第 {} 页: 超话中 {} 条微博，本人发布 {} 条",
+                    page,
+                    list_page.scanned,
+                    weibos.len()
+                );
+            } else {
+                info!("✓ 第 {} 页: 获取 {} 条微博", page, weibos.len());
+            }
+
+            let mut page_ids: Vec<String> = weibos.iter().map(|w| w.id.clone()).collect();
+            page_ids.sort_unstable();
+            if !page_ids.is_empty() && page_ids == previous_ids {
+                warn!("第 {} 页与上一页完全相同，接口可能出现异常，停止翻页", page);
+                break;
+            }
+
+            let before = weibos.len();
+            weibos.retain(|weibo| seen_ids.insert(weibo.id.clone()));
+            duplicates += before - weibos.len();
+
+            if before > 0 && weibos.is_empty() {
+                stale_pages += 1;
+                if stale_pages >= MAX_STALE_PAGES {
+                    warn!(
+                        "连续 {} 页都是已获取过的微博，接口可能出现异常，停止翻页",
+                        stale_pages
+                    );
+                    break;
+                }
+            } else {
+                stale_pages = 0;
+            }
+            previous_ids = page_ids;
+
+            scanned += list_page.scanned;
+            malformed += list_page.malformed;
+            let over_ceiling = scanned > MAX_SCANNED_WEIBOS;
+
+            // 置顶微博不按时间排列，只去掉而不作为停止翻页的依据；ID 不是数字的微博保留
+            let mut reached_after_id = false;
+            if let Some(after_id) = options.after_id {
+                weibos.retain(|weibo| match weibo.id.parse::<u64>() {
+                    Ok(id) if id <= after_id => {
+                        reached_after_id |= !weibo.is_top;
+                        false
+                    }
+                    _ => true,
+                });
+                if reached_after_id {
+                    info!("第 {} 页已到达 ID {}，不再获取更早的微博", page, after_id);
+                }
+            }
+
+            weibos.truncate(max_weibos - collected);
+            collected += weibos.len();
+
+            // 去重与截断之后再展开，不为用不到的微博额外请求全文
+            if options.expand_long_text {
+                self.expand_long_texts(&mut weibos).await;
+            }
+
+            if on_page(weibos).await?.is_break() || collected >= max_weibos || reached_after_id {
+                break;
+            }
+            if over_ceiling {
+                warn!(
+                    "已扫描 {} 条微博，超过上限 {}，接口可能出现异常，停止翻页",
+                    scanned, MAX_SCANNED_WEIBOS
+                );
+                break;
+            }
+
+            match next_cursor {
+                Some(cursor) => {
+                    cursor_seen = true;
+                    since_id = Some(cursor);
+                }
+                // 之前有游标、本页没有，说明已到最后一页
+                None if cursor_seen => break,
+                None => {}
+            }
+
+            page += 1;
+
+            // 避免请求过快；同时请求的一组页面之间不等待
+            if prefetched.is_empty() && !options.page_delay.is_zero() {
+                tokio::time::sleep(options.page_delay).await;
+            }
+        }
+
+        if duplicates > 0 {
+            info!("已过滤 {} 条跨页重复的微博", duplicates);
+        }
+        if malformed > 0 {
+            warn!("共有 {} 条微博结构异常、无法解析，已跳过", malformed);
+        }
+        if let Some(ref topic) = options.topic {
+            if collected == 0 {
+                warn!("超话 {} 中没有找到用户 {} 发布的微博", topic, user_id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 请求并解析列表的一页
+    async fn fetch_list_page(
+        &self,
+        user_id: &str,
+        options: &FetchOptions,
+        page: u32,
+        since_id: Option<&str>,
+    ) -> Result<ListPage> {
+        let api = options.api;
+        let (url, referer) = match options.topic {
+            Some(ref topic) => (
+                topic_url(topic, page, since_id),
+                format!("{}/p/{}", MOBILE_BASE_URL, topic_container_id(topic)),
+            ),
+            None => (
+                api.list_url(&self.base_url, user_id, page, options, since_id),
+                api.referer(&self.base_url, user_id),
+            ),
+        };
+        let response = self.get_with_retry(&url, &referer).await?;
+        let response_text = read_body(response).await?;
+
+        match options.topic {
+            Some(ref topic) => parse_topic_page(&response_text, page, topic, user_id),
+            None => api.parse_page(&response_text, page),
+        }
+    }
+
+    /// 将被截断的长微博替换为全文，获取失败时保留原内容
+    async fn expand_long_texts(&self, weibos: &mut [WeiboInfo]) {
+        for weibo in weibos.iter_mut().filter(|w| w.is_long_text) {
+            match self.get_long_text(&weibo.id).await {
+                Ok(text) => weibo.text = Some(text),
+                Err(e) => warn!("获取微博 {} 全文失败，保留截断内容: {}", weibo.id, e),
+            }
+        }
+    }
+
+    /// 获取长微博全文，同一 ID 只请求一次
+    pub async fn get_long_text(&self, weibo_id: &str) -> Result<String> {
+        if let Some(text) = self.long_text_cache.lock().unwrap().get(weibo_id) {
+            return Ok(text.clone());
+        }
+
+        let url = self.endpoint(&format!("/ajax/statuses/longText?id={}", weibo_id));
+        let response = self.get_with_retry(&url, &self.base_url).await?;
+        let response_text = read_body(response).await?;
+
+        let long_text: LongTextResponse = serde_json::from_str(&response_text).map_err(
+            WeiboError::parse(format!("无法解析微博 {} 的全文响应", weibo_id)),
+        )?;
+
+        let text = match (
+            long_text.ok,
+            long_text.data.and_then(|d| d.long_text_content),
+        ) {
+            (1, Some(text)) => text,
+            _ => return Err(WeiboError::api(format!("ok={}", long_text.ok))),
+        };
+
+        self.long_text_cache
+            .lock()
+            .unwrap()
+            .insert(weibo_id.to_string(), text.clone());
+        Ok(text)
+    }
+
+    /// 获取单条微博的详情，用于设置后重新读取可见性确认是否生效
+    pub async fn get_weibo(&self, weibo_id: &str) -> Result<WeiboInfo> {
+        let url = self.endpoint(&format!("/ajax/statuses/show?id={}", weibo_id));
+        let response = self.get_with_retry(&url, &self.base_url).await?;
+        let response_text = read_body(response).await?;
+
+        // 成功时直接返回微博对象，失败时与写操作一样返回 ok=0 与错误说明
+        let context = format!("无法解析微博 {} 的详情响应", weibo_id);
+        let resp: PrivacyResponse =
+            serde_json::from_str(&response_text).map_err(WeiboError::parse(context.clone()))?;
+        if resp.ok.is_some_and(|ok| ok != 1) {
+            return Err(WeiboError::from_response(resp.errno, resp.msg.as_deref()));
+        }
+        serde_json::from_str(&response_text).map_err(WeiboError::parse(context))
+    }
+
+    /// 设置微博隐私
+    pub async fn set_weibo_privacy(&self, weibo_id: &str, visibility: Visibility) -> Result<()> {
+        let text = self.post_modify_visible(weibo_id, visibility).await?;
+
+        // 微博 API 可能返回不同格式，我们尝试解析
+        let Ok(resp) = serde_json::from_str::<PrivacyResponse>(&text) else {
+            // 如果成功但无法解析，也视为成功
+            return Ok(());
+        };
+        if resp.ok.is_some_and(|ok| ok != 1) {
+            return Err(
+                WeiboError::from_response(resp.errno, resp.msg.as_deref()).for_visibility_change()
+            );
+        }
+
+        // ok=1 或缺少 ok 时，data 中仍可能带有逐条结果，与批量请求的判断方式一致
+        let rejected = resp
+            .data
+            .as_ref()
+            .and_then(|data| failed_ids_from_data(data, &[weibo_id]))
+            .and_then(|failed| failed.into_iter().find(|(id, _)| id == weibo_id));
+        match rejected {
+            Some((_, msg)) => Err(WeiboError::from_response(
+                resp.errno,
+                msg.as_deref().or(resp.msg.as_deref()),
+            )
+            .for_visibility_change()),
+            None => Ok(()),
+        }
+    }
+
+    /// 批量设置微博隐私
+    ///
+    /// 将多个 ID 以逗号拼接后在一次请求中提交。整批失败或响应中标明失败的 ID
+    /// 会逐条调用 `set_weibo_privacy` 重试，结果汇总在返回值中。
+    /// 一旦触发限流即停止提交，尚未完成的 ID 放入 `rate_limited`，由调用方冷却后重试。
+    /// 达到请求数上限时同样停止，尚未提交的 ID 放入 `not_sent`。
+    /// 需要在每条结果产生时立即处理的，使用 [`Self::set_weibo_privacy_batch_with`]。
+    pub async fn set_weibo_privacy_batch(
+        &self,
+        weibo_ids: &[&str],
+        visibility: Visibility,
+    ) -> BatchOutcome {
+        let mut outcome = BatchOutcome::default();
+        self.set_weibo_privacy_batch_with(weibo_ids, visibility, |id, result| {
+            outcome.record(id, result)
+        })
+        .await;
+        outcome
+    }
+
+    /// 批量设置微博隐私，每条微博有结果时调用一次 `on_result`
+    ///
+    /// 提交与重试方式同 [`Self::set_weibo_privacy_batch`]，每个 ID 恰好回调一次：
+    /// 成功为 `Ok(())`；因限流未完成的为 [`WeiboError::RateLimited`]，
+    /// 因达到请求数上限未提交的为 [`WeiboError::RequestLimit`]，其余为设置失败的原因。
+    /// 整批提交成功的部分先于逐条重试的部分回调。
+    pub async fn set_weibo_privacy_batch_with<F>(
+        &self,
+        weibo_ids: &[&str],
+        visibility: Visibility,
+        mut on_result: F,
+    ) where
+        F: FnMut(&str, Result<()>),
+    {
+        if weibo_ids.is_empty() {
+            return;
+        }
+
+        let joined = weibo_ids.join(",");
+        let retry_ids: Vec<&str> = match self.post_modify_visible(&joined, visibility).await {
+            Ok(text) => match serde_json::from_str::<PrivacyResponse>(&text) {
+                // ok=1 或缺少 ok：按 data 中的逐条结果找出失败的 ID，无法识别时整批视为成功
+                Ok(resp) if resp.ok.is_none_or(|ok| ok == 1) => {
+                    let failed = resp
+                        .data
+                        .as_ref()
+                        .and_then(|data| failed_ids_from_data(data, weibo_ids))
+                        .unwrap_or_default();
+                    weibo_ids
+                        .iter()
+                        .copied()
+                        .filter(|id| failed.iter().any(|(f, _)| f == id))
+                        .collect()
+                }
+                // ok != 1：整批视为失败
+                Ok(resp) if resp.ok.is_some() => {
+                    let error = WeiboError::from_response(resp.errno, resp.msg.as_deref());
+                    if error.is_rate_limited() {
+                        report_all(weibo_ids, &error, &mut on_result);
+                        return;
+                    }
+                    weibo_ids.to_vec()
+                }
+                // 成功但无法解析，与单条接口一致视为成功
+                _ => Vec::new(),
+            },
+            Err(e) if e.is_rate_limited() || e.is_request_limit() => {
+                report_all(weibo_ids, &e, &mut on_result);
+                return;
+            }
+            Err(_) => weibo_ids.to_vec(),
+        };
+
+        for id in weibo_ids {
+            if !retry_ids.contains(id) {
+                on_result(id, Ok(()));
+            }
+        }
+
+        // 对失败的子集逐条重试
+        for (idx, id) in retry_ids.iter().enumerate() {
+            match self.set_weibo_privacy(id, visibility).await {
+                Err(e) if e.is_rate_limited() || e.is_request_limit() => {
+                    report_all(&retry_ids[idx..], &e, &mut on_result);
+                    break;
+                }
+                result => on_result(id, result),
+            }
+        }
+    }
+
+    /// 调用 modifyVisible 接口（带重试），返回成功响应的原始内容
+    async fn post_modify_visible(&self, ids: &str, visibility: Visibility) -> Result<String> {
+        // 微博设置隐私的 API 端点（根据实际抓包结果）
+        let url = self.endpoint("/ajax/statuses/modifyVisible");
+
+        let visible_value = match visibility {
+            Visibility::Public => 0,
+            Visibility::FriendsOnly => 2,
+            Visibility::Private => 1,
+            Visibility::FansOnly => 10,
+        };
+
+        // 使用 form 格式，参数名是 ids（复数）不是 id，多个 ID 以逗号分隔
+        let visible_str = visible_value.to_string();
+        let params = [("ids", ids), ("visible", visible_str.as_str())];
+
+        trace!("设置微博隐私，参数: ids={}, visible={}", ids, visible_str);
+
+        self.post_form_with_retry(&url, &params, ids).await
+    }
+
+    /// 设置微博的评论权限，与可见性相互独立
+    pub async fn set_comment_permission(
+        &self,
+        weibo_id: &str,
+        permission: CommentPermission,
+    ) -> Result<()> {
+        // 评论管理接口（根据实际抓包结果，可能需要调整）
+        let url = self.endpoint("/ajax/statuses/comment_manage");
+        let value = (permission as u8).to_string();
+        let params = [("id", weibo_id), ("comment_type", value.as_str())];
+
+        trace!(
+            "设置评论权限，参数: id={}, comment_type={}",
+            weibo_id,
+            value
+        );
+
+        let text = self.post_form_with_retry(&url, &params, weibo_id).await?;
+        match serde_json::from_str::<PrivacyResponse>(&text) {
+            Ok(resp) if resp.ok.is_some_and(|ok| ok != 1) => {
+                Err(WeiboError::from_response(resp.errno, resp.msg.as_deref()))
+            }
+            // 与设置可见性一致，成功但无法解析时视为成功
+            _ => Ok(()),
+        }
+    }
+
+    /// 永久删除一条微博，删除后无法恢复
+    pub async fn delete_weibo(&self, weibo_id: &str) -> Result<()> {
+        let url = self.endpoint("/ajax/statuses/destroy");
+        let text = self
+            .post_form_with_retry(&url, &[("id", weibo_id)], weibo_id)
+            .await?;
+
+        let resp: PrivacyResponse = serde_json::from_str(&text).map_err(WeiboError::parse(
+            format!("无法解析删除微博 {} 的响应", weibo_id),
+        ))?;
+        if resp.ok == Some(1) {
+            Ok(())
+        } else {
+            Err(WeiboError::from_response(resp.errno, resp.msg.as_deref()))
+        }
+    }
+
+    /// 带重试的表单 POST 请求，返回成功响应的原始内容
+    ///
+    /// `label` 用于日志中标识本次请求涉及的微博。
+    /// 响应表示 XSRF-TOKEN 无效或已过期时，重新获取 token 后再请求一次。
+    async fn post_form_with_retry(
+        &self,
+        url: &str,
+        params: &[(&str, &str)],
+        label: &str,
+    ) -> Result<String> {
+        let credentials = self.credentials();
+        let text = self
+            .post_form_attempts(url, params, label, &credentials)
+            .await?;
+        if !is_token_rejected(&text) {
+            return Ok(text);
+        }
+
+        warn!(
+            "微博 {} 的请求因 XSRF-TOKEN 失效被拒绝，重新获取后重试",
+            label
+        );
+        self.refresh_xsrf_token(&credentials.xsrf_token).await?;
+        self.post_form_attempts(url, params, label, &self.credentials())
+            .await
+    }
+
+    /// 按 `max_retries` 重试的表单 POST 请求，使用给定的 Cookie 与 XSRF-TOKEN
+    async fn post_form_attempts(
+        &self,
+        url: &str,
+        params: &[(&str, &str)],
+        label: &str,
+        credentials: &Credentials,
+    ) -> Result<String> {
+        for retry in 0..self.max_retries {
+            let request = self
+                .client
+                .post(url)
+                .header("Accept", "application/json, text/plain, */*")
+                .header("Accept-Language", "zh-CN,zh;q=0.9,en;q=0.8")
+                .header("Content-Type", "application/x-www-form-urlencoded")
+                .header("Cookie", &credentials.cookie)
+                .header("X-Xsrf-Token", &credentials.xsrf_token)
+                .header("X-Requested-With", "XMLHttpRequest")
+                .header("Referer", &self.base_url)
+                .header("Origin", &self.base_url)
+                .header("Client-Version", "3.0.0")
+                .header("Sec-Fetch-Dest", "empty")
+                .header("Sec-Fetch-Mode", "cors")
+                .header("Sec-Fetch-Site", "same-origin")
+                .headers(self.extra_headers.clone())
+                .form(params);
+
+            self.before_request().await?;
+            let started = Instant::now();
+            let result = request.send().await;
+            self.latency.record(started.elapsed());
+
+            match result {
+                Ok(response) => {
+                    let status = response.status();
+
+                    if status == StatusCode::TOO_MANY_REQUESTS {
+                        return Err(WeiboError::rate_limited(format!("HTTP {}", status)));
+                    }
+
+                    if status.is_success() {
+                        let text = read_body(response).await?;
+
+                        debug!("微博 {} 响应: {}", label, truncate(&text, 200));
+
+                        return Ok(text);
+                    }
+
+                    if retry == self.max_retries - 1 {
+                        let error_body = response.text().await.unwrap_or_default();
+                        debug!("HTTP 错误 {}: {}", status, truncate(&error_body, 500));
+                        return Err(WeiboError::Http {
+                            status,
+                            body: error_body,
+                        });
+                    }
+                }
+                Err(e) => {
+                    if retry == self.max_retries - 1 {
+                        return Err(e.into());
+                    }
+                }
+            }
+
+            // 指数退避
+            let delay = self.backoff_delay(retry);
+            tokio::time::sleep(delay).await;
+        }
+
+        unreachable!()
+    }
+
+    /// 带重试的 GET 请求
+    async fn get_with_retry(&self, url: &str, referer: &str) -> Result<Response> {
+        let credentials = self.credentials();
+        for retry in 0..self.max_retries {
+            let request = self
+                .client
+                .get(url)
+                .header("Accept", "application/json, text/plain, */*")
+                .header("Accept-Language", "zh-CN,zh;q=0.9")
+                .header("Referer", referer)
+                .header("X-Requested-With", "XMLHttpRequest")
+                .header("Cookie", &credentials.cookie)
+                .header("X-Xsrf-Token", &credentials.xsrf_token)
+                .header("Accept-Encoding", "gzip, deflate, br, zstd")
+                .header("Client-Version", "v2.47.139")
+                .header("Sec-Fetch-Dest", "empty")
+                .header("Sec-Fetch-Mode", "cors")
+                .header("Sec-Fetch-Site", "same-origin")
+                .headers(self.extra_headers.clone());
+
+            self.before_request().await?;
+            let started = Instant::now();
+            let result = request.send().await;
+            self.latency.record(started.elapsed());
+
+            match result {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        return Ok(response);
+                    }
+
+                    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                        return Err(WeiboError::rate_limited(format!(
+                            "HTTP {}",
+                            response.status()
+                        )));
+                    }
+
+                    if retry == self.max_retries - 1 {
+                        let status = response.status();
+                        let error_body = response.text().await.unwrap_or_default();
+                        return Err(WeiboError::Http {
+                            status,
+                            body: error_body,
+                        });
+                    }
+                }
+                Err(e) => {
+                    if retry == self.max_retries - 1 {
+                        return Err(e.into());
+                    }
+                }
+            }
+
+            let delay = self.backoff_delay(retry);
+            tokio::time::sleep(delay).await;
         }
 
         unreachable!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::any;
+    use wiremock::{Mock, Request, ResponseTemplate};
+
+    const TEST_COOKIE: &str = "SUB=_2A25test; XSRF-TOKEN=dG9rZW4==";
+
+    /// 读取模拟服务记录下的请求
+    trait RecordedRequest {
+        fn header(&self, name: &str) -> Option<&str>;
+        fn query(&self, name: &str) -> Option<String>;
+        fn body_text(&self) -> &str;
+    }
+
+    impl RecordedRequest for Request {
+        fn header(&self, name: &str) -> Option<&str> {
+            self.headers.get(name).and_then(|v| v.to_str().ok())
+        }
+
+        fn query(&self, name: &str) -> Option<String> {
+            self.url
+                .query_pairs()
+                .find(|(n, _)| n == name)
+                .map(|(_, v)| v.into_owned())
+        }
+
+        fn body_text(&self) -> &str {
+            std::str::from_utf8(&self.body).unwrap()
+        }
+    }
+
+    fn json_response(status: u16, body: &str) -> ResponseTemplate {
+        ResponseTemplate::new(status).set_body_raw(body.to_owned(), "application/json")
+    }
+
+    /// 本地的模拟微博接口：按顺序对每个请求返回预设的响应，并记录收到的请求
+    struct MockServer {
+        base_url: String,
+        server: wiremock::MockServer,
+    }
+
+    impl MockServer {
+        async fn start(responses: Vec<(u16, &'static str)>) -> Self {
+            let responses = responses
+                .into_iter()
+                .map(|(status, body)| json_response(status, body))
+                .collect();
+            Self::start_with_templates(responses).await
+        }
+
+        /// 每个预设响应只用一次，用完后的请求得到 404
+        async fn start_with_templates(responses: Vec<ResponseTemplate>) -> Self {
+            let server = wiremock::MockServer::start().await;
+            for response in responses {
+                Mock::given(any())
+                    .respond_with(response)
+                    .up_to_n_times(1)
+                    .mount(&server)
+                    .await;
+            }
+            Self::wrap(server)
+        }
+
+        /// 按请求内容决定响应的服务，请求可同时进行
+        async fn start_routed(respond: fn(&Request) -> String) -> Self {
+            let server = wiremock::MockServer::start().await;
+            Mock::given(any())
+                .respond_with(move |request: &Request| json_response(200, &respond(request)))
+                .mount(&server)
+                .await;
+            Self::wrap(server)
+        }
+
+        fn wrap(server: wiremock::MockServer) -> Self {
+            Self {
+                base_url: server.uri(),
+                server,
+            }
+        }
+
+        /// 指向本服务的客户端，重试不等待太久
+        fn client(&self) -> WeiboPrivacyClient {
+            WeiboPrivacyClient::builder(TEST_COOKIE)
+                .base_url(&self.base_url)
+                .backoff_base(0)
+                .build()
+                .unwrap()
+        }
+
+        async fn requests(&self) -> Vec<Request> {
+            self.server.received_requests().await.unwrap()
+        }
+    }
+
+    #[test]
+    fn base_url_is_normalized() {
+        assert_eq!(
+            validate_base_url("https://weibo.com/").unwrap(),
+            "https://weibo.com"
+        );
+        assert_eq!(
+            validate_base_url("http://127.0.0.1:8080/weibo/").unwrap(),
+            "http://127.0.0.1:8080/weibo"
+        );
+        assert!(validate_base_url("ftp://weibo.com").is_err());
+        assert!(validate_base_url("weibo.com").is_err());
+        assert!(validate_base_url("https://weibo.com/?a=1").is_err());
+    }
+
+    #[tokio::test]
+    async fn base_url_with_path_prefix() {
+        let server = MockServer::start(vec![(200, r#"{"ok":1}"#)]).await;
+        let client = WeiboPrivacyClient::builder(TEST_COOKIE)
+            .base_url(format!("{}/mirror/", server.base_url))
+            .build()
+            .unwrap();
+        assert_eq!(client.base_url(), format!("{}/mirror", server.base_url));
+
+        client.delete_weibo("4890000000000001").await.unwrap();
+        let requests = server.requests().await;
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].url.path(), "/mirror/ajax/statuses/destroy");
+    }
+
+    #[test]
+    fn topic_page_keeps_own_weibos() {
+        let text = r#"{"ok":1,"data":{"cardlistInfo":{"since_id":"4890000000000100"},"cards":[
+            {"card_type":11,"card_group":[
+                {"card_type":9,"mblog":{"id":"4890000000000001","text":"a","user":{"id":42}}},
+                {"card_type":9,"mblog":{"id":"4890000000000002","text":"b","user":{"id":7}}}
+            ]},
+            {"card_type":9,"mblog":{"id":"4890000000000003","text":"c","user":{"id":"42"}}}
+        ]}}"#;
+
+        let page = parse_topic_page(text, 1, "abc", "42").unwrap();
+        let ids: Vec<&str> = page.weibos.iter().map(|w| w.id.as_str()).collect();
+        assert_eq!(ids, ["4890000000000001", "4890000000000003"]);
+        assert_eq!(page.scanned, 3);
+        assert_eq!(page.since_id.as_deref(), Some("4890000000000100"));
+    }
+
+    #[test]
+    fn topic_missing_is_an_error() {
+        let text = r#"{"ok":0,"msg":"这里还没有内容","data":{"cards":[]}}"#;
+        let error = parse_topic_page(text, 1, "abc", "42").unwrap_err();
+        assert!(error.to_string().contains("超话 abc 不存在"), "{}", error);
+
+        // 翻过最后一页时同样的响应表示结束
+        let page = parse_topic_page(text, 3, "abc", "42").unwrap();
+        assert_eq!(page.scanned, 0);
+    }
+
+    #[test]
+    fn topic_container_id_prefix() {
+        assert_eq!(topic_container_id("abc"), "100808abc");
+        assert_eq!(topic_container_id("100808abc"), "100808abc");
+    }
+
+    fn no_delay() -> FetchOptions {
+        FetchOptions {
+            page_delay: Duration::ZERO,
+            ..FetchOptions::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn get_all_weibo_ids_stops_at_empty_page() {
+        let server = MockServer::start(vec![
+            (
+                200,
+                r#"{"ok":1,"data":{"list":[{"id":101,"text":"a"},{"id":"102","text":"b"}]}}"#,
+            ),
+            (200, r#"{"ok":1,"data":{"list":[{"id":103,"text":"c"}]}}"#),
+            (200, r#"{"ok":1,"data":{"list":[]}}"#),
+        ])
+        .await;
+
+        let weibos = server
+            .client()
+            .get_all_weibo_ids("42", &no_delay())
+            .await
+            .unwrap();
+        let ids: Vec<&str> = weibos.iter().map(|w| w.id.as_str()).collect();
+        assert_eq!(ids, ["101", "102", "103"]);
+
+        let requests = server.requests().await;
+        assert_eq!(requests.len(), 3);
+        for (idx, request) in requests.iter().enumerate() {
+            let query = format!("uid=42&page={}&feature=0", idx + 1);
+            assert_eq!(request.method, "GET");
+            assert_eq!(request.url.path(), "/ajax/statuses/mymblog");
+            assert_eq!(request.url.query(), Some(query.as_str()));
+            assert_eq!(request.header("x-xsrf-token"), Some("dG9rZW4=="));
+        }
+    }
+
+    #[tokio::test]
+    async fn get_all_weibo_ids_passes_feature() {
+        let server = MockServer::start(vec![(200, r#"{"ok":1,"data":{"list":[]}}"#)]).await;
+
+        let options = FetchOptions {
+            feature: FEATURE_VIDEO,
+            ..no_delay()
+        };
+        server
+            .client()
+            .get_all_weibo_ids("42", &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            server.requests().await[0].query("feature").as_deref(),
+            Some("3")
+        );
+    }
+
+    #[tokio::test]
+    async fn get_all_weibo_ids_passes_clamped_page_size() {
+        let server = MockServer::start(vec![
+            (200, r#"{"ok":1,"data":{"list":[]}}"#),
+            (200, r#"{"ok":1,"data":{"list":[]}}"#),
+        ])
+        .await;
+        let client = server.client();
+
+        for page_size in [50, 500] {
+            let options = FetchOptions {
+                page_size: Some(page_size),
+                ..no_delay()
+            };
+            client.get_all_weibo_ids("42", &options).await.unwrap();
+        }
+        let requests = server.requests().await;
+        assert_eq!(requests[0].query("count").as_deref(), Some("50"));
+        assert_eq!(requests[1].query("count").as_deref(), Some("100"));
+    }
+
+    #[tokio::test]
+    async fn get_all_weibo_ids_respects_max_pages() {
+        let server = MockServer::start(vec![
+            (200, r#"{"ok":1,"data":{"list":[{"id":1}]}}"#),
+            (200, r#"{"ok":1,"data":{"list":[{"id":2}]}}"#),
+        ])
+        .await;
+
+        let options = FetchOptions {
+            max_pages: Some(2),
+            ..no_delay()
+        };
+        let weibos = server
+            .client()
+            .get_all_weibo_ids("42", &options)
+            .await
+            .unwrap();
+        assert_eq!(weibos.len(), 2);
+        assert_eq!(server.requests().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn get_all_weibo_ids_stops_at_after_id() {
+        let server = MockServer::start(vec![
+            (
+                200,
+                r#"{"ok":1,"data":{"list":[{"id":5,"isTop":1},{"id":30},{"id":20}]}}"#,
+            ),
+            (
+                200,
+                r#"{"ok":1,"data":{"list":[{"id":12},{"id":10},{"id":9}]}}"#,
+            ),
+            (200, r#"{"ok":1,"data":{"list":[{"id":8}]}}"#),
+        ])
+        .await;
+
+        let options = FetchOptions {
+            after_id: Some(10),
+            ..no_delay()
+        };
+        let weibos = server
+            .client()
+            .get_all_weibo_ids("42", &options)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = weibos.iter().map(|w| w.id.as_str()).collect();
+        // 置顶的旧微博不会提前停止翻页，到达 10 所在的页后不再请求第 3 页
+        assert_eq!(ids, ["30", "20", "12"]);
+        assert_eq!(server.requests().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn malformed_items_are_skipped() {
+        let server = MockServer::start(vec![
+            (
+                200,
+                r#"{"ok":1,"data":{"list":[{"id":3},{"id":{"bad":1}},{"id":1}]}}"#,
+            ),
+            (200, r#"{"ok":1,"data":{"list":[{"id":[]}]}}"#),
+            (200, r#"{"ok":1,"data":{"list":[]}}"#),
+        ])
+        .await;
+
+        let weibos = server
+            .client()
+            .get_all_weibo_ids("42", &no_delay())
+            .await
+            .unwrap();
+        let ids: Vec<&str> = weibos.iter().map(|w| w.id.as_str()).collect();
+        // 整页都无法解析时不当作最后一页，继续翻页
+        assert_eq!(ids, ["3", "1"]);
+        assert_eq!(server.requests().await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn parallel_pages_are_assembled_in_order() {
+        let server = MockServer::start_routed(|request| {
+            let page: u32 = request.query("page").unwrap().parse().unwrap();
+            match page {
+                1..=4 => format!(r#"{{"ok":1,"data":{{"list":[{{"id":{}}}]}}}}"#, page * 10),
+                _ => r#"{"ok":1,"data":{"list":[]}}"#.to_string(),
+            }
+        })
+        .await;
+
+        let options = FetchOptions {
+            parallel_pages: 4,
+            ..no_delay()
+        };
+        let weibos = server
+            .client()
+            .get_all_weibo_ids("42", &options)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = weibos.iter().map(|w| w.id.as_str()).collect();
+        assert_eq!(ids, ["10", "20", "30", "40"]);
+        // 第 1 页单独请求，之后第 2～5 页一起请求，第 5 页为空即停止
+        assert_eq!(server.requests().await.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn get_all_weibo_ids_stops_when_page_repeats() {
+        let page = r#"{"ok":1,"data":{"list":[{"id":1},{"id":2}]}}"#;
+        let server = MockServer::start(vec![(200, page), (200, page), (200, page)]).await;
+
+        let weibos = server
+            .client()
+            .get_all_weibo_ids("42", &no_delay())
+            .await
+            .unwrap();
+        assert_eq!(weibos.len(), 2);
+        assert_eq!(server.requests().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn get_all_weibo_ids_stops_after_stale_pages() {
+        let server = MockServer::start(vec![
+            (200, r#"{"ok":1,"data":{"list":[{"id":1},{"id":2}]}}"#),
+            (200, r#"{"ok":1,"data":{"list":[{"id":3}]}}"#),
+            (200, r#"{"ok":1,"data":{"list":[{"id":1}]}}"#),
+            (200, r#"{"ok":1,"data":{"list":[{"id":2}]}}"#),
+            (200, r#"{"ok":1,"data":{"list":[{"id":3}]}}"#),
+            (200, r#"{"ok":1,"data":{"list":[{"id":4}]}}"#),
+        ])
+        .await;
+
+        let weibos = server
+            .client()
+            .get_all_weibo_ids("42", &no_delay())
+            .await
+            .unwrap();
+        assert_eq!(weibos.len(), 3);
+        assert_eq!(server.requests().await.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn set_weibo_privacy_ok() {
+        let server = MockServer::start(vec![(200, r#"{"ok":1}"#)]).await;
+
+        server
+            .client()
+            .set_weibo_privacy("4890000000000001", Visibility::FriendsOnly)
+            .await
+            .unwrap();
+
+        let requests = server.requests().await;
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].url.path(), "/ajax/statuses/modifyVisible");
+        assert_eq!(requests[0].body_text(), "ids=4890000000000001&visible=2");
+        assert_eq!(requests[0].header("x-xsrf-token"), Some("dG9rZW4=="));
+        assert_eq!(requests[0].header("origin"), Some(server.base_url.as_str()));
+    }
+
+    #[test]
+    fn messages_follow_language() {
+        use crate::i18n::{text, Lang};
+
+        assert_eq!(text(Lang::Zh, "visibility.friends"), "仅好友可见");
+        assert_eq!(text(Lang::En, "visibility.friends"), "friends only");
+        assert_eq!(text(Lang::En, "summary.success"), "Succeeded");
+        // 表中没有的键原样返回
+        assert_eq!(text(Lang::En, "no.such.key"), "no.such.key");
+        // 未设置语言时默认中文
+        assert_eq!(Visibility::Private.as_str(), "仅自己可见");
+    }
+
+    #[tokio::test]
+    async fn get_weibo_reads_visibility() {
+        let server = MockServer::start(vec![
+            (
+                200,
+                r#"{"ok":1,"id":4890000000000001,"text":"a","visible":{"type":1}}"#,
+            ),
+            (200, r#"{"ok":0,"msg":"该微博不存在"}"#),
+        ])
+        .await;
+        let client = server.client();
+
+        let weibo = client.get_weibo("4890000000000001").await.unwrap();
+        assert_eq!(weibo.id, "4890000000000001");
+        assert_eq!(weibo.visibility, Some(Visibility::Private));
+        let requests = server.requests().await;
+        assert_eq!(requests[0].url.path(), "/ajax/statuses/show");
+        assert_eq!(requests[0].url.query(), Some("id=4890000000000001"));
+
+        let error = client.get_weibo("4890000000000002").await.unwrap_err();
+        assert!(error.is_not_found());
+    }
+
+    #[tokio::test]
+    async fn failure_categories_follow_error_kind() {
+        // 端口 1 上没有服务，连接被拒绝
+        let client = WeiboPrivacyClient::builder(TEST_COOKIE)
+            .base_url("http://127.0.0.1:1")
+            .max_retries(0)
+            .build()
+            .unwrap();
+        let error = client
+            .set_weibo_privacy("100", Visibility::Private)
+            .await
+            .unwrap_err();
+        assert_eq!(error.category(), FailureCategory::Network);
+
+        let forbidden = WeiboError::Http {
+            status: StatusCode::FORBIDDEN,
+            body: String::new(),
+        };
+        assert_eq!(forbidden.category(), FailureCategory::Auth);
+        let unavailable = WeiboError::Http {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            body: String::new(),
+        };
+        assert_eq!(unavailable.category(), FailureCategory::Network);
+        assert_eq!(
+            WeiboError::from_response(Some(-100), None).category(),
+            FailureCategory::Auth
+        );
+        assert_eq!(
+            WeiboError::from_response(None, Some("参数错误")).category(),
+            FailureCategory::ApiRejected
+        );
+        assert_eq!(
+            WeiboError::Decode("br".to_string()).category(),
+            FailureCategory::Unknown
+        );
+    }
+
+    #[tokio::test]
+    async fn set_weibo_privacy_rejected_with_message() {
+        let server = MockServer::start(vec![(200, r#"{"ok":0,"msg":"参数错误"}"#)]).await;
+
+        let error = server
+            .client()
+            .set_weibo_privacy("4890000000000001", Visibility::Private)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, WeiboError::Api { .. }), "{:?}", error);
+        assert!(error.to_string().contains("参数错误"), "{}", error);
+        assert_eq!(server.requests().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn set_weibo_privacy_rejected_in_per_id_map() {
+        let server = MockServer::start(vec![(
+            200,
+            r#"{"ok":1,"data":{"4890000000000001":{"ok":0,"msg":"该微博不支持设置为仅粉丝可见"}}}"#,
+        )])
+        .await;
+
+        let error = server
+            .client()
+            .set_weibo_privacy("4890000000000001", Visibility::FansOnly)
+            .await
+            .unwrap_err();
+
+        assert!(error.is_unsupported(), "{:?}", error);
+        assert_eq!(server.requests().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn set_weibo_privacy_accepted_in_per_id_map() {
+        let server =
+            MockServer::start(vec![(200, r#"{"ok":1,"data":{"4890000000000001":1}}"#)]).await;
+
+        server
+            .client()
+            .set_weibo_privacy("4890000000000001", Visibility::Private)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn set_weibo_privacy_refreshes_expired_xsrf_token() {
+        let server = MockServer::start_with_templates(vec![
+            json_response(200, r#"{"ok":0,"msg":"token 已过期"}"#),
+            ResponseTemplate::new(200)
+                .insert_header("Set-Cookie", "XSRF-TOKEN=bmV3; Path=/")
+                .set_body_string("<html></html>"),
+            json_response(200, r#"{"ok":1}"#),
+        ])
+        .await;
+        let client = server.client();
+
+        client
+            .set_weibo_privacy("100", Visibility::Private)
+            .await
+            .unwrap();
+        assert_eq!(client.cookie(), "SUB=_2A25test; XSRF-TOKEN=bmV3");
+
+        let requests = server.requests().await;
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[1].method, "GET");
+        assert_eq!(requests[1].url.path(), "/");
+        assert_eq!(requests[2].header("x-xsrf-token"), Some("bmV3"));
+        assert_eq!(
+            requests[2].header("cookie"),
+            Some("SUB=_2A25test; XSRF-TOKEN=bmV3")
+        );
+    }
+
+    #[tokio::test]
+    async fn set_weibo_privacy_fails_when_token_cannot_be_refreshed() {
+        let server = MockServer::start(vec![
+            (200, r#"{"ok":0,"msg":"token 已过期"}"#),
+            (200, "<html></html>"),
+        ])
+        .await;
+
+        let error = server
+            .client()
+            .set_weibo_privacy("100", Visibility::Private)
+            .await
+            .unwrap_err();
+        assert!(error.is_token_expired(), "{:?}", error);
+        assert_eq!(server.requests().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn set_weibo_privacy_batch_partial_per_id_map() {
+        let server = MockServer::start(vec![
+            (
+                200,
+                r#"{"ok":1,"data":{"1001":1,"1002":0,"1003":{"ok":0,"msg":"不支持"}}}"#,
+            ),
+            (200, r#"{"ok":0,"msg":"参数错误"}"#),
+            (200, r#"{"ok":0,"msg":"该微博不支持设置为仅粉丝可见"}"#),
+        ])
+        .await;
+
+        let outcome = server
+            .client()
+            .set_weibo_privacy_batch(&["1001", "1002", "1003"], Visibility::FansOnly)
+            .await;
+
+        assert_eq!(outcome.succeeded, vec!["1001"]);
+        assert_eq!(outcome.failed.len(), 1);
+        assert_eq!(outcome.failed[0].0, "1002");
+        assert_eq!(outcome.failed[0].1, FailureCategory::ApiRejected);
+        assert!(
+            outcome.failed[0].2.contains("参数错误"),
+            "{}",
+            outcome.failed[0].2
+        );
+        assert_eq!(outcome.unsupported, vec!["1003"]);
+
+        let requests = server.requests().await;
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].body_text(), "ids=1001%2C1002%2C1003&visible=10");
+        assert_eq!(requests[1].body_text(), "ids=1002&visible=10");
+        assert_eq!(requests[2].body_text(), "ids=1003&visible=10");
+    }
+
+    #[tokio::test]
+    async fn set_weibo_privacy_batch_partial_fail_ids() {
+        let server = MockServer::start(vec![
+            (200, r#"{"ok":1,"data":{"fail_ids":[1002]}}"#),
+            (200, r#"{"ok":0,"msg":"参数错误"}"#),
+        ])
+        .await;
+
+        let outcome = server
+            .client()
+            .set_weibo_privacy_batch(&["1001", "1002"], Visibility::Private)
+            .await;
+
+        assert_eq!(outcome.succeeded, vec!["1001"]);
+        assert_eq!(outcome.failed.len(), 1);
+        assert_eq!(outcome.failed[0].0, "1002");
+        assert_eq!(server.requests().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn set_weibo_privacy_batch_separates_deleted_weibos() {
+        let server = MockServer::start(vec![
+            (200, r#"{"ok":0,"msg":"部分微博不存在"}"#),
+            (200, r#"{"ok":1}"#),
+            (
+                200,
+                r#"{"ok":0,"errno":20101,"msg":"微博不存在或暂无查看权限"}"#,
+            ),
+        ])
+        .await;
+
+        let outcome = server
+            .client()
+            .set_weibo_privacy_batch(&["1001", "1002"], Visibility::Private)
+            .await;
+
+        assert_eq!(outcome.succeeded, vec!["1001"]);
+        assert_eq!(outcome.missing, vec!["1002"]);
+        assert!(outcome.failed.is_empty(), "{:?}", outcome.failed);
+    }
+
+    #[tokio::test]
+    async fn set_weibo_privacy_batch_with_reports_each_id_once() {
+        let server = MockServer::start(vec![
+            (200, r#"{"ok":1,"data":{"fail_ids":[1002,1003]}}"#),
+            (200, r#"{"ok":0,"errno":10023,"msg":"操作过于频繁"}"#),
+        ])
+        .await;
+
+        let mut results = Vec::new();
+        server
+            .client()
+            .set_weibo_privacy_batch_with(
+                &["1001", "1002", "1003"],
+                Visibility::Private,
+                |id, result| results.push((id.to_string(), result)),
+            )
+            .await;
+
+        // 1002 逐条重试时触发限流，不再提交 1003，两者都报告为限流
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].0, "1001");
+        assert!(results[0].1.is_ok());
+        for (id, result) in &results[1..] {
+            assert!(
+                result.as_ref().is_err_and(WeiboError::is_rate_limited),
+                "{}: {:?}",
+                id,
+                result
+            );
+        }
+        assert_eq!(server.requests().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_spaces_concurrent_requests() {
+        let server = MockServer::start(vec![
+            (200, r#"{"ok":1}"#),
+            (200, r#"{"ok":1}"#),
+            (200, r#"{"ok":1}"#),
+        ])
+        .await;
+        // 每分钟 600 次，即每 100ms 一次
+        let client = WeiboPrivacyClient::builder(TEST_COOKIE)
+            .base_url(&server.base_url)
+            .rate_limit(600)
+            .build()
+            .unwrap();
+
+        let started = Instant::now();
+        let (a, b, c) = tokio::join!(
+            client.set_weibo_privacy("1001", Visibility::Private),
+            client.set_weibo_privacy("1002", Visibility::Private),
+            client.set_weibo_privacy("1003", Visibility::Private),
+        );
+        assert!(a.is_ok() && b.is_ok() && c.is_ok());
+        // 第一个请求立即发出，其余两个依次等待一个间隔
+        assert!(
+            started.elapsed() >= Duration::from_millis(200),
+            "{:?}",
+            started.elapsed()
+        );
+        assert_eq!(server.requests().await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn max_requests_stops_further_requests() {
+        let server = MockServer::start(vec![(500, "internal error"), (200, r#"{"ok":1}"#)]).await;
+        let client = WeiboPrivacyClient::builder(TEST_COOKIE)
+            .base_url(&server.base_url)
+            .backoff_base(0)
+            .max_requests(2)
+            .build()
+            .unwrap();
+
+        // 重试也计入请求数
+        client
+            .set_weibo_privacy("1001", Visibility::Private)
+            .await
+            .unwrap();
+        assert!(!client.request_limit_hit());
+
+        let error = client
+            .set_weibo_privacy("1002", Visibility::Private)
+            .await
+            .unwrap_err();
+        assert!(error.is_request_limit(), "{:?}", error);
+        assert!(client.request_limit_hit());
+        assert_eq!(client.request_count(), 2);
+        assert_eq!(server.requests().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn max_requests_leaves_batch_not_sent() {
+        let server =
+            MockServer::start(vec![(200, r#"{"ok":1,"data":{"fail_ids":["1002"]}}"#)]).await;
+        let client = WeiboPrivacyClient::builder(TEST_COOKIE)
+            .base_url(&server.base_url)
+            .max_requests(1)
+            .build()
+            .unwrap();
+
+        let outcome = client
+            .set_weibo_privacy_batch(&["1001", "1002"], Visibility::Private)
+            .await;
+
+        assert_eq!(outcome.succeeded, vec!["1001"]);
+        assert_eq!(outcome.not_sent, vec!["1002"]);
+        assert!(outcome.failed.is_empty());
+
+        let outcome = client
+            .set_weibo_privacy_batch(&["1003"], Visibility::Private)
+            .await;
+        assert_eq!(outcome.not_sent, vec!["1003"]);
+        assert_eq!(server.requests().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn user_agent_is_sent_with_every_request() {
+        let server = MockServer::start(vec![(200, r#"{"ok":1}"#)]).await;
+        let firefox = user_agent_preset("Firefox").unwrap();
+        let client = WeiboPrivacyClient::builder(TEST_COOKIE)
+            .base_url(&server.base_url)
+            .user_agent(firefox)
+            .build()
+            .unwrap();
+        assert_eq!(client.user_agent(), firefox);
+
+        client
+            .set_weibo_privacy("1001", Visibility::Private)
+            .await
+            .unwrap();
+        assert_eq!(
+            server.requests().await[0].header("user-agent"),
+            Some(firefox)
+        );
+    }
+
+    #[test]
+    fn user_agent_presets() {
+        assert_eq!(user_agent_preset("chrome"), Some(DEFAULT_USER_AGENT));
+        assert!(user_agent_preset("SAFARI").unwrap().contains("Version/"));
+        assert_eq!(user_agent_preset("netscape"), None);
+    }
+
+    #[tokio::test]
+    async fn cookie_and_extra_headers_are_sent() {
+        let server = MockServer::start(vec![(200, r#"{"ok":1}"#), (200, r#"{"ok":1}"#)]).await;
+        let cookie = "SUB=_2A25test; WBPSESS=sess; SUBP=0033; XSRF-TOKEN=dG9rZW4==";
+        let client = WeiboPrivacyClient::builder(cookie)
+            .base_url(&server.base_url)
+            .header("x-s", "abc123")
+            .header("Client-Version", "9.9.9")
+            .build()
+            .unwrap();
+
+        client
+            .set_weibo_privacy("1001", Visibility::Private)
+            .await
+            .unwrap();
+        client.get_long_text("1001").await.ok();
+
+        let requests = server.requests().await;
+        assert_eq!(requests.len(), 2);
+        for request in requests.iter() {
+            assert_eq!(request.header("cookie"), Some(cookie));
+            assert_eq!(request.header("x-s"), Some("abc123"));
+            // 同名的内置请求头被替换，而不是重复发送
+            assert_eq!(request.header("client-version"), Some("9.9.9"));
+            assert_eq!(request.headers.get_all("client-version").iter().count(), 1);
+        }
+    }
+
+    #[test]
+    fn invalid_extra_header_is_rejected() {
+        let result = WeiboPrivacyClient::builder(TEST_COOKIE)
+            .header("bad header", "x")
+            .build();
+        assert!(matches!(result, Err(WeiboError::Config(_))));
+    }
+
+    #[test]
+    fn header_line_parsing() {
+        assert_eq!(
+            parse_header_line("x-s: abc 123").unwrap(),
+            ("x-s".to_string(), "abc 123".to_string())
+        );
+        assert!(parse_header_line("no-colon").is_err());
+        assert!(parse_header_line(": value").is_err());
+    }
+
+    #[test]
+    fn missing_required_cookies() {
+        assert_eq!(
+            missing_cookies("SUB=a; WBPSESS=b; XSRF-TOKEN=c"),
+            Vec::<&str>::new()
+        );
+        assert_eq!(missing_cookies("SUB=a; XSRF-TOKEN=c"), vec!["WBPSESS"]);
+        assert_eq!(missing_cookies("XSRF-TOKEN=c"), vec!["SUB", "WBPSESS"]);
+    }
+
+    #[test]
+    fn engagement_counts_are_parsed() {
+        let weibo: WeiboInfo = serde_json::from_str(
+            r#"{"id":1,"reposts_count":12,"comments_count":"34","attitudes_count":"1.5万+"}"#,
+        )
+        .unwrap();
+        assert_eq!(weibo.reposts_count, Some(12));
+        assert_eq!(weibo.comments_count, Some(34));
+        assert_eq!(weibo.attitudes_count, Some(15_000));
+
+        let weibo: WeiboInfo =
+            serde_json::from_str(r#"{"id":2,"reposts_count":null,"attitudes_count":"很多"}"#)
+                .unwrap();
+        assert_eq!(weibo.reposts_count, None);
+        assert_eq!(weibo.comments_count, None);
+        assert_eq!(weibo.attitudes_count, None);
+        assert_eq!(parse_count("100万+"), Some(1_000_000));
+        assert_eq!(parse_count("1亿"), Some(100_000_000));
+    }
+
+    #[test]
+    fn media_is_classified_from_own_and_reposted_content() {
+        let parse = |json: &str| serde_json::from_str::<WeiboInfo>(json).unwrap();
+
+        let text = parse(r#"{"id":1,"pic_ids":[],"page_info":{"type":"article"}}"#);
+        assert!(!text.has_media && !text.is_repost);
+
+        let pics = parse(r#"{"id":2,"pic_ids":["a1","b2"]}"#);
+        assert!(pics.has_media);
+
+        let video = parse(r#"{"id":3,"page_info":{"type":11,"object_type":"video"}}"#);
+        assert!(video.has_media);
+
+        let repost = parse(r#"{"id":4,"pic_ids":[],"retweeted_status":{"id":5,"pic_ids":["c3"]}}"#);
+        assert!(repost.is_repost && !repost.has_media && repost.repost_has_media);
+
+        let deleted_original = parse(r#"{"id":6,"retweeted_status":{}}"#);
+        assert!(deleted_original.is_repost && !deleted_original.repost_has_media);
+
+        let not_repost = parse(r#"{"id":7,"retweeted_status":null}"#);
+        assert!(!not_repost.is_repost);
+    }
+
+    #[test]
+    fn mid_to_bid_matches_known_pairs() {
+        assert_eq!(mid_to_bid("3501756485200075").as_deref(), Some("z0JH2lOMb"));
+        // 中间一组不足 4 位时补 0
+        assert_eq!(mid_to_bid("10000001").as_deref(), Some("10001"));
+        assert_eq!(mid_to_bid("62").as_deref(), Some("10"));
+        assert_eq!(mid_to_bid(""), None);
+        assert_eq!(mid_to_bid("12a4"), None);
+
+        let mut weibo = WeiboInfo::from_id("3501756485200075".to_string());
+        assert_eq!(weibo.permalink(), None);
+        weibo.user_id = Some("1642591402".to_string());
+        assert_eq!(
+            weibo.permalink().as_deref(),
+            Some("https://weibo.com/1642591402/z0JH2lOMb")
+        );
+    }
+
+    #[test]
+    fn media_urls_and_source_are_parsed() {
+        let parse = |json: &str| serde_json::from_str::<WeiboInfo>(json).unwrap();
+
+        let desktop = parse(
+            r#"{"id":1,"pic_ids":["p1","p2"],
+                "pic_infos":{"p2":{"large":{"url":"https://img/large/p2.jpg"}},
+                             "p1":{"bmiddle":{"url":"https://img/bmiddle/p1.jpg"},
+                                   "largest":{"url":"https://img/largest/p1.jpg"}}},
+                "source":"<a href=\"https://app.weibo.com\" rel=\"nofollow\">iPhone客户端</a>"}"#,
+        );
+        assert_eq!(
+            desktop.pic_urls,
+            vec!["https://img/largest/p1.jpg", "https://img/large/p2.jpg"]
+        );
+        assert_eq!(desktop.source.as_deref(), Some("iPhone客户端"));
+
+        let ids_only = parse(r#"{"id":2,"pic_ids":["p3"],"source":""}"#);
+        assert_eq!(
+            ids_only.pic_urls,
+            vec!["https://wx1.sinaimg.cn/large/p3.jpg"]
+        );
+        assert_eq!(ids_only.source, None);
+
+        let mobile = parse(
+            r#"{"id":3,"pics":[{"pid":"p4","url":"https://img/orj360/p4.jpg",
+                                "large":{"url":"https://img/large/p4.jpg"}},
+                               {"pid":"p5","url":"https://img/orj360/p5.jpg"}]}"#,
+        );
+        assert_eq!(
+            mobile.pic_urls,
+            vec!["https://img/large/p4.jpg", "https://img/orj360/p5.jpg"]
+        );
+
+        let video = parse(
+            r#"{"id":4,"page_info":{"type":"video","media_info":
+                {"stream_url":"https://video/ld.mp4","mp4_hd_url":"https://video/hd.mp4"}}}"#,
+        );
+        assert_eq!(video.video_url.as_deref(), Some("https://video/hd.mp4"));
+        assert!(video.pic_urls.is_empty());
+    }
+
+    #[test]
+    fn failed_ids_from_data_shapes() {
+        let ids = ["1001", "1002", "1003"];
+        let failed = |data: &str| {
+            let data: serde_json::Value = serde_json::from_str(data).unwrap();
+            failed_ids_from_data(&data, &ids)
+                .map(|list| list.into_iter().map(|(id, _)| id).collect::<Vec<_>>())
+        };
+
+        assert_eq!(
+            failed(r#"{"failed_ids":["1002"]}"#),
+            Some(vec!["1002".to_string()])
+        );
+        assert_eq!(
+            failed(r#"{"succ_ids":[1001,1003]}"#),
+            Some(vec!["1002".to_string()])
+        );
+        assert_eq!(
+            failed(r#"{"1001":true,"1002":"1","1003":false}"#),
+            Some(vec!["1003".to_string()])
+        );
+        assert_eq!(
+            failed(r#"[{"id":"1001","ok":1},{"mid":1002,"result":false},{"idstr":"1003","ok":1}]"#),
+            Some(vec!["1002".to_string()])
+        );
+        assert_eq!(
+            failed(r#"{"list":[{"id":"1001","ok":1},{"id":"1002","ok":1}]}"#),
+            Some(vec!["1003".to_string()])
+        );
+        assert_eq!(failed(r#"{"total":3}"#), None);
+        assert_eq!(failed(r#"[]"#), None);
+    }
+
+    const LOGIN_PAGE: &str =
+        "<!DOCTYPE html><html><head><title>新浪通行证</title></head><body>请登录</body></html>";
+
+    #[tokio::test]
+    async fn get_all_weibo_ids_detects_login_page() {
+        let server = MockServer::start(vec![(200, LOGIN_PAGE)]).await;
+
+        let error = server
+            .client()
+            .get_all_weibo_ids("42", &no_delay())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, WeiboError::NotLoggedIn { .. }),
+            "{:?}",
+            error
+        );
+        assert!(error.to_string().contains("Cookie 可能已过期"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn get_all_weibo_ids_detects_empty_body() {
+        let server = MockServer::start(vec![(200, "")]).await;
+
+        let error = server
+            .client()
+            .get_all_weibo_ids("42", &no_delay())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, WeiboError::NotLoggedIn { .. }),
+            "{:?}",
+            error
+        );
+    }
+
+    #[tokio::test]
+    async fn set_weibo_privacy_detects_login_page() {
+        let server = MockServer::start(vec![(200, LOGIN_PAGE)]).await;
+
+        let error = server
+            .client()
+            .set_weibo_privacy("4890000000000001", Visibility::Private)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, WeiboError::NotLoggedIn { .. }),
+            "{:?}",
+            error
+        );
+    }
+
+    #[tokio::test]
+    async fn set_weibo_privacy_retries_after_server_error() {
+        let server = MockServer::start(vec![(500, "internal error"), (200, r#"{"ok":1}"#)]).await;
+
+        server
+            .client()
+            .set_weibo_privacy("4890000000000001", Visibility::Private)
+            .await
+            .unwrap();
+
+        assert_eq!(server.requests().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn set_weibo_privacy_gives_up_after_max_retries() {
+        let server = MockServer::start(vec![(500, "internal error"), (500, "still broken")]).await;
+        let client = WeiboPrivacyClient::builder(TEST_COOKIE)
+            .base_url(&server.base_url)
+            .backoff_base(0)
+            .max_retries(2)
+            .build()
+            .unwrap();
+
+        let error = client
+            .set_weibo_privacy("4890000000000001", Visibility::Private)
+            .await
+            .unwrap_err();
+
+        match error {
+            WeiboError::Http { status, ref body } => {
+                assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+                assert_eq!(body, "still broken");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert_eq!(server.requests().await.len(), 2);
+    }
+
+    fn xsrf(cookie: &str) -> Option<String> {
+        WeiboPrivacyClient::extract_xsrf_token(cookie)
+    }
+
+    #[test]
+    fn xsrf_token_plain() {
+        let cookie = "SUB=_2A25abc; XSRF-TOKEN=pM1Ya8sK2dWq3Jf9bTzXvNc; WBPSESS=xyz";
+        assert_eq!(xsrf(cookie).as_deref(), Some("pM1Ya8sK2dWq3Jf9bTzXvNc"));
+    }
+
+    #[test]
+    fn xsrf_token_keeps_base64_padding() {
+        let cookie = "SUB=_2A25abc; XSRF-TOKEN=aGVsbG8gd2VpYm8=; SUBP=0033";
+        assert_eq!(xsrf(cookie).as_deref(), Some("aGVsbG8gd2VpYm8="));
+
+        let cookie = "XSRF-TOKEN=dG9rZW4==";
+        assert_eq!(xsrf(cookie).as_deref(), Some("dG9rZW4=="));
+    }
+
+    #[test]
+    fn xsrf_token_url_encoded() {
+        let cookie = "SUB=_2A25abc; XSRF-TOKEN=dG9rZW4%3D%3D; WBPSESS=xyz";
+        assert_eq!(xsrf(cookie).as_deref(), Some("dG9rZW4=="));
+
+        let cookie = "XSRF-TOKEN=a%2Bb%2Fc%3D";
+        assert_eq!(xsrf(cookie).as_deref(), Some("a+b/c="));
+    }
+
+    #[test]
+    fn xsrf_token_trims_whitespace_and_quotes() {
+        let cookie = "SUB=_2A25abc;   XSRF-TOKEN= \"Ab12Cd34\" ;WBPSESS=xyz";
+        assert_eq!(xsrf(cookie).as_deref(), Some("Ab12Cd34"));
+    }
+
+    #[test]
+    fn xsrf_token_missing_or_empty() {
+        assert_eq!(xsrf("SUB=_2A25abc; WBPSESS=xyz"), None);
+        assert_eq!(xsrf("SUB=_2A25abc; XSRF-TOKEN=; WBPSESS=xyz"), None);
+        assert_eq!(xsrf(""), None);
+    }
+
+    #[test]
+    fn xsrf_token_uses_last_of_several() {
+        let cookie = "XSRF-TOKEN=old; SUB=_2A25abc; XSRF-TOKEN=new; XSRF-TOKEN=";
+        assert_eq!(xsrf(cookie).as_deref(), Some("new"));
+        assert_eq!(
+            replace_cookie_value(cookie, "XSRF-TOKEN", "fresh"),
+            "XSRF-TOKEN=fresh; SUB=_2A25abc"
+        );
+        assert_eq!(
+            replace_cookie_value("SUB=a; X-XSRF-TOKEN=b", "XSRF-TOKEN", "c"),
+            "SUB=a; X-XSRF-TOKEN=b; XSRF-TOKEN=c"
+        );
+    }
+
+    #[test]
+    fn xsrf_token_ignores_similar_names() {
+        let cookie = "X-XSRF-TOKEN=wrong; XSRF-TOKEN=right";
+        assert_eq!(xsrf(cookie).as_deref(), Some("right"));
+    }
+
+    #[test]
+    fn percent_decode_keeps_invalid_escapes() {
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+        assert_eq!(percent_decode("%E5%BE%AE%E5%8D%9A"), "微博");
+    }
+}